@@ -0,0 +1,217 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use helium_api::{
+    BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1, BlockchainTxnCreateHtlcV1,
+    BlockchainTxnOuiV1, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2, BlockchainTxnRedeemHtlcV1,
+    BlockchainTxnSecurityExchangeV1, BlockchainTxnStakeValidatorV1, BlockchainTxnTokenBurnV1,
+    BlockchainTxnTransferHotspotV1, BlockchainTxnTransferValidatorStakeV1,
+    BlockchainTxnUnstakeValidatorV1, Payment,
+};
+use helium_wallet::{
+    cmd::pay::calculate_remaining_hnt,
+    keypair::Keypair,
+    traits::{TxnFee, TxnFeeConfig},
+};
+
+/// $1.00 per HNT in the oracle price unit of 1/100_000_000 USD
+const ORACLE_PRICE: u64 = 100_000_000;
+/// The balance of the payer in bones
+const BALANCE: u64 = 100_000_000_000;
+
+fn fee_config() -> TxnFeeConfig {
+    TxnFeeConfig::with_txn_fees(5000, 24)
+}
+
+fn address() -> Vec<u8> {
+    Keypair::default().public_key().to_vec()
+}
+
+fn payment_v2(payees: usize) -> BlockchainTxnPaymentV2 {
+    BlockchainTxnPaymentV2 {
+        payer: address(),
+        payments: (0..payees)
+            .map(|_| Payment {
+                payee: address(),
+                amount: 100_000_000,
+            })
+            .collect(),
+        nonce: 1,
+        ..Default::default()
+    }
+}
+
+macro_rules! bench_txn_fee {
+    ($c:expr, $name:expr, $txn:expr) => {
+        let txn = $txn;
+        let config = fee_config();
+        $c.bench_function($name, |b| {
+            b.iter(|| black_box(&txn).txn_fee(black_box(&config)).unwrap())
+        });
+    };
+}
+
+fn txn_fee(c: &mut Criterion) {
+    bench_txn_fee!(
+        c,
+        "txn_fee payment_v1",
+        BlockchainTxnPaymentV1 {
+            payer: address(),
+            payee: address(),
+            amount: 100_000_000,
+            nonce: 1,
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(c, "txn_fee payment_v2 1 payee", payment_v2(1));
+    bench_txn_fee!(c, "txn_fee payment_v2 10 payees", payment_v2(10));
+    bench_txn_fee!(
+        c,
+        "txn_fee create_htlc_v1",
+        BlockchainTxnCreateHtlcV1 {
+            payer: address(),
+            payee: address(),
+            address: address(),
+            hashlock: vec![0; 32],
+            timelock: 1000,
+            amount: 100_000_000,
+            nonce: 1,
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee redeem_htlc_v1",
+        BlockchainTxnRedeemHtlcV1 {
+            payee: address(),
+            address: address(),
+            preimage: vec![0; 32],
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee security_exchange_v1",
+        BlockchainTxnSecurityExchangeV1 {
+            payer: address(),
+            payee: address(),
+            amount: 100_000_000,
+            nonce: 1,
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee token_burn_v1",
+        BlockchainTxnTokenBurnV1 {
+            payer: address(),
+            payee: address(),
+            amount: 100_000_000,
+            nonce: 1,
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee add_gateway_v1",
+        BlockchainTxnAddGatewayV1 {
+            owner: address(),
+            gateway: address(),
+            payer: address(),
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee assert_location_v1",
+        BlockchainTxnAssertLocationV1 {
+            owner: address(),
+            gateway: address(),
+            payer: address(),
+            location: "8c283475d4e89ff".to_string(),
+            nonce: 1,
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee oui_v1",
+        BlockchainTxnOuiV1 {
+            owner: address(),
+            payer: address(),
+            addresses: vec![address()],
+            oui: 1,
+            requested_subnet_size: 8,
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee transfer_hotspot_v1",
+        BlockchainTxnTransferHotspotV1 {
+            gateway: address(),
+            seller: address(),
+            buyer: address(),
+            buyer_nonce: 1,
+            amount_to_seller: 100_000_000,
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee stake_validator_v1",
+        BlockchainTxnStakeValidatorV1 {
+            address: address(),
+            owner: address(),
+            stake: 1_000_000_000_000,
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee unstake_validator_v1",
+        BlockchainTxnUnstakeValidatorV1 {
+            address: address(),
+            owner: address(),
+            ..Default::default()
+        }
+    );
+    bench_txn_fee!(
+        c,
+        "txn_fee transfer_validator_stake_v1",
+        BlockchainTxnTransferValidatorStakeV1 {
+            old_address: address(),
+            new_address: address(),
+            old_owner: address(),
+            new_owner: address(),
+            amount: 100_000_000,
+            ..Default::default()
+        }
+    );
+}
+
+/// The sweep computation of pay: the sweep payment starts at the balance to
+/// calculate an upper bound of the fee and is then set to what remains of
+/// the balance after the fee.
+fn sweep(txn: &mut BlockchainTxnPaymentV2, config: &TxnFeeConfig, dc_balance: u64) -> u64 {
+    let last = txn.payments.len() - 1;
+    txn.payments[last].amount = BALANCE;
+    txn.fee = txn.txn_fee(config).unwrap();
+    let remaining = calculate_remaining_hnt(BALANCE, dc_balance, txn.fee, ORACLE_PRICE, 0).unwrap();
+    txn.payments[last].amount = remaining;
+    remaining
+}
+
+fn sweep_fee(c: &mut Criterion) {
+    let config = fee_config();
+    for payees in &[1, 10] {
+        let mut txn = payment_v2(*payees);
+        c.bench_function(&format!("sweep {} payees from dc", payees), |b| {
+            b.iter(|| sweep(black_box(&mut txn), &config, u64::MAX))
+        });
+        c.bench_function(&format!("sweep {} payees hnt burn", payees), |b| {
+            b.iter(|| sweep(black_box(&mut txn), &config, 0))
+        });
+    }
+}
+
+criterion_group!(benches, txn_fee, sweep_fee);
+criterion_main!(benches);
@@ -0,0 +1,327 @@
+use crate::{
+    result::{anyhow, Result},
+    traits::B64,
+};
+use helium_api::{Account, BlockchainTxn, Hotspot, PendingTxnStatus};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{fs, path::Path, sync::Mutex, time::Duration};
+
+/// The default timeout for API requests
+pub const DEFAULT_TIMEOUT: u64 = 120;
+
+lazy_static! {
+    // The TLS settings of all clients, set once from the global options
+    // before a command runs
+    static ref TLS: Mutex<Tls> = Mutex::new(Tls::default());
+}
+
+/// TLS settings for connections to the API, for private API deployments
+/// with a self-signed or privately issued certificate
+#[derive(Clone, Default)]
+pub struct Tls {
+    /// Accept any server certificate without verifying it
+    pub allow_insecure: bool,
+    /// An additional root certificate to verify the server with
+    pub ca_cert: Option<reqwest::Certificate>,
+}
+
+impl Tls {
+    /// Reads a PEM or DER encoded CA certificate from the given file
+    pub fn read_ca_cert(path: &Path) -> Result<reqwest::Certificate> {
+        let data = fs::read(path)
+            .map_err(|e| anyhow!("Failed to read CA certificate {}: {}", path.display(), e))?;
+        let cert = if data.starts_with(b"-----BEGIN") {
+            reqwest::Certificate::from_pem(&data)
+        } else {
+            reqwest::Certificate::from_der(&data)
+        };
+        cert.map_err(|e| anyhow!("Invalid CA certificate {}: {}", path.display(), e))
+    }
+}
+
+/// Sets the TLS settings used by clients created afterwards
+pub fn set_tls(tls: Tls) {
+    if let Ok(mut current) = TLS.lock() {
+        *current = tls;
+    }
+}
+
+/// A client for the Helium API. The helium_api crate provides the types of
+/// the API, but its client can not be configured with the TLS settings for
+/// private API deployments, so all requests go through this client.
+pub struct Client {
+    base_url: String,
+    client: reqwest::blocking::Client,
+}
+
+/// A single page of results from a paged API endpoint. The cursor is used
+/// to request the next page of results and is absent on the last page.
+#[derive(Deserialize, Debug)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub cursor: Option<String>,
+}
+
+/// The oracle price of HNT in 1/100_000_000 USD at a given block
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct OraclePrice {
+    pub price: u64,
+    pub block: u64,
+    pub timestamp: Option<String>,
+}
+
+/// A predicted oracle price of HNT in 1/100_000_000 USD that takes effect
+/// at the given time in seconds since the unix epoch
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct OraclePrediction {
+    pub price: u64,
+    pub time: u64,
+}
+
+/// The status of a submitted transaction. The status is one of
+/// "received", "pending", "cleared" or "failed".
+#[derive(Deserialize, Debug)]
+pub struct PendingTxn {
+    pub hash: String,
+    pub status: String,
+    pub failed_reason: Option<String>,
+}
+
+/// The chain state of a validator. The stake status is one of "staked",
+/// "cooldown" or "unstaked".
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Validator {
+    pub address: String,
+    pub owner: String,
+    pub stake: u64,
+    pub stake_status: String,
+    pub last_heartbeat: u64,
+    pub version_heartbeat: u64,
+    #[serde(default)]
+    pub penalty: f64,
+    pub block: u64,
+}
+
+/// The block height of the chain or of the block a transaction is in
+#[derive(Deserialize, Debug)]
+struct Height {
+    height: u64,
+}
+
+/// The most recently assigned OUI
+#[derive(Deserialize, Debug)]
+struct Oui {
+    oui: u64,
+}
+
+#[derive(Deserialize)]
+struct Data<T> {
+    data: T,
+}
+
+impl Client {
+    /// Create a new client using a given base URL and a default
+    /// timeout. The library will use absoluate paths based on this
+    /// base_url.
+    pub fn new_with_base_url(base_url: String) -> Result<Self> {
+        Self::new_with_timeout(base_url, DEFAULT_TIMEOUT)
+    }
+
+    /// Create a new client using a given base URL, and request
+    /// timeout value.  The library will use absoluate paths based on
+    /// the given base_url.
+    pub fn new_with_timeout(base_url: String, timeout: u64) -> Result<Self> {
+        Self::new_with_timeouts(base_url, None, Duration::from_secs(timeout))
+    }
+
+    /// Create a new client using a given base URL, an optional timeout
+    /// for establishing a connection and a timeout for each request as a
+    /// whole. Fails if the TLS settings can not be used, like a CA
+    /// certificate the TLS backend rejects.
+    pub fn new_with_timeouts(
+        base_url: String,
+        connect_timeout: Option<Duration>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let mut builder = reqwest::blocking::Client::builder()
+            .gzip(true)
+            .timeout(timeout);
+        if let Some(connect_timeout) = connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        let tls = TLS.lock().map(|tls| tls.clone()).unwrap_or_default();
+        if tls.allow_insecure {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        if let Some(cert) = tls.ca_cert {
+            builder = builder.add_root_certificate(cert);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| anyhow!("Failed to create the API client: {}", e))?;
+        Ok(Self { base_url, client })
+    }
+
+    fn fetch<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let response = self.get(path, query)?.error_for_status()?;
+        Ok(response.json()?)
+    }
+
+    /// Like fetch but returns None if the requested resource does not
+    /// exist.
+    fn fetch_optional<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Option<T>> {
+        let response = self.get(path, query)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json()?))
+    }
+
+    fn get(&self, path: &str, query: &[(&str, String)]) -> Result<reqwest::blocking::Response> {
+        let request_url = format!("{}{}", self.base_url, path);
+        Ok(self.client.get(&request_url).query(query).send()?)
+    }
+
+    /// Fetch the account for a given address
+    pub fn get_account(&self, address: &str) -> Result<Account> {
+        let result: Data<Account> = self.fetch(&format!("/accounts/{}", address), &[])?;
+        Ok(result.data)
+    }
+
+    /// Fetch all hotspots owned by a given account address
+    pub fn get_hotspots(&self, address: &str) -> Result<Vec<Hotspot>> {
+        let path = format!("/accounts/{}/hotspots", address);
+        let mut hotspots = vec![];
+        let mut cursor = None;
+        loop {
+            let query: Vec<(&str, String)> = cursor
+                .take()
+                .map(|cursor| vec![("cursor", cursor)])
+                .unwrap_or_default();
+            let page: Page<Hotspot> = self.fetch(&path, &query)?;
+            hotspots.extend(page.data);
+            match page.cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(hotspots),
+            }
+        }
+    }
+
+    /// Fetch the chain variables
+    pub fn get_vars(&self) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let result: Data<serde_json::Map<String, serde_json::Value>> = self.fetch("/vars", &[])?;
+        Ok(result.data)
+    }
+
+    /// Fetch the most recently assigned OUI
+    pub fn get_last_oui(&self) -> Result<u64> {
+        let result: Data<Oui> = self.fetch("/ouis/last", &[])?;
+        Ok(result.data.oui)
+    }
+
+    /// Submit a signed transaction
+    pub fn submit_txn(&self, txn: &BlockchainTxn) -> Result<PendingTxnStatus> {
+        let request_url = format!("{}/pending_transactions", self.base_url);
+        let result: Data<PendingTxnStatus> = self
+            .client
+            .post(&request_url)
+            .json(&json!({ "txn": txn.to_b64()? }))
+            .send()?
+            .error_for_status()?
+            .json()?;
+        Ok(result.data)
+    }
+
+    /// Fetch a page of activity for a given account address. The
+    /// transactions are returned newest first as raw json since their
+    /// shape depends on the transaction type. An empty list of filter
+    /// types returns all transaction types.
+    pub fn get_account_activity(
+        &self,
+        address: &str,
+        filter_types: &[String],
+        cursor: Option<&str>,
+    ) -> Result<Page<serde_json::Value>> {
+        let mut query = vec![];
+        if !filter_types.is_empty() {
+            query.push(("filter_types", filter_types.join(",")));
+        }
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor.to_string()));
+        }
+        self.fetch(&format!("/accounts/{}/activity", address), &query)
+    }
+
+    /// Fetch a page of pending transactions for a given account
+    /// address. Each entry has the transaction status, hash and type with
+    /// the transaction itself as raw json under "txn".
+    pub fn get_account_pending_txns(
+        &self,
+        address: &str,
+        cursor: Option<&str>,
+    ) -> Result<Page<serde_json::Value>> {
+        let mut query = vec![];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor.to_string()));
+        }
+        self.fetch(
+            &format!("/accounts/{}/pending_transactions", address),
+            &query,
+        )
+    }
+
+    /// Fetch the current oracle price
+    pub fn get_oracle_price(&self) -> Result<OraclePrice> {
+        let result: Data<OraclePrice> = self.fetch("/oracle/prices/current", &[])?;
+        Ok(result.data)
+    }
+
+    /// Fetch the predicted oracle prices that take effect in the near
+    /// future
+    pub fn get_oracle_predictions(&self) -> Result<Vec<OraclePrediction>> {
+        let result: Data<Vec<OraclePrediction>> = self.fetch("/oracle/predictions", &[])?;
+        Ok(result.data)
+    }
+
+    /// Fetch the oracle price that was in effect at a given block. Returns
+    /// None if the API has no price for the block.
+    pub fn get_oracle_price_at(&self, block: u64) -> Result<Option<OraclePrice>> {
+        let result: Option<Data<OraclePrice>> =
+            self.fetch_optional(&format!("/oracle/prices/{}", block), &[])?;
+        Ok(result.map(|result| result.data))
+    }
+
+    /// Fetch the chain state of a validator. Returns None if the
+    /// validator is not (yet) on chain.
+    pub fn get_validator(&self, address: &str) -> Result<Option<Validator>> {
+        let result: Option<Data<Validator>> =
+            self.fetch_optional(&format!("/validators/{}", address), &[])?;
+        Ok(result.map(|result| result.data))
+    }
+
+    /// Fetch the status of a submitted transaction. Returns None if the
+    /// transaction is not known to the API.
+    pub fn get_pending_txn(&self, hash: &str) -> Result<Option<PendingTxn>> {
+        let result: Data<Vec<PendingTxn>> =
+            self.fetch(&format!("/pending_transactions/{}", hash), &[])?;
+        Ok(result.data.into_iter().next())
+    }
+
+    /// Fetch the current block height of the chain
+    pub fn get_height(&self) -> Result<u64> {
+        let result: Data<Height> = self.fetch("/blocks/height", &[])?;
+        Ok(result.data.height)
+    }
+
+    /// Fetch the height of the block a transaction is in. Returns None if
+    /// the transaction is not (yet) on chain.
+    pub fn get_txn_height(&self, hash: &str) -> Result<Option<u64>> {
+        let result: Option<Data<Height>> =
+            self.fetch_optional(&format!("/transactions/{}", hash), &[])?;
+        Ok(result.map(|result| result.data.height))
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    cmd::*,
+    keypair::PublicKey,
+    result::{anyhow, Result},
+    wallet::Wallet,
+};
+use helium_api::{Account, Hnt, Hst};
+use prettytable::{format, Table};
+use serde_json::json;
+use std::{collections::HashSet, fs, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Show the balances of all wallets in a directory along with the total
+/// across all of them. Both full and watch-only wallets are listed, no
+/// password is needed. The shards of a sharded wallet are listed once.
+pub struct Cmd {
+    /// Directory with the wallet files to list
+    #[structopt(long)]
+    dir: PathBuf,
+
+    /// Maximum number of concurrent API requests
+    #[structopt(long, default_value = "8")]
+    concurrency: usize,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallets = read_wallets(&self.dir)?;
+        if wallets.is_empty() {
+            bail!("No wallet files found in {}", self.dir.display());
+        }
+        let url = opts.api_url.clone();
+        let timeouts = opts.timeouts;
+        let results = map_concurrent(wallets, self.concurrency, move |(path, address)| {
+            let account: Result<Account> = timeouts
+                .client(api_url(&url, address.network))
+                .and_then(|client| client.get_account(&address.to_string()));
+            (path, address, account)
+        })?;
+        print_accounts(&results, opts.format)
+    }
+}
+
+/// Reads the address of every wallet file in the given directory, ordered
+/// by file name. Files that are not wallets are skipped with a warning and
+/// only the first shard of a sharded wallet is included.
+fn read_wallets(dir: &Path) -> Result<Vec<(PathBuf, PublicKey)>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)
+        .map_err(|e| anyhow!("Failed to read directory {}: {}", dir.display(), e))?
+    {
+        let path = entry?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut seen = HashSet::new();
+    let mut wallets = Vec::with_capacity(paths.len());
+    for path in paths {
+        let wallet = match fs::File::open(&path)
+            .map_err(|e| e.into())
+            .and_then(|mut reader| Wallet::read(&mut reader))
+        {
+            Ok(wallet) => wallet,
+            Err(err) => {
+                eprintln!("Skipping {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        if seen.insert(wallet.public_key.to_string()) {
+            wallets.push((path, wallet.public_key));
+        }
+    }
+    Ok(wallets)
+}
+
+/// The sum of the HNT, DC and HST balances of the fetched accounts, in
+/// bones for HNT and HST
+#[derive(Debug, Default, PartialEq)]
+struct Total {
+    balance: u64,
+    dc_balance: u64,
+    sec_balance: u64,
+}
+
+fn total(results: &[(PathBuf, PublicKey, Result<Account>)]) -> Total {
+    results
+        .iter()
+        .filter_map(|(_, _, account)| account.as_ref().ok())
+        .fold(Total::default(), |total, account| Total {
+            balance: total.balance + account.balance,
+            dc_balance: total.dc_balance + account.dc_balance,
+            sec_balance: total.sec_balance + account.sec_balance,
+        })
+}
+
+fn print_accounts(
+    results: &[(PathBuf, PublicKey, Result<Account>)],
+    format: OutputFormat,
+) -> Result {
+    let total = total(results);
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row![
+                "File",
+                "Address",
+                "Network",
+                "Balance",
+                "Data Credits",
+                "Security Tokens"
+            ]);
+            for (path, address, result) in results {
+                match result {
+                    Ok(account) => table.add_row(row![
+                        path.display(),
+                        address,
+                        address.network,
+                        Hnt::from_bones(account.balance),
+                        account.dc_balance,
+                        Hst::from_bones(account.sec_balance)
+                    ]),
+                    Err(err) => table.add_row(
+                        row![path.display(), address, address.network, H3 -> err.to_string()],
+                    ),
+                };
+            }
+            table.add_row(row![
+                "Total",
+                "",
+                "",
+                Hnt::from_bones(total.balance),
+                total.dc_balance,
+                Hst::from_bones(total.sec_balance)
+            ]);
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let accounts: Vec<serde_json::Value> = results
+                .iter()
+                .map(|(path, address, result)| {
+                    let mut value = json!({
+                        "file": path.display().to_string(),
+                        "address": address.to_string(),
+                        "network": address.network.to_string(),
+                    });
+                    match result {
+                        Ok(account) => {
+                            value["balance"] = json!(Hnt::from_bones(account.balance));
+                            value["dc_balance"] = json!(account.dc_balance);
+                            value["sec_balance"] = json!(account.sec_balance);
+                        }
+                        Err(err) => value["error"] = json!(err.to_string()),
+                    }
+                    value
+                })
+                .collect();
+            let dashboard = json!({
+                "accounts": accounts,
+                "total": {
+                    "balance": Hnt::from_bones(total.balance),
+                    "dc_balance": total.dc_balance,
+                    "sec_balance": total.sec_balance,
+                }
+            });
+            print_json(&dashboard)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    fn account(balance: u64, dc_balance: u64, sec_balance: u64) -> Account {
+        serde_json::from_value(json!({
+            "address": Keypair::default().public_key().to_string(),
+            "balance": balance,
+            "dc_balance": dc_balance,
+            "dc_nonce": 0,
+            "sec_balance": sec_balance,
+            "sec_nonce": 0,
+            "speculative_sec_nonce": 0,
+            "nonce": 0,
+            "speculative_nonce": 0,
+            "block": 100,
+            "staked_balance": 0,
+        }))
+        .expect("account")
+    }
+
+    #[test]
+    fn wallets_in_dir() {
+        let dir =
+            std::env::temp_dir().join(format!("helium-wallet-accounts-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("dir");
+        let first = Wallet::watch(Keypair::default().public_key().clone());
+        let second = Wallet::watch(Keypair::default().public_key().clone());
+        for (name, wallet) in &[("a.key", &first), ("b.key", &second), ("c.key", &first)] {
+            let mut file = fs::File::create(dir.join(name)).expect("file");
+            wallet.write(&mut file).expect("write");
+        }
+        fs::write(dir.join("notes.txt"), "not a wallet").expect("write");
+
+        let wallets = read_wallets(&dir).expect("wallets");
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(
+            vec![
+                (dir.join("a.key"), first.public_key),
+                (dir.join("b.key"), second.public_key),
+            ],
+            wallets
+        );
+    }
+
+    #[test]
+    fn totals() {
+        let address = Keypair::default().public_key().clone();
+        let results = vec![
+            (
+                PathBuf::from("a.key"),
+                address.clone(),
+                Ok(account(100, 5, 1)),
+            ),
+            (
+                PathBuf::from("b.key"),
+                address.clone(),
+                Err(anyhow!("failed")),
+            ),
+            (PathBuf::from("c.key"), address, Ok(account(50, 10, 0))),
+        ];
+        assert_eq!(
+            Total {
+                balance: 150,
+                dc_balance: 15,
+                sec_balance: 1,
+            },
+            total(&results)
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{
+    keypair::PublicKey,
+    result::Result,
+    traits::{TxnPayer, B64},
+};
+use helium_api::{BlockchainTxn, PendingTxnStatus, Txn};
+use serde_json::{json, Value};
+use std::{
+    env,
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Appends a json line describing a successfully submitted transaction to
+/// the audit log at the given path.
+pub fn append(path: &Path, envelope: &BlockchainTxn, status: &PendingTxnStatus) -> Result {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let mut entry = txn_summary(envelope)?;
+    entry["time"] = json!(time);
+    entry["command"] = json!(env::args().skip(1).collect::<Vec<String>>().join(" "));
+    entry["hash"] = json!(status.hash);
+    entry["txn"] = json!(envelope.to_b64()?);
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+/// Summarizes the type, network, payments, fee and nonce of a
+/// transaction. Fields that do not apply to a transaction type are left
+/// out.
+fn txn_summary(envelope: &BlockchainTxn) -> Result<Value> {
+    let payment = |payee: &[u8], amount: u64| -> Result<Value> {
+        Ok(json!({
+            "payee": PublicKey::from_bytes(payee)?.to_string(),
+            "amount": amount,
+        }))
+    };
+    let mut summary = match &envelope.txn {
+        Some(Txn::Payment(t)) => json!({
+            "type": "payment_v1",
+            "payments": [payment(&t.payee, t.amount)?],
+            "fee": t.fee,
+            "nonce": t.nonce,
+        }),
+        Some(Txn::PaymentV2(t)) => {
+            let mut payments = Vec::with_capacity(t.payments.len());
+            for p in &t.payments {
+                payments.push(payment(&p.payee, p.amount)?);
+            }
+            json!({
+                "type": "payment_v2",
+                "payments": payments,
+                "fee": t.fee,
+                "nonce": t.nonce,
+            })
+        }
+        Some(Txn::TokenBurn(t)) => json!({
+            "type": "token_burn_v1",
+            "payments": [payment(&t.payee, t.amount)?],
+            "fee": t.fee,
+            "nonce": t.nonce,
+        }),
+        Some(Txn::CreateHtlc(t)) => json!({
+            "type": "create_htlc_v1",
+            "payments": [payment(&t.payee, t.amount)?],
+            "fee": t.fee,
+            "nonce": t.nonce,
+        }),
+        Some(Txn::SecurityExchange(t)) => json!({
+            "type": "security_exchange_v1",
+            "payments": [payment(&t.payee, t.amount)?],
+            "fee": t.fee,
+            "nonce": t.nonce,
+        }),
+        _ => json!({ "type": "other" }),
+    };
+    if let Ok(Some(payer)) = envelope.payer() {
+        summary["network"] = json!(payer.network.to_string());
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::Keypair, txn::TxnBuilder};
+    use std::fs;
+
+    #[test]
+    fn append_line() {
+        let payer = Keypair::default().public_key().clone();
+        let payee = Keypair::default().public_key().clone();
+        let (envelope, _) = TxnBuilder::payment(&[(payee.clone(), 100)])
+            .nonce(7)
+            .fee(35_000)
+            .build_unsigned(&payer)
+            .expect("payment");
+        let status = PendingTxnStatus {
+            hash: "hash".to_string(),
+        };
+
+        let path = env::temp_dir().join(format!("helium-wallet-audit-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        append(&path, &envelope, &status).expect("append");
+        append(&path, &envelope, &status).expect("append");
+        let contents = fs::read_to_string(&path).expect("audit log");
+        fs::remove_file(&path).expect("remove audit log");
+
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(2, lines.len());
+        let entry: Value = serde_json::from_str(lines[1]).expect("json line");
+        assert_eq!("payment_v2", entry["type"]);
+        assert_eq!("hash", entry["hash"]);
+        assert_eq!(7, entry["nonce"]);
+        assert_eq!(35_000, entry["fee"]);
+        assert_eq!(payer.network.to_string(), entry["network"]);
+        assert_eq!(payee.to_string(), entry["payments"][0]["payee"]);
+    }
+}
@@ -3,9 +3,13 @@ use crate::{
     keypair::PublicKey,
     result::{anyhow, Result},
 };
-use helium_api::{Account, Client, Hnt, Hst};
+use helium_api::{Account, Hnt, Hst};
 use prettytable::{format, Table};
 use serde_json::json;
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -15,69 +19,85 @@ pub struct Cmd {
     /// Addresses to get balances for
     #[structopt(short = "a", long = "address")]
     addresses: Vec<PublicKey>,
+
+    /// Maximum number of concurrent API requests
+    #[structopt(long, default_value = "8")]
+    concurrency: usize,
+
+    #[structopt(flatten)]
+    output: OutputFile,
 }
 
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
         let addresses = collect_addresses(opts.files, self.addresses.clone())?;
         let api_url = api_url(
+            &opts.api_url,
             addresses
                 .first()
                 .map(|key| key.network)
                 .ok_or_else(|| anyhow!("at least one address expected"))?,
         );
-        let client = Client::new_with_base_url(api_url);
+        let client = Arc::new(opts.timeouts.client(api_url)?);
 
-        let mut results = Vec::with_capacity(self.addresses.len());
-        for address in addresses {
-            results.push((
-                address.to_string(),
-                client
-                    .get_account(&address.to_string())
-                    .map_err(|e| e.into()),
-            ));
-        }
-        print_results(results, opts.format)
+        let results = map_concurrent(addresses, self.concurrency, move |address| {
+            let account = fetch_account(&client, &address);
+            (address, account)
+        })?;
+        let mut writer = self.output.writer()?;
+        write_results(&mut writer, results, opts.format)?;
+        writer.finish()
     }
 }
 
-fn print_results(results: Vec<(String, Result<Account>)>, format: OutputFormat) -> Result {
+fn write_results(
+    writer: &mut dyn io::Write,
+    results: Vec<(PublicKey, Result<Option<Account>>)>,
+    format: OutputFormat,
+) -> Result {
     match format {
         OutputFormat::Table => {
             let mut table = Table::new();
             table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
             table.set_titles(row![
                 "Address",
+                "Network",
                 "Balance",
                 "Data Credits",
                 "Security Tokens"
             ]);
             for (address, result) in results {
                 match result {
-                    Ok(account) => table.add_row(row![
+                    Ok(Some(account)) => table.add_row(row![
                         address,
+                        address.network,
                         Hnt::from_bones(account.balance),
                         account.dc_balance,
                         Hst::from_bones(account.sec_balance)
                     ]),
-                    Err(err) => table.add_row(row![address, H3 -> err.to_string()]),
+                    Ok(None) => table.add_row(row![address, address.network, H3 -> NOT_ON_CHAIN]),
+                    Err(err) => {
+                        table.add_row(row![address, address.network, H3 -> err.to_string()])
+                    }
                 };
             }
-            print_table(&table)
+            write_table(writer, &table)
         }
         OutputFormat::Json => {
             let mut rows = Vec::with_capacity(results.len());
             for (address, result) in results {
                 if let Ok(account) = result {
                     rows.push(json!({
-                        "address": address,
-                        "dc_balance": account.dc_balance,
-                        "sec_balance": account.sec_balance,
-                        "balance": Hnt::from_bones(account.balance),
+                        "address": address.to_string(),
+                        "network": address.network.to_string(),
+                        "on_chain": account.is_some(),
+                        "dc_balance": account.as_ref().map_or(0, |a| a.dc_balance),
+                        "sec_balance": account.as_ref().map_or(0, |a| a.sec_balance),
+                        "balance": Hnt::from_bones(account.as_ref().map_or(0, |a| a.balance)),
                     }));
                 };
             }
-            print_json(&rows)
+            write_json(writer, &rows)
         }
     }
 }
@@ -0,0 +1,303 @@
+use crate::{
+    api::Client,
+    cmd::{
+        payee::{parse_address, Amount},
+        validators::stake::{get_minimum_stake, Stake},
+        *,
+    },
+    result::{anyhow, bail, Result},
+    txn::{BuildTxn, TxnBuilder},
+};
+use helium_api::{BlockchainTxnPaymentV2, BlockchainTxnStakeValidatorV1, Hnt};
+use serde::Deserialize;
+use std::fs;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Build and sign a transaction from a declarative spec file. Specs in
+/// YAML (.yaml or .yml) or JSON describe the transaction type and its
+/// fields, so a transaction can be reviewed and kept under version control
+/// before it is signed. A payment spec has the fields "payees" (a list of
+/// "address" and "amount"), "nonce" and "fee". A stake spec has the fields
+/// "validator", "stake" ("auto" or the HNT amount) and "fee". Amounts are
+/// quoted HNT or whole bones like "150000000b". Without a nonce the next
+/// nonce of the wallet is fetched from the API, and without a fee it is
+/// calculated from the chain fee configuration. The signed transaction is
+/// printed in the selected encoding and can be submitted with submit.
+pub struct Cmd {
+    /// The transaction spec file
+    #[structopt(long)]
+    spec: PathBuf,
+}
+
+/// A declarative transaction spec. Unknown fields are rejected so a typo
+/// does not silently fall back to a default.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Spec {
+    Payment {
+        payees: Vec<PayeeSpec>,
+        nonce: Option<u64>,
+        fee: Option<u64>,
+    },
+    Stake {
+        validator: String,
+        stake: String,
+        fee: Option<u64>,
+    },
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PayeeSpec {
+    address: String,
+    amount: String,
+}
+
+impl Spec {
+    /// Reads a spec from the given file, as YAML for a .yaml or .yml file
+    /// and as JSON otherwise
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read spec {}: {}", path.display(), e))?;
+        let yaml = matches!(get_file_extension(path).as_str(), "yaml" | "yml");
+        Self::parse(&contents, yaml).map_err(|e| anyhow!("Invalid spec {}: {}", path.display(), e))
+    }
+
+    pub fn parse(contents: &str, yaml: bool) -> Result<Self> {
+        if yaml {
+            Ok(serde_yaml::from_str(contents)?)
+        } else {
+            Ok(serde_json::from_str(contents)?)
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Payment { .. } => "payment",
+            Self::Stake { .. } => "stake",
+        }
+    }
+}
+
+/// Validates the payees of a payment spec and returns their addresses and
+/// amounts in bones. Only fixed amounts are allowed, since converted or
+/// balance dependent amounts would change the reviewed transaction.
+fn spec_payees(payees: &[PayeeSpec], network: Network) -> Result<Vec<(PublicKey, u64)>> {
+    if payees.is_empty() {
+        bail!("A payment spec needs at least one payee");
+    }
+    payees
+        .iter()
+        .map(|payee| {
+            let address = spec_address(&payee.address, network)?;
+            let amount: Amount = payee.amount.parse()?;
+            let bones = match amount {
+                Amount::Hnt(_) | Amount::Bones(_) => amount.to_bones(None)?,
+                _ => None,
+            };
+            match bones {
+                Some(0) => bail!("Zero amount for payee {}", payee.address),
+                Some(bones) => Ok((address, bones)),
+                None => bail!(
+                    "Amount {} for payee {} is not a fixed amount, use HNT or bones",
+                    payee.amount,
+                    payee.address
+                ),
+            }
+        })
+        .collect()
+}
+
+fn spec_address(address: &str, network: Network) -> Result<PublicKey> {
+    let public_key =
+        parse_address(address).map_err(|e| anyhow!("Invalid address {}: {}", address, e))?;
+    if public_key.network != network {
+        bail!("Address {} is not a {} address", address, network);
+    }
+    Ok(public_key)
+}
+
+/// Sets the fee of the builder to the given fee, or to the fee calculated
+/// from the chain fee configuration if none is given
+fn with_fee<T: BuildTxn>(
+    builder: TxnBuilder<T>,
+    fee: Option<u64>,
+    client: &Client,
+    opts: &SubmitOpts,
+) -> Result<TxnBuilder<T>> {
+    match fee {
+        Some(fee) => Ok(builder.fee(fee)),
+        None => Ok(builder.fee_config(&get_txn_fees(client, opts)?)),
+    }
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let spec = Spec::read(&self.spec)?;
+        let wallet = load_wallet(opts.files)?;
+        let network = wallet.public_key.network;
+        let client = opts.timeouts.client(api_url(&opts.api_url, network))?;
+
+        // Validate the whole spec before asking for the password
+        let (builder, nonce, amount) = match &spec {
+            Spec::Payment { payees, nonce, fee } => {
+                let payees = spec_payees(payees, network)?;
+                let amount = payees.iter().map(|(_, amount)| amount).sum();
+                let nonce = match nonce {
+                    Some(nonce) => *nonce,
+                    None => fetch_payer_account(&client, &wallet.public_key)?.speculative_nonce + 1,
+                };
+                let builder = TxnBuilder::payment(&payees).nonce(nonce);
+                (
+                    Builder::Payment(with_fee(builder, *fee, &client, &opts.submit)?),
+                    Some(nonce),
+                    amount,
+                )
+            }
+            Spec::Stake {
+                validator,
+                stake,
+                fee,
+            } => {
+                let validator = spec_address(validator, network)?;
+                let stake: Stake = stake
+                    .parse()
+                    .map_err(|e| anyhow!("Invalid stake {}: {}", stake, e))?;
+                let stake = stake.to_bones(get_minimum_stake(&client)?)?;
+                let builder = TxnBuilder::stake_validator(&validator, stake);
+                (
+                    Builder::Stake(with_fee(builder, *fee, &client, &opts.submit)?),
+                    None,
+                    stake,
+                )
+            }
+        };
+
+        let signer = wallet_signer(&wallet, &opts.key_file, &opts.signer_command)?;
+        let (envelope, fee) = match builder {
+            Builder::Payment(builder) => builder.build_signed(&signer)?,
+            Builder::Stake(builder) => builder.build_signed(&signer)?,
+        };
+        print_txn(
+            &spec,
+            &envelope,
+            &wallet.public_key,
+            nonce,
+            amount,
+            fee,
+            opts.encoding,
+            opts.format,
+        )
+    }
+}
+
+/// The builder for each supported spec type
+enum Builder {
+    Payment(TxnBuilder<BlockchainTxnPaymentV2>),
+    Stake(TxnBuilder<BlockchainTxnStakeValidatorV1>),
+}
+
+fn print_txn(
+    spec: &Spec,
+    envelope: &BlockchainTxn,
+    payer: &PublicKey,
+    nonce: Option<u64>,
+    amount: u64,
+    fee: u64,
+    encoding: Encoding,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Type", spec.type_name()],
+                ["Network", payer.network],
+                ["Payer", payer],
+                ["Nonce", nonce.map_or("none".to_string(), |n| n.to_string())],
+                ["Amount", Hnt::from_bones(amount)],
+                ["Fee", fee],
+                ["Txn", encoding.encode(envelope)?]
+            ))?;
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "type": spec.type_name(),
+                "network": payer.network.to_string(),
+                "payer": payer.to_string(),
+                "nonce": nonce,
+                "amount": Hnt::from_bones(amount),
+                "fee": fee,
+                "txn": encoding.encode(envelope)?,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn parse_specs() {
+        let payee = Keypair::default().public_key().to_string();
+        let yaml = format!(
+            "type: payment\npayees:\n  - address: {}\n    amount: \"1.5\"\nnonce: 42\n",
+            payee
+        );
+        let json = format!(
+            r#"{{"type": "payment", "payees": [{{"address": "{}", "amount": "1.5"}}], "nonce": 42}}"#,
+            payee
+        );
+        let expected = Spec::Payment {
+            payees: vec![PayeeSpec {
+                address: payee.clone(),
+                amount: "1.5".to_string(),
+            }],
+            nonce: Some(42),
+            fee: None,
+        };
+        assert_eq!(expected, Spec::parse(&yaml, true).expect("yaml"));
+        assert_eq!(expected, Spec::parse(&json, false).expect("json"));
+
+        let stake = "type: stake\nvalidator: abc\nstake: auto\nfee: 35000\n";
+        assert!(matches!(
+            Spec::parse(stake, true).expect("stake"),
+            Spec::Stake {
+                fee: Some(35000),
+                ..
+            }
+        ));
+
+        // Unknown types and fields, and missing fields are rejected
+        assert!(Spec::parse("type: burn\n", true).is_err());
+        assert!(Spec::parse(&yaml.replace("nonce", "nonse"), true).is_err());
+        assert!(Spec::parse("type: stake\nvalidator: abc\n", true).is_err());
+    }
+
+    #[test]
+    fn validate_payees() {
+        let payee = |address: &str, amount: &str| PayeeSpec {
+            address: address.to_string(),
+            amount: amount.to_string(),
+        };
+        let address = Keypair::default().public_key().to_string();
+        let payees = spec_payees(
+            &[payee(&address, "1.5"), payee(&address, "100b")],
+            Network::MainNet,
+        )
+        .expect("payees");
+        assert_eq!(150_000_000, payees[0].1);
+        assert_eq!(100, payees[1].1);
+
+        assert!(spec_payees(&[], Network::MainNet).is_err());
+        assert!(spec_payees(&[payee(&address, "1")], Network::TestNet).is_err());
+        for amount in &["0", "10usd", "50%", "sweep"] {
+            assert!(spec_payees(&[payee(&address, amount)], Network::MainNet).is_err());
+        }
+    }
+}
@@ -1,13 +1,15 @@
 use crate::{
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_footer, print_json, status_json,
-        status_str, Opts, OutputFormat,
+        api_url, check_balance, check_logged_nonce, check_nonce, decrypt_wallet,
+        fetch_payer_account, get_txn_fees, load_wallet, print_footer, print_json, print_table,
+        status_json, status_str, submit_txn, Encoding, Opts, OutputFormat,
     },
-    keypair::PublicKey,
+    keypair::{Network, PublicKey},
     result::Result,
-    traits::{TxnEnvelope, TxnFee, TxnSign, B64},
+    traits::{BalanceType, TxnEnvelope, TxnSign, B64},
+    txn::TxnBuilder,
 };
-use helium_api::{BlockchainTxn, BlockchainTxnTokenBurnV1, Client, Hnt, PendingTxnStatus};
+use helium_api::{BlockchainTxn, BlockchainTxnTokenBurnV1, Hnt, PendingTxnStatus};
 use serde_json::json;
 use structopt::StructOpt;
 
@@ -29,71 +31,92 @@ pub struct Cmd {
     /// Commit the payment to the API
     #[structopt(long)]
     commit: bool,
+
+    /// Re-read the account nonce before committing and abort if it changed
+    /// since the transaction was built
+    #[structopt(long)]
+    check_nonce: bool,
 }
 
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let account = client.get_account(&keypair.public_key().to_string())?;
+        let account = fetch_payer_account(&client, &wallet.public_key)?;
+        check_logged_nonce(
+            &wallet.public_key,
+            account.speculative_nonce + 1,
+            self.commit,
+            &opts.submit,
+        )?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
         let memo = match &self.memo {
             None => 0,
             Some(s) => u64::from_b64(&s)?,
         };
 
-        let mut txn = BlockchainTxnTokenBurnV1 {
-            fee: 0,
-            payee: self.payee.to_bytes().to_vec(),
-            amount: self.amount.to_bones(),
-            payer: keypair.public_key().into(),
-            memo,
-            nonce: account.speculative_nonce + 1,
-            signature: Vec::new(),
-        };
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
+        let mut txn = TxnBuilder::token_burn(&self.payee, self.amount.to_bones(), memo)
+            .nonce(account.speculative_nonce + 1)
+            .fee_config(&get_txn_fees(&client, &opts.submit)?)
+            .build(keypair.public_key())?;
+        check_balance(&account, &txn.in_envelope(), BalanceType::Hnt)?;
         txn.signature = txn.sign(&keypair)?;
         let envelope = txn.in_envelope();
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-        print_txn(&txn, &envelope, &status, opts.format)
+        if self.commit && self.check_nonce {
+            check_nonce(
+                &client,
+                &keypair.public_key().to_string(),
+                account.speculative_nonce,
+            )?;
+        }
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            &txn,
+            &envelope,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 fn print_txn(
     txn: &BlockchainTxnTokenBurnV1,
     envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Payee", PublicKey::from_bytes(&txn.payee)?.to_string()],
                 ["Memo", txn.memo.to_b64()?],
                 ["Amount", Hnt::from_bones(txn.amount)],
                 ["Fee", txn.fee],
                 ["Nonce", txn.nonce],
                 ["Hash", status_str(status)]
-            );
+            ))?;
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "payee": PublicKey::from_bytes(&txn.payee)?.to_string(),
                 "amount": Hnt::from_bones(txn.amount),
                 "memo": txn.memo.to_b64()?,
                 "fee": txn.fee,
                 "nonce": txn.nonce,
                 "hash": status_json(status),
-                "txn": envelope.to_b64()?
+                "txn": encoding.encode(envelope)?
             });
             print_json(&table)
         }
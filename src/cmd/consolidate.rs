@@ -0,0 +1,261 @@
+use crate::{
+    cmd::{pay::calculate_remaining_hnt, *},
+    keypair::PublicKey,
+    result::{bail, Result},
+    traits::{TxnEnvelope, TxnFeeConfig, TxnSign},
+    txn::TxnBuilder,
+};
+use dialoguer::Confirm;
+use helium_api::{Account, BlockchainTxnPaymentV2, Hnt};
+use prettytable::{format, Table};
+use serde_json::json;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Sweep the balances of several wallets into one address. Every wallet
+/// given with -f is swept separately with its own nonce and fee, asking
+/// for its password and for confirmation before each sweep is committed.
+/// A failing wallet does not stop the others, all results are summarized
+/// at the end.
+pub struct Cmd {
+    /// Address to sweep the balances to
+    #[structopt(long)]
+    to: PublicKey,
+
+    /// Commit the sweeps to the API
+    #[structopt(long)]
+    commit: bool,
+}
+
+/// The result of sweeping a single wallet
+enum Outcome {
+    Swept {
+        amount: u64,
+        fee: u64,
+        hash: Option<String>,
+    },
+    Skipped,
+    Failed(String),
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let commit = self.commit && !opts.submit.dry_run;
+        let mut results = Vec::with_capacity(opts.files.len());
+        for path in &opts.files {
+            let outcome = match self.sweep(path, &opts) {
+                Ok(outcome) => outcome,
+                Err(err) => Outcome::Failed(err.to_string()),
+            };
+            if let Outcome::Failed(err) = &outcome {
+                eprintln!("Failed to sweep {}: {}", path.display(), err);
+            }
+            results.push((path.clone(), outcome));
+            interrupt::check(|| {
+                format!("{} of {} wallets swept", results.len(), opts.files.len())
+            })?;
+        }
+        print_results(&results, &self.to, commit, opts.format)?;
+
+        let failed = results
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, Outcome::Failed(_)))
+            .count();
+        if failed > 0 {
+            bail!("{} of {} sweeps failed", failed, results.len());
+        }
+        Ok(())
+    }
+
+    /// Sweeps the balance of the wallet in the given file to the
+    /// destination
+    fn sweep(&self, path: &Path, opts: &Opts) -> Result<Outcome> {
+        let wallet = read_wallet_file(path)?;
+        let payer = &wallet.public_key;
+        if payer == &self.to {
+            bail!("Wallet is the destination");
+        }
+        if payer.network != self.to.network {
+            bail!("Wallet is not a {} wallet", self.to.network);
+        }
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, payer.network))?;
+        let account = fetch_payer_account(&client, payer)?;
+        check_logged_nonce(
+            payer,
+            account.speculative_nonce + 1,
+            self.commit,
+            &opts.submit,
+        )?;
+        let fee_config = get_txn_fees(&client, &opts.submit)?;
+        let oracle_price = client.get_oracle_price()?.price;
+        let mut txn = sweep_txn(&account, payer, &self.to, &fee_config, oracle_price)?;
+
+        eprintln!(
+            "Sweeping {} from {} ({})",
+            Hnt::from_bones(txn.payments[0].amount),
+            payer,
+            path.display()
+        );
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
+        txn.signature = txn.sign(&keypair)?;
+
+        if self.commit
+            && !opts.submit.dry_run
+            && !Confirm::new()
+                .with_prompt(format!(
+                    "Sweep {} from {} to {} for a fee of {} DC?",
+                    Hnt::from_bones(txn.payments[0].amount),
+                    payer,
+                    self.to,
+                    txn.fee
+                ))
+                .interact()?
+        {
+            return Ok(Outcome::Skipped);
+        }
+        // Every swept wallet gets its own receipt
+        let submit_opts = match &opts.submit.receipt {
+            Some(path) if opts.files.len() > 1 => SubmitOpts {
+                receipt: Some(batch_receipt_path(path, payer)),
+                ..opts.submit.clone()
+            },
+            _ => opts.submit.clone(),
+        };
+        let status = submit_txn(&client, &txn.in_envelope(), self.commit, &submit_opts)?;
+        Ok(Outcome::Swept {
+            amount: txn.payments[0].amount,
+            fee: txn.fee,
+            hash: status.map(|status| status.hash),
+        })
+    }
+}
+
+/// Builds an unsigned payment of the whole balance of the given account to
+/// the destination, less the HNT burned for the fee if the DC balance
+/// does not cover it.
+fn sweep_txn(
+    account: &Account,
+    payer: &PublicKey,
+    to: &PublicKey,
+    fee_config: &TxnFeeConfig,
+    oracle_price: u64,
+) -> Result<BlockchainTxnPaymentV2> {
+    if account.balance == 0 {
+        bail!("No balance to sweep");
+    }
+    // The fee is calculated for the full balance, which is an upper bound
+    // for the swept amount
+    let mut txn = TxnBuilder::payment(&[(to.clone(), account.balance)])
+        .nonce(account.speculative_nonce + 1)
+        .fee_config(fee_config)
+        .build(payer)?;
+    txn.payments[0].amount = calculate_remaining_hnt(
+        account.balance,
+        account.dc_balance,
+        txn.fee,
+        oracle_price,
+        0,
+    )?;
+    Ok(txn)
+}
+
+fn print_results(
+    results: &[(PathBuf, Outcome)],
+    to: &PublicKey,
+    committed: bool,
+    format: OutputFormat,
+) -> Result {
+    let swept: u64 = results
+        .iter()
+        .map(|(_, outcome)| match outcome {
+            Outcome::Swept { amount, .. } => *amount,
+            _ => 0,
+        })
+        .sum();
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["File", "Amount", "Fee", "Result"]);
+            for (path, outcome) in results {
+                match outcome {
+                    Outcome::Swept { amount, fee, hash } => table.add_row(row![
+                        path.display(),
+                        Hnt::from_bones(*amount),
+                        fee,
+                        hash.as_deref().unwrap_or("none")
+                    ]),
+                    Outcome::Skipped => table.add_row(row![path.display(), "", "", "skipped"]),
+                    Outcome::Failed(err) => table.add_row(row![path.display(), "", "", err]),
+                };
+            }
+            print_table(&table)?;
+            println!("\nTotal: {} to {}", Hnt::from_bones(swept), to);
+            if !committed {
+                println!("\nNot committed, use --commit to submit the sweeps");
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let sweeps: Vec<serde_json::Value> = results
+                .iter()
+                .map(|(path, outcome)| {
+                    let file = path.display().to_string();
+                    match outcome {
+                        Outcome::Swept { amount, fee, hash } => json!({
+                            "file": file,
+                            "amount": Hnt::from_bones(*amount),
+                            "fee": fee,
+                            "hash": hash,
+                        }),
+                        Outcome::Skipped => json!({ "file": file, "skipped": true }),
+                        Outcome::Failed(err) => json!({ "file": file, "error": err }),
+                    }
+                })
+                .collect();
+            let table = json!({
+                "to": to.to_string(),
+                "total": Hnt::from_bones(swept),
+                "sweeps": sweeps,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn sweep_balance() {
+        let payer = Keypair::default().public_key().clone();
+        let to = Keypair::default().public_key().clone();
+        let account = |balance, dc_balance| testing::account(&payer, balance, dc_balance);
+        let fee_config = TxnFeeConfig::with_txn_fees(5000, 24);
+        // $1.00 per HNT
+        let price = 100_000_000;
+
+        let txn = sweep_txn(
+            &account(100_000_000, 1_000_000),
+            &payer,
+            &to,
+            &fee_config,
+            price,
+        )
+        .expect("sweep");
+        assert_eq!(6, txn.nonce);
+        assert_eq!(to.to_vec(), txn.payments[0].payee);
+        assert_eq!(100_000_000, txn.payments[0].amount);
+
+        // Without DC the fee is burned from the swept balance
+        let txn =
+            sweep_txn(&account(100_000_000, 0), &payer, &to, &fee_config, price).expect("sweep");
+        assert_eq!(100_000_000 - txn.fee * 1_000, txn.payments[0].amount);
+
+        assert!(sweep_txn(&account(0, 1_000_000), &payer, &to, &fee_config, price).is_err());
+    }
+}
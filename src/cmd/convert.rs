@@ -0,0 +1,199 @@
+use crate::{
+    api::OraclePrice,
+    cmd::{
+        payee::{
+            dc_to_hnt_bones, dc_to_usd, format_usd, hnt_bones_to_dc, round_usd, usd_to_hnt_bones,
+            Amount,
+        },
+        *,
+    },
+    result::{anyhow, bail, Result},
+};
+use helium_api::Hnt;
+use rust_decimal::{prelude::*, Decimal};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Convert an amount between HNT, DC and USD at the current oracle price.
+/// The amount is given with its unit, like "1hnt", "150000000b" for bones,
+/// "100000dc" or "10usd". A number without a unit is read as HNT. DC are
+/// worth $0.00001 each and HNT is converted at the oracle price, which is
+/// shown with its block and time. No wallet is needed.
+pub struct Cmd {
+    /// The amount to convert with its unit
+    amount: String,
+
+    /// The network to get the oracle price for (mainnet/testnet)
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let amount = parse_amount(&self.amount)?;
+        let price = opts
+            .timeouts
+            .client(api_url(&opts.api_url, self.network))?
+            .get_oracle_price()?;
+        let conversion = convert(amount, price.price)?;
+        print_conversion(
+            &amount,
+            &conversion,
+            &price,
+            opts.fiat_decimals,
+            opts.format,
+        )
+    }
+}
+
+/// Parses an amount to convert. Besides the units of payment amounts an
+/// "hnt" suffix is accepted, percentages and sweep have no fixed value and
+/// are refused.
+fn parse_amount(s: &str) -> Result<Amount> {
+    let lower = s.trim().to_lowercase();
+    let amount = match lower.strip_suffix("hnt") {
+        Some(hnt) => Amount::Hnt(hnt.trim_end().parse()?),
+        None => lower.parse()?,
+    };
+    if amount.percent().is_some() || amount.is_sweep() {
+        bail!("Invalid amount {}, expected HNT, bones, DC or USD", s);
+    }
+    Ok(amount)
+}
+
+/// An amount expressed in each unit
+#[derive(Debug, PartialEq)]
+struct Conversion {
+    bones: u64,
+    dc: u64,
+    usd: Decimal,
+}
+
+/// Converts an amount to HNT, DC and USD at the given oracle price. DC
+/// converted to HNT are rounded up to the HNT burned for them, HNT and USD
+/// converted to DC are rounded down to the DC a burn would yield.
+fn convert(amount: Amount, oracle_price: u64) -> Result<Conversion> {
+    if oracle_price == 0 {
+        bail!("Invalid oracle price 0");
+    }
+    let conversion = match amount {
+        Amount::Dc(dc) => Conversion {
+            bones: dc_to_hnt_bones(dc, oracle_price)?,
+            dc,
+            usd: dc_to_usd(dc),
+        },
+        Amount::Usd(usd) => Conversion {
+            bones: usd_to_hnt_bones(usd, oracle_price)?,
+            dc: usd_to_dc(usd)?,
+            usd,
+        },
+        _ => {
+            let bones = amount
+                .to_bones(Some(oracle_price))?
+                .ok_or_else(|| anyhow!("Invalid amount {}", amount))?;
+            // The oracle price is in 1/100_000_000 USD per HNT
+            let usd = Decimal::from(bones) * Decimal::from(oracle_price)
+                / Decimal::from(10_000_000_000_000_000u64);
+            Conversion {
+                bones,
+                dc: hnt_bones_to_dc(bones, oracle_price)?,
+                usd,
+            }
+        }
+    };
+    Ok(conversion)
+}
+
+/// Converts USD to DC at $0.00001 per DC, rounded down
+fn usd_to_dc(usd: Decimal) -> Result<u64> {
+    (usd * Decimal::from(100_000))
+        .floor()
+        .to_u64()
+        .ok_or_else(|| anyhow!("Invalid USD amount {}", usd))
+}
+
+fn print_conversion(
+    amount: &Amount,
+    conversion: &Conversion,
+    price: &OraclePrice,
+    fiat_decimals: u32,
+    format: OutputFormat,
+) -> Result {
+    let hnt = Hnt::from_bones(conversion.bones);
+    let timestamp = price.timestamp.clone().unwrap_or_default();
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Amount", amount],
+                ["HNT", hnt],
+                ["DC", conversion.dc],
+                [
+                    "USD",
+                    format!("${}", format_usd(conversion.usd, fiat_decimals))
+                ],
+                [
+                    "Oracle Price",
+                    format!("${}", Decimal::new(price.price as i64, 8))
+                ],
+                ["Oracle Block", price.block],
+                ["Oracle Time", timestamp]
+            ))?;
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "amount": amount.to_string(),
+                "hnt": hnt,
+                "bones": conversion.bones,
+                "dc": conversion.dc,
+                "usd": round_usd(conversion.usd, fiat_decimals),
+                "oracle_price": price,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        // $1.50 per HNT
+        let price = 150_000_000;
+        let hnt = convert(parse_amount("1hnt").unwrap(), price).unwrap();
+        assert_eq!(
+            Conversion {
+                bones: 100_000_000,
+                dc: 150_000,
+                usd: Decimal::new(15, 1),
+            },
+            hnt
+        );
+        assert_eq!(hnt, convert(parse_amount("1").unwrap(), price).unwrap());
+        assert_eq!(
+            hnt,
+            convert(parse_amount("100000000b").unwrap(), price).unwrap()
+        );
+
+        let dc = convert(parse_amount("150000dc").unwrap(), price).unwrap();
+        assert_eq!(100_000_000, dc.bones);
+        assert_eq!(Decimal::new(15, 1), dc.usd);
+        let usd = convert(parse_amount("1.5 USD").unwrap(), price).unwrap();
+        assert_eq!(100_000_000, usd.bones);
+        assert_eq!(150_000, usd.dc);
+
+        // HNT is rounded up to what a burn for the DC costs
+        assert_eq!(
+            667,
+            convert(parse_amount("1dc").unwrap(), price).unwrap().bones
+        );
+
+        assert!(parse_amount("50%").is_err());
+        assert!(parse_amount("sweep").is_err());
+        assert!(parse_amount("1eth").is_err());
+        assert!(convert(parse_amount("1hnt").unwrap(), 0).is_err());
+    }
+}
@@ -0,0 +1,419 @@
+use crate::{
+    cmd::*,
+    keypair::{Keypair, PublicKey},
+    result::{bail, Result},
+    traits::TxnSign,
+};
+use helium_api::{BlockchainTxn, PendingTxnStatus, Txn};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Co-sign a partially signed transaction. The owner of a transaction with a
+/// separate payer signs and emits the transaction with the payer signature
+/// left empty. The payer (or owner) then uses this command to fill in the
+/// empty signature that belongs to this wallet. Supported transactions are
+/// add gateway, assert location and OUI transactions, unsigned validator
+/// stake and unstake transactions which are signed by the owner, and
+/// unsigned payments which are signed by the payer.
+pub struct Cmd {
+    /// Encoded transaction (see --encoding) to co-sign. If no transaction if given
+    /// stdin is read for the transaction. Note that the stdin feature only
+    /// works if the wallet password is set in the HELIUM_WALLET_PASSWORD
+    /// environment variable
+    #[structopt(name = "TRANSACTION")]
+    txn: Option<Transaction>,
+
+    /// Commit the co-signed transaction to the API
+    #[structopt(long)]
+    commit: bool,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let mut envelope = read_txn(&self.txn, opts.encoding)?;
+        check_logged_txn_nonce(&envelope, self.commit, &opts.submit)?;
+
+        let wallet = load_wallet(opts.files)?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
+
+        let signatures = cosign(&mut envelope, &keypair)?;
+        if self.commit && !signatures.is_complete() {
+            bail!("Transaction is missing a signature and can not be committed");
+        }
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            &envelope,
+            &signatures,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
+    }
+}
+
+/// The state of the owner, gateway and payer signatures of a co-signed
+/// transaction. Transactions without a gateway or payer signature report
+/// those as signed.
+#[derive(Debug, PartialEq)]
+pub struct Signatures {
+    pub owner: bool,
+    pub gateway: bool,
+    pub payer: bool,
+}
+
+impl Signatures {
+    pub fn is_complete(&self) -> bool {
+        self.owner && self.gateway && self.payer
+    }
+}
+
+macro_rules! cosign_txn {
+    ($txn:ident, $keypair:ident, gateway) => {{
+        // The gateway signs the transaction before the owner and payer
+        let gateway = PublicKey::from_bytes(&$txn.gateway)?;
+        if !$txn.gateway_signature.is_empty() {
+            $txn.verify(&gateway, &$txn.gateway_signature)?;
+        }
+        let gateway_signed = !$txn.gateway_signature.is_empty();
+        let signatures: Result<Signatures> = cosign_txn!($txn, $keypair);
+        Ok(Signatures {
+            gateway: gateway_signed,
+            ..signatures?
+        })
+    }};
+    ($txn:ident, $keypair:ident) => {{
+        let wallet_key = $keypair.public_key();
+        let owner = PublicKey::from_bytes(&$txn.owner)?;
+        // An empty payer means the owner pays and no payer signature is
+        // needed
+        let payer = if $txn.payer.is_empty() {
+            None
+        } else {
+            Some(PublicKey::from_bytes(&$txn.payer)?)
+        };
+
+        let mut signed = false;
+        if $txn.owner_signature.is_empty() && &owner == wallet_key {
+            $txn.owner_signature = $txn.sign($keypair)?;
+            signed = true;
+        }
+        if $txn.payer_signature.is_empty() && payer.as_ref() == Some(wallet_key) {
+            $txn.payer_signature = $txn.sign($keypair)?;
+            signed = true;
+        }
+        if !signed {
+            bail!(
+                "No empty signature in transaction for wallet {}",
+                wallet_key
+            );
+        }
+
+        // Validate all present signatures on the resulting transaction
+        if !$txn.owner_signature.is_empty() {
+            $txn.verify(&owner, &$txn.owner_signature)?;
+        }
+        if let Some(payer) = &payer {
+            if !$txn.payer_signature.is_empty() {
+                $txn.verify(payer, &$txn.payer_signature)?;
+            }
+        }
+        Ok(Signatures {
+            owner: !$txn.owner_signature.is_empty(),
+            gateway: true,
+            payer: payer.is_none() || !$txn.payer_signature.is_empty(),
+        })
+    }};
+}
+
+macro_rules! cosign_owner_txn {
+    ($txn:ident, $keypair:ident) => {{
+        let wallet_key = $keypair.public_key();
+        let owner = PublicKey::from_bytes(&$txn.owner)?;
+        if !$txn.owner_signature.is_empty() || &owner != wallet_key {
+            bail!(
+                "No empty signature in transaction for wallet {}",
+                wallet_key
+            );
+        }
+        $txn.owner_signature = $txn.sign($keypair)?;
+        $txn.verify(&owner, &$txn.owner_signature)?;
+        // The owner pays for these transactions
+        Ok(Signatures {
+            owner: true,
+            gateway: true,
+            payer: true,
+        })
+    }};
+}
+
+macro_rules! cosign_payer_txn {
+    ($txn:ident, $keypair:ident) => {{
+        let wallet_key = $keypair.public_key();
+        let payer = PublicKey::from_bytes(&$txn.payer)?;
+        if !$txn.signature.is_empty() || &payer != wallet_key {
+            bail!(
+                "No empty signature in transaction for wallet {}",
+                wallet_key
+            );
+        }
+        $txn.signature = $txn.sign($keypair)?;
+        $txn.verify(&payer, &$txn.signature)?;
+        // Payments only carry the signature of the payer
+        Ok(Signatures {
+            owner: true,
+            gateway: true,
+            payer: true,
+        })
+    }};
+}
+
+/// Signs the empty owner and/or payer signature slots of the given
+/// transaction that belong to the given keypair and verifies the resulting
+/// signatures.
+pub fn cosign(envelope: &mut BlockchainTxn, keypair: &Keypair) -> Result<Signatures> {
+    match &mut envelope.txn {
+        Some(Txn::AddGateway(t)) => cosign_txn!(t, keypair, gateway),
+        Some(Txn::AssertLocation(t)) => cosign_txn!(t, keypair, gateway),
+        Some(Txn::Oui(t)) => cosign_txn!(t, keypair),
+        Some(Txn::StakeValidator(t)) => cosign_owner_txn!(t, keypair),
+        Some(Txn::UnstakeValidator(t)) => cosign_owner_txn!(t, keypair),
+        Some(Txn::Payment(t)) => cosign_payer_txn!(t, keypair),
+        Some(Txn::PaymentV2(t)) => cosign_payer_txn!(t, keypair),
+        _ => bail!("Unsupported transaction for cosign"),
+    }
+}
+
+fn print_txn(
+    envelope: &BlockchainTxn,
+    signatures: &Signatures,
+    network: Network,
+    status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Network", network],
+                ["Owner signed", signatures.owner],
+                ["Gateway signed", signatures.gateway],
+                ["Payer signed", signatures.payer],
+                ["Hash", status_str(status)]
+            ))?;
+            print_footer(status)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "network": network.to_string(),
+                "owner_signed": signatures.owner,
+                "gateway_signed": signatures.gateway,
+                "payer_signed": signatures.payer,
+                "hash": status_json(status),
+                "txn": encoding.encode(envelope)?,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{TxnEnvelope, TxnSign};
+    use helium_api::BlockchainTxnAddGatewayV1;
+
+    fn unsigned_add_gateway(owner: &Keypair, payer: &Keypair) -> BlockchainTxnAddGatewayV1 {
+        BlockchainTxnAddGatewayV1 {
+            owner: owner.public_key().to_vec(),
+            gateway: Keypair::default().public_key().to_vec(),
+            payer: payer.public_key().to_vec(),
+            staking_fee: 4_000_000,
+            fee: 65_000,
+            owner_signature: vec![],
+            gateway_signature: vec![],
+            payer_signature: vec![],
+        }
+    }
+
+    /// An add gateway transaction as emitted by the hotspot, signed by the
+    /// gateway
+    fn add_gateway(owner: &Keypair, payer: &Keypair) -> BlockchainTxnAddGatewayV1 {
+        let gateway = Keypair::default();
+        let mut txn = BlockchainTxnAddGatewayV1 {
+            gateway: gateway.public_key().to_vec(),
+            ..unsigned_add_gateway(owner, payer)
+        };
+        txn.gateway_signature = txn.sign(&gateway).expect("gateway signature");
+        txn
+    }
+
+    #[test]
+    fn owner_then_payer() {
+        let owner = Keypair::default();
+        let payer = Keypair::default();
+        let mut envelope = add_gateway(&owner, &payer).in_envelope();
+
+        let signatures = cosign(&mut envelope, &owner).expect("owner cosign");
+        assert_eq!(
+            Signatures {
+                owner: true,
+                gateway: true,
+                payer: false
+            },
+            signatures
+        );
+        let signatures = cosign(&mut envelope, &payer).expect("payer cosign");
+        assert!(signatures.is_complete());
+
+        let txn = BlockchainTxnAddGatewayV1::from_envelope(&envelope).expect("add gateway");
+        txn.verify(owner.public_key(), &txn.owner_signature)
+            .expect("owner signature");
+        txn.verify(payer.public_key(), &txn.payer_signature)
+            .expect("payer signature");
+    }
+
+    #[test]
+    fn payer_then_owner() {
+        let owner = Keypair::default();
+        let payer = Keypair::default();
+        let mut envelope = add_gateway(&owner, &payer).in_envelope();
+
+        cosign(&mut envelope, &payer).expect("payer cosign");
+        let signatures = cosign(&mut envelope, &owner).expect("owner cosign");
+        assert!(signatures.is_complete());
+
+        let txn = BlockchainTxnAddGatewayV1::from_envelope(&envelope).expect("add gateway");
+        txn.verify(owner.public_key(), &txn.owner_signature)
+            .expect("owner signature");
+        txn.verify(payer.public_key(), &txn.payer_signature)
+            .expect("payer signature");
+    }
+
+    #[test]
+    fn offline_stake() {
+        use crate::{cmd::submit_log::SubmitLog, traits::B64};
+        use helium_api::BlockchainTxnStakeValidatorV1;
+        use std::cell::RefCell;
+
+        #[derive(Default)]
+        struct Submitter {
+            submitted: RefCell<Option<BlockchainTxn>>,
+        }
+
+        impl SubmitTxn for Submitter {
+            fn submit(&self, envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+                *self.submitted.borrow_mut() = Some(envelope.clone());
+                Ok(PendingTxnStatus {
+                    hash: "hash".to_string(),
+                })
+            }
+
+            fn account_nonce(&self, _address: &PublicKey) -> Result<u64> {
+                Ok(0)
+            }
+        }
+
+        // Built online with the fee but without a signature
+        let owner = Keypair::default();
+        let unsigned = BlockchainTxnStakeValidatorV1 {
+            address: Keypair::default().public_key().to_vec(),
+            owner: owner.public_key().to_vec(),
+            stake: 1_000_000_000_000,
+            fee: 35_000,
+            owner_signature: vec![],
+        }
+        .in_envelope();
+        let encoded = unsigned.to_b64().expect("encode");
+
+        // Signed offline, only by the owner
+        let mut envelope = BlockchainTxn::from_b64(&encoded).expect("decode");
+        assert!(cosign(&mut envelope, &Keypair::default()).is_err());
+        let signatures = cosign(&mut envelope, &owner).expect("owner cosign");
+        assert!(signatures.is_complete());
+        assert!(cosign(&mut envelope, &owner).is_err());
+        let encoded = envelope.to_b64().expect("encode");
+
+        // Submitted online
+        let envelope = BlockchainTxn::from_b64(&encoded).expect("decode");
+        let submitter = Submitter::default();
+        let opts = SubmitOpts::default();
+        let status = submit_txn_logged(
+            &submitter,
+            &mut SubmitLog::default(),
+            &envelope,
+            true,
+            &opts,
+        )
+        .expect("submit");
+        assert_eq!("hash", status.expect("status").hash);
+
+        let submitted = submitter.submitted.borrow().clone().expect("submitted");
+        let txn = BlockchainTxnStakeValidatorV1::from_envelope(&submitted).expect("stake");
+        assert_eq!(35_000, txn.fee);
+        txn.verify(owner.public_key(), &txn.owner_signature)
+            .expect("owner signature");
+    }
+
+    #[test]
+    fn sponsored_payment() {
+        use crate::{
+            traits::{TxnFee, TxnFeeConfig},
+            txn::TxnBuilder,
+        };
+        use helium_api::BlockchainTxnPaymentV2;
+
+        let payer = Keypair::default();
+        let fee_config = TxnFeeConfig::with_txn_fees(5000, 24);
+        let unsigned = TxnBuilder::payment(&[(Keypair::default().public_key().clone(), 10_000)])
+            .nonce(1)
+            .fee_config(&fee_config)
+            .build(payer.public_key())
+            .expect("payment");
+        let mut envelope = unsigned.in_envelope();
+
+        assert!(cosign(&mut envelope, &Keypair::default()).is_err());
+        let signatures = cosign(&mut envelope, &payer).expect("payer cosign");
+        assert!(signatures.is_complete());
+        assert!(cosign(&mut envelope, &payer).is_err());
+
+        let txn = BlockchainTxnPaymentV2::from_envelope(&envelope).expect("payment");
+        txn.verify(payer.public_key(), &txn.signature)
+            .expect("payer signature");
+        // The fee is the same with and without the signature
+        assert_eq!(
+            unsigned.txn_fee(&fee_config).expect("fee"),
+            txn.txn_fee(&fee_config).expect("fee")
+        );
+        assert_eq!(unsigned.fee, txn.fee);
+    }
+
+    #[test]
+    fn missing_gateway_signature() {
+        let owner = Keypair::default();
+        let payer = Keypair::default();
+        let mut envelope = unsigned_add_gateway(&owner, &payer).in_envelope();
+
+        cosign(&mut envelope, &owner).expect("owner cosign");
+        let signatures = cosign(&mut envelope, &payer).expect("payer cosign");
+        assert!(signatures.owner && signatures.payer);
+        assert!(!signatures.gateway);
+        assert!(!signatures.is_complete());
+    }
+
+    #[test]
+    fn unrelated_wallet() {
+        let owner = Keypair::default();
+        let payer = Keypair::default();
+        let mut envelope = add_gateway(&owner, &payer).in_envelope();
+        assert!(cosign(&mut envelope, &Keypair::default()).is_err());
+
+        // Signing the same slot twice is also rejected
+        cosign(&mut envelope, &owner).expect("owner cosign");
+        assert!(cosign(&mut envelope, &owner).is_err());
+    }
+}
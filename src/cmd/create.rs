@@ -1,8 +1,15 @@
+#[cfg(feature = "slip39")]
+use crate::cmd::get_slip39_entropy;
 use crate::{
-    cmd::{get_file_extension, get_password, get_seed_words, verify, Opts},
+    cmd::{get_file_extension, get_password, get_seed_words, read_seed_words, verify, Opts},
     format::{self, Format},
-    keypair::{KeyTag, KeyType, Keypair, Network, KEYTYPE_ED25519_STR, NETTYPE_MAIN_STR},
-    mnemonic::mnemonic_to_entropy,
+    keypair::{
+        KeyTag, KeyType, Keypair, Network, PublicKey, KEYTYPE_ED25519_STR, NETTYPE_MAIN_STR,
+    },
+    mnemonic::{
+        derive_account_entropy, mnemonic_to_entropy_checked, resolve_words, seed_fingerprint,
+        Checksum, WordMatch,
+    },
     pwhash::PwHash,
     result::Result,
     wallet::Wallet,
@@ -18,6 +25,7 @@ use structopt::StructOpt;
 pub enum Cmd {
     Basic(Basic),
     Sharded(Sharded),
+    Watch(Watch),
 }
 
 #[derive(Debug, StructOpt)]
@@ -35,6 +43,35 @@ pub struct Basic {
     /// Use space separated seed words to create the wallet
     seed: bool,
 
+    #[structopt(long, requires = "seed")]
+    /// Read the seed words from the given file instead of prompting for
+    /// them, or from stdin with "-". The file must not be readable by
+    /// other users.
+    seed_file: Option<PathBuf>,
+
+    #[cfg(feature = "slip39")]
+    #[structopt(long, conflicts_with = "seed")]
+    /// Use SLIP-0039 mnemonic shares to create the wallet
+    slip39: bool,
+
+    #[structopt(long, requires = "seed")]
+    /// Accept seed words abbreviated to their first four or more letters.
+    /// The word each seed word was resolved to is shown for review.
+    abbreviated: bool,
+
+    #[structopt(long, requires = "seed")]
+    /// Verify the standard BIP39 checksum of the seed words instead of the
+    /// all zero checksum written by the Helium mobile wallet. A checksum
+    /// mismatch suggests swapping adjacent words that would fix it.
+    strict_checksum: bool,
+
+    #[structopt(long, requires = "seed")]
+    /// The account index to derive the key for from the seed words. Account
+    /// 0 is the same key as a wallet created without an account index.
+    /// Other accounts use the helium-wallet HMAC derivation, which is not
+    /// compatible with BIP32 derivation paths.
+    account: Option<u32>,
+
     #[structopt(long, default_value = NETTYPE_MAIN_STR)]
     /// The network to generate the wallet (testnet/mainnet)
     network: Network,
@@ -67,6 +104,35 @@ pub struct Sharded {
     /// Use space separated seed words to create the wallet
     seed: bool,
 
+    #[structopt(long, requires = "seed")]
+    /// Read the seed words from the given file instead of prompting for
+    /// them, or from stdin with "-". The file must not be readable by
+    /// other users.
+    seed_file: Option<PathBuf>,
+
+    #[cfg(feature = "slip39")]
+    #[structopt(long, conflicts_with = "seed")]
+    /// Use SLIP-0039 mnemonic shares to create the wallet
+    slip39: bool,
+
+    #[structopt(long, requires = "seed")]
+    /// Accept seed words abbreviated to their first four or more letters.
+    /// The word each seed word was resolved to is shown for review.
+    abbreviated: bool,
+
+    #[structopt(long, requires = "seed")]
+    /// Verify the standard BIP39 checksum of the seed words instead of the
+    /// all zero checksum written by the Helium mobile wallet. A checksum
+    /// mismatch suggests swapping adjacent words that would fix it.
+    strict_checksum: bool,
+
+    #[structopt(long, requires = "seed")]
+    /// The account index to derive the key for from the seed words. Account
+    /// 0 is the same key as a wallet created without an account index.
+    /// Other accounts use the helium-wallet HMAC derivation, which is not
+    /// compatible with BIP32 derivation paths.
+    account: Option<u32>,
+
     #[structopt(long, default_value = NETTYPE_MAIN_STR)]
     /// The network to generate the wallet (testnet/mainnet)
     network: Network,
@@ -76,32 +142,68 @@ pub struct Sharded {
     key_type: KeyType,
 }
 
+#[derive(Debug, StructOpt)]
+/// Create a watch-only wallet for an address. A watch-only wallet can be
+/// used to look up information like the balance or history of the address
+/// but can not sign transactions.
+pub struct Watch {
+    #[structopt(short, long, default_value = "wallet.key")]
+    /// Output file to store the wallet in
+    output: PathBuf,
+
+    #[structopt(long)]
+    /// Overwrite an existing file
+    force: bool,
+
+    #[structopt(short, long)]
+    /// The address to watch
+    address: PublicKey,
+}
+
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
         match self {
             Cmd::Basic(cmd) => cmd.run(opts),
             Cmd::Sharded(cmd) => cmd.run(opts),
+            Cmd::Watch(cmd) => cmd.run(opts),
         }
     }
 }
 
 impl Basic {
     pub fn run(&self, opts: Opts) -> Result {
-        let seed_words = if self.seed {
-            Some(get_seed_words()?)
+        let entropy = if self.seed {
+            Some(seed_entropy(
+                self.seed_file.as_deref(),
+                self.abbreviated,
+                self.strict_checksum,
+            )?)
         } else {
             None
         };
+        #[cfg(feature = "slip39")]
+        let entropy = if self.slip39 {
+            Some(get_slip39_entropy()?)
+        } else {
+            entropy
+        };
         let password = get_password(true)?;
         let tag = KeyTag {
             network: self.network,
             key_type: self.key_type,
         };
-        let keypair = gen_keypair(tag, seed_words)?;
+        let account = self.account;
+        let keypair = gen_keypair(tag, entropy, account)?;
         let format = format::Basic {
             pwhash: PwHash::argon2id13_default(),
         };
-        let wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Basic(format))?;
+        let wallet = encrypt_wallet(
+            &keypair,
+            entropy,
+            account,
+            password.as_bytes(),
+            Format::Basic(format),
+        )?;
         let mut writer = open_output_file(&self.output, !self.force)?;
         wallet.write(&mut writer)?;
         verify::print_result(&wallet, true, opts.format)
@@ -110,25 +212,42 @@ impl Basic {
 
 impl Sharded {
     pub fn run(&self, opts: Opts) -> Result {
-        let seed_words = if self.seed {
-            Some(get_seed_words()?)
+        let entropy = if self.seed {
+            Some(seed_entropy(
+                self.seed_file.as_deref(),
+                self.abbreviated,
+                self.strict_checksum,
+            )?)
         } else {
             None
         };
+        #[cfg(feature = "slip39")]
+        let entropy = if self.slip39 {
+            Some(get_slip39_entropy()?)
+        } else {
+            entropy
+        };
         let password = get_password(true)?;
         let tag = KeyTag {
             network: self.network,
             key_type: self.key_type,
         };
 
-        let keypair = gen_keypair(tag, seed_words)?;
+        let account = self.account;
+        let keypair = gen_keypair(tag, entropy, account)?;
         let format = format::Sharded {
             key_share_count: self.key_share_count,
             recovery_threshold: self.recovery_threshold,
             pwhash: PwHash::argon2id13_default(),
             key_shares: vec![],
         };
-        let wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Sharded(format))?;
+        let wallet = encrypt_wallet(
+            &keypair,
+            entropy,
+            account,
+            password.as_bytes(),
+            Format::Sharded(format),
+        )?;
 
         let extension = get_file_extension(&self.output);
         for (i, shard) in wallet.shards()?.iter().enumerate() {
@@ -142,17 +261,78 @@ impl Sharded {
     }
 }
 
-fn gen_keypair(tag: KeyTag, seed_words: Option<Vec<String>>) -> Result<Keypair> {
-    match seed_words {
-        Some(words) => {
-            let entropy = mnemonic_to_entropy(words)?;
+impl Watch {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = Wallet::watch(self.address.clone());
+        let mut writer = open_output_file(&self.output, !self.force)?;
+        wallet.write(&mut writer)?;
+        verify::print_result(&wallet, true, opts.format)
+    }
+}
+
+/// Reads seed words from the given file, or prompts for them, and converts
+/// them to entropy. With `abbreviated` seed words may be abbreviated and
+/// the wordlist word each seed word resolved to is reported. With
+/// `strict_checksum` the BIP39 checksum of the words is verified.
+fn seed_entropy(
+    seed_file: Option<&Path>,
+    abbreviated: bool,
+    strict_checksum: bool,
+) -> Result<[u8; 32]> {
+    let checksum = if strict_checksum {
+        Checksum::Bip39
+    } else {
+        Checksum::Zero
+    };
+    let matching = if abbreviated {
+        WordMatch::Prefix
+    } else {
+        WordMatch::Exact
+    };
+    let words = match seed_file {
+        Some(path) => read_seed_words(path)?,
+        None => get_seed_words(checksum, matching)?,
+    };
+    if abbreviated {
+        for (i, resolution) in resolve_words(&words)?.iter().enumerate() {
+            eprintln!("{:>2}. {}", i + 1, resolution);
+        }
+    }
+    mnemonic_to_entropy_checked(words, checksum, matching)
+}
+
+fn gen_keypair(tag: KeyTag, entropy: Option<[u8; 32]>, account: Option<u32>) -> Result<Keypair> {
+    match entropy {
+        Some(entropy) => {
+            let entropy = derive_account_entropy(&entropy, account.unwrap_or(0))?;
             Keypair::generate_from_entropy(tag, &entropy)
         }
         None => Ok(Keypair::generate(tag)),
     }
 }
 
-fn open_output_file(filename: &Path, create: bool) -> io::Result<fs::File> {
+/// Encrypts the key of a new wallet. A wallet derived from seed words for
+/// an account stores the fingerprint of the seed with its key.
+fn encrypt_wallet(
+    keypair: &Keypair,
+    entropy: Option<[u8; 32]>,
+    account: Option<u32>,
+    password: &[u8],
+    format: Format,
+) -> Result<Wallet> {
+    match (entropy, account) {
+        (Some(entropy), Some(account)) => Wallet::encrypt_account(
+            keypair,
+            account,
+            &seed_fingerprint(&entropy),
+            password,
+            format,
+        ),
+        _ => Wallet::encrypt(keypair, password, format),
+    }
+}
+
+pub(crate) fn open_output_file(filename: &Path, create: bool) -> io::Result<fs::File> {
     fs::OpenOptions::new()
         .write(true)
         .create(true)
@@ -0,0 +1,119 @@
+use crate::{
+    api::OraclePrice,
+    cmd::{
+        payee::{dc_to_hnt_bones, dc_to_usd, format_usd, round_usd},
+        *,
+    },
+    result::Result,
+};
+use helium_api::{Account, Hnt};
+use rust_decimal::Decimal;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Show the Data Credit balance of a wallet. The USD value is fixed at
+/// $0.00001 per DC, the HNT value is the HNT burned for the same number of
+/// DC at the current oracle price. The staked HNT balance of the account is
+/// shown alongside.
+pub struct Cmd {
+    /// Address to get the DC balance for. If not given the address of the
+    /// wallet is used.
+    #[structopt(short = "a", long = "address")]
+    address: Option<PublicKey>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let address = match &self.address {
+            Some(address) => address.clone(),
+            None => load_wallet(opts.files)?.public_key,
+        };
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, address.network))?;
+        let account = client.get_account(&address.to_string())?;
+        let price = client.get_oracle_price()?;
+        print_balance(&address, &account, &price, opts.fiat_decimals, opts.format)
+    }
+}
+
+fn print_balance(
+    address: &PublicKey,
+    account: &Account,
+    price: &OraclePrice,
+    fiat_decimals: u32,
+    format: OutputFormat,
+) -> Result {
+    let hnt = Hnt::from_bones(dc_to_hnt_bones(account.dc_balance, price.price)?);
+    let usd = dc_to_usd(account.dc_balance);
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Address", address],
+                ["Network", address.network],
+                ["DC Balance", account.dc_balance],
+                ["USD Value", format!("${}", format_usd(usd, fiat_decimals))],
+                ["HNT Value", hnt],
+                ["Staked Balance", Hnt::from_bones(account.staked_balance)],
+                [
+                    "Oracle Price",
+                    format!("${}", Decimal::new(price.price as i64, 8))
+                ],
+                ["Oracle Block", price.block]
+            ))?;
+            Ok(())
+        }
+        OutputFormat::Json => print_json(&balance_json(address, account, price, fiat_decimals)?),
+    }
+}
+
+fn balance_json(
+    address: &PublicKey,
+    account: &Account,
+    price: &OraclePrice,
+    fiat_decimals: u32,
+) -> Result<serde_json::Value> {
+    let hnt = Hnt::from_bones(dc_to_hnt_bones(account.dc_balance, price.price)?);
+    Ok(json!({
+        "address": address.to_string(),
+        "network": address.network.to_string(),
+        "dc_balance": account.dc_balance,
+        "usd_value": round_usd(dc_to_usd(account.dc_balance), fiat_decimals),
+        "hnt_value": hnt,
+        "staked_balance": Hnt::from_bones(account.staked_balance),
+        "oracle_price": price,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dc_usd_value() {
+        assert_eq!(Decimal::new(1, 0), dc_to_usd(100_000));
+        assert_eq!(Decimal::new(35, 2), dc_to_usd(35_000));
+        assert_eq!(Decimal::new(0, 0), dc_to_usd(0));
+    }
+
+    #[test]
+    fn staked_balance() {
+        let address = crate::keypair::Keypair::default().public_key().clone();
+        let account = Account {
+            staked_balance: 10_000 * 100_000_000,
+            ..testing::account(&address, 0, 100_000)
+        };
+        let price = OraclePrice {
+            price: 100_000_000,
+            block: 100,
+            timestamp: None,
+        };
+        let table = balance_json(&address, &account, &price, 2).expect("json");
+        assert_eq!(
+            json!(Hnt::from_bones(10_000 * 100_000_000)),
+            table["staked_balance"]
+        );
+        assert_eq!(json!(100_000), table["dc_balance"]);
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{cmd::*, result::Result};
+use structopt::StructOpt;
+
+mod balance;
+
+#[derive(Debug, StructOpt)]
+/// Commands for Data Credits (DC)
+pub enum Cmd {
+    /// Show the DC balance of a wallet and its USD and HNT value
+    Balance(balance::Cmd),
+}
+
+impl Cmd {
+    pub fn run(self, opts: Opts) -> Result {
+        match self {
+            Self::Balance(cmd) => cmd.run(opts),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    cmd::{
+        api_url, get_txn_fees, payee::dc_to_hnt_bones, print_json, print_table, Opts, OutputFormat,
+    },
+    keypair::Network,
+    result::{anyhow, Result},
+    traits::TxnFeeConfig,
+};
+use helium_api::Hnt;
+use rust_decimal::{prelude::*, Decimal};
+use serde_json::json;
+use std::num::NonZeroUsize;
+use structopt::StructOpt;
+
+/// The transaction fee multiplier used with --active
+const DEFAULT_TXN_FEE_MULTIPLIER: u64 = 5000;
+/// The number of bytes covered by a single DC used with --active
+const DEFAULT_DC_PAYLOAD_SIZE: usize = 24;
+
+#[derive(Debug, StructOpt)]
+/// Calculate the transaction fee in DC for a payload of a given number of
+/// bytes, and the HNT that is burned for it. The current fee configuration
+/// and oracle price are fetched from the API unless --active is given.
+pub struct Cmd {
+    /// The payload size in bytes
+    #[structopt(long)]
+    bytes: usize,
+
+    /// Use an active fee configuration instead of fetching the current one.
+    /// No API requests are made if the oracle price is given as well.
+    #[structopt(long)]
+    active: bool,
+
+    /// The transaction fee multiplier to use with --active
+    #[structopt(long, requires = "active")]
+    multiplier: Option<u64>,
+
+    /// The number of bytes covered by a single DC to use with --active
+    #[structopt(long, requires = "active")]
+    payload_size: Option<NonZeroUsize>,
+
+    /// The oracle price in USD per HNT to convert the fee to HNT with
+    #[structopt(long)]
+    oracle_price: Option<Decimal>,
+
+    /// The network to get the fee configuration and oracle price for
+    /// (mainnet/testnet)
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let url = || api_url(&opts.api_url, self.network);
+        let config = if self.active {
+            TxnFeeConfig::with_txn_fees(
+                self.multiplier.unwrap_or(DEFAULT_TXN_FEE_MULTIPLIER),
+                self.payload_size
+                    .map_or(DEFAULT_DC_PAYLOAD_SIZE, NonZeroUsize::get),
+            )
+        } else {
+            get_txn_fees(&opts.timeouts.client(url())?, &opts.submit)?
+        };
+        let oracle_price = match self.oracle_price {
+            Some(price) => Some(to_oracle_price(price)?),
+            // Stay offline with --active unless a price was given
+            None if self.active => None,
+            None => Some(opts.timeouts.client(url())?.get_oracle_price()?.price),
+        };
+        let fee = config.payload_fee(self.bytes);
+        let hnt = match oracle_price {
+            Some(price) => Some(Hnt::from_bones(dc_to_hnt_bones(fee, price)?)),
+            None => None,
+        };
+        print_cost(self.bytes, &config, fee, hnt, opts.format)
+    }
+}
+
+/// Converts a price in USD to the oracle price unit of 1/100_000_000 USD
+fn to_oracle_price(usd: Decimal) -> Result<u64> {
+    (usd * Decimal::from(100_000_000u64))
+        .floor()
+        .to_u64()
+        .filter(|price| *price > 0)
+        .ok_or_else(|| anyhow!("Invalid oracle price {}", usd))
+}
+
+fn print_cost(
+    bytes: usize,
+    config: &TxnFeeConfig,
+    fee: u64,
+    hnt: Option<Hnt>,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Payload size (bytes)", bytes],
+                ["Bytes per DC", config.dc_payload_size()],
+                ["Fee multiplier", config.txn_fee_multiplier()],
+                ["Fee (DC)", fee],
+                [
+                    "Fee (HNT)",
+                    hnt.map_or_else(|| "-".to_string(), |v| v.to_string())
+                ]
+            ))?;
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "bytes": bytes,
+                "dc_payload_size": config.dc_payload_size(),
+                "txn_fee_multiplier": config.txn_fee_multiplier(),
+                "fee": fee,
+                "fee_hnt": hnt.map(|v| v.to_string()),
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oracle_price_units() {
+        let price = Decimal::from_str("1.5").expect("price");
+        assert_eq!(150_000_000, to_oracle_price(price).expect("oracle price"));
+        assert!(to_oracle_price(Decimal::from(0)).is_err());
+        assert!(to_oracle_price(Decimal::from(-1)).is_err());
+    }
+
+    #[test]
+    fn zero_payload_size() {
+        let args = |size: &'static str| {
+            vec![
+                "dc-cost",
+                "--bytes",
+                "100",
+                "--active",
+                "--payload-size",
+                size,
+            ]
+        };
+        assert!(Cmd::from_iter_safe(args("0")).is_err());
+        let cmd = Cmd::from_iter_safe(args("24")).expect("payload size");
+        assert_eq!(Some(24), cmd.payload_size.map(NonZeroUsize::get));
+    }
+}
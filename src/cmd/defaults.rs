@@ -0,0 +1,285 @@
+use crate::{
+    cmd::config_dir,
+    result::{anyhow, Result},
+};
+use serde::Deserialize;
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Defaults for global options read from config.toml in the user
+/// configuration directory, like:
+///
+/// ```toml
+/// api_url = "https://api.example.com/v1"
+/// format = "json"
+/// ```
+///
+/// An option given on the command line takes precedence over its
+/// environment variable, which takes precedence over the config file,
+/// which in turn takes precedence over the built in default.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Defaults {
+    api_url: Option<String>,
+    format: Option<String>,
+    encoding: Option<String>,
+    fiat_decimals: Option<u32>,
+    audit_log: Option<PathBuf>,
+    connect_timeout: Option<u64>,
+    request_timeout: Option<u64>,
+    wait_timeout: Option<u64>,
+}
+
+impl Defaults {
+    /// The path of the config file in the user configuration directory
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Loads the defaults from the given file. A missing file has no
+    /// defaults.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| anyhow!("Invalid config file {}: {}", path.display(), e)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Inserts the configured defaults as global options after the
+    /// program name. Options that are given as global options in the
+    /// arguments, or whose environment variable is set according to
+    /// `is_env_set`, are left out. Options of the subcommand, like the
+    /// `--format` of `export pubkey`, do not count.
+    pub fn apply(&self, args: Vec<OsString>, is_env_set: impl Fn(&str) -> bool) -> Vec<OsString> {
+        let mut options: Vec<(&str, Option<&str>, String)> = Vec::new();
+        if let Some(url) = &self.api_url {
+            options.push(("--api-url", Some("HELIUM_WALLET_API_URL"), url.clone()));
+        }
+        if let Some(format) = &self.format {
+            options.push(("--format", None, format.clone()));
+        }
+        if let Some(encoding) = &self.encoding {
+            options.push(("--encoding", None, encoding.clone()));
+        }
+        if let Some(decimals) = self.fiat_decimals {
+            options.push(("--fiat-decimals", None, decimals.to_string()));
+        }
+        if let Some(path) = &self.audit_log {
+            options.push((
+                "--audit-log",
+                Some("HELIUM_WALLET_AUDIT_LOG"),
+                path.display().to_string(),
+            ));
+        }
+        for &(flag, timeout) in &[
+            ("--connect-timeout", self.connect_timeout),
+            ("--request-timeout", self.request_timeout),
+            ("--wait-timeout", self.wait_timeout),
+        ] {
+            if let Some(timeout) = timeout {
+                options.push((flag, None, timeout.to_string()));
+            }
+        }
+
+        let mut args = args.into_iter();
+        let mut result: Vec<OsString> = args.next().into_iter().collect();
+        let rest: Vec<OsString> = args.collect();
+        let global = global_args(&rest);
+        for (flag, env_var, value) in options {
+            if has_flag(global, flag) || env_var.map_or(false, |var| is_env_set(var)) {
+                continue;
+            }
+            result.push(flag.into());
+            result.push(value.into());
+        }
+        result.extend(rest);
+        result
+    }
+}
+
+/// The global options that take no value. Every other global option is
+/// followed by its value unless given as `--option=value`.
+const GLOBAL_SWITCHES: &[&str] = &[
+    "--allow-insecure-tls",
+    "--no-color",
+    "--dry-run",
+    "--force-nonce",
+    "--help",
+    "-h",
+    "--version",
+    "-V",
+];
+
+/// The leading arguments up to the first subcommand, which are the global
+/// options and their values
+fn global_args(args: &[OsString]) -> &[OsString] {
+    let mut expect_value = false;
+    for (i, arg) in args.iter().enumerate() {
+        if expect_value {
+            expect_value = false;
+            continue;
+        }
+        let arg = arg.to_string_lossy();
+        if arg == "--" || !arg.starts_with('-') {
+            return &args[..i];
+        }
+        // Values can be attached as --option=value or -fvalue
+        let attached = arg.contains('=') || (!arg.starts_with("--") && arg.len() > 2);
+        expect_value = !attached && !GLOBAL_SWITCHES.contains(&&*arg);
+    }
+    args
+}
+
+/// Whether the given long flag appears in the arguments, either as
+/// `--flag value` or `--flag=value`
+fn has_flag(args: &[OsString], flag: &str) -> bool {
+    args.iter()
+        .filter_map(|arg| arg.to_str())
+        .any(|arg| arg == flag || arg.starts_with(&format!("{}=", flag)))
+}
+
+/// Returns the command line arguments with the defaults from the config
+/// file applied
+pub fn args() -> Result<Vec<OsString>> {
+    let args = env::args_os().collect();
+    match Defaults::path() {
+        Some(path) => Ok(Defaults::load(&path)?.apply(args, |var| env::var_os(var).is_some())),
+        None => Ok(args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cmd::{resolve_api_url, Opts, OutputFormat},
+        keypair::Network,
+    };
+    use structopt::StructOpt;
+
+    fn opts(defaults: &Defaults, args: &[&str], env: &[&str]) -> Opts {
+        let args = args.iter().map(OsString::from).collect();
+        Opts::from_iter(defaults.apply(args, |var| env.contains(&var)))
+    }
+
+    #[test]
+    fn load_defaults() {
+        let path =
+            std::env::temp_dir().join(format!("helium-wallet-config-{}.toml", std::process::id()));
+        assert_eq!(Defaults::default(), Defaults::load(&path).expect("missing"));
+
+        fs::write(&path, "format = \"json\"\nfiat_decimals = 4\n").expect("write");
+        let defaults = Defaults::load(&path);
+        fs::write(&path, "colour = \"blue\"\n").expect("write");
+        let unknown = Defaults::load(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(
+            Defaults {
+                format: Some("json".to_string()),
+                fiat_decimals: Some(4),
+                ..Default::default()
+            },
+            defaults.expect("defaults")
+        );
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn precedence() {
+        const ENV_URL: &str = "https://env.example.com/v1";
+        let defaults = Defaults {
+            api_url: Some("https://config.example.com/v1".to_string()),
+            format: Some("json".to_string()),
+            fiat_decimals: Some(4),
+            ..Default::default()
+        };
+        let resolve = |opts: &Opts, env_url: Option<&str>| {
+            resolve_api_url(
+                opts.api_url.clone(),
+                env_url.map(str::to_string),
+                Network::MainNet,
+            )
+        };
+
+        // flag > env > config
+        let flag = opts(
+            &defaults,
+            &["helium-wallet", "--api-url", "https://flag.example.com/v1"],
+            &["HELIUM_WALLET_API_URL"],
+        );
+        assert_eq!("https://flag.example.com/v1", resolve(&flag, Some(ENV_URL)));
+        let env = opts(&defaults, &["helium-wallet"], &["HELIUM_WALLET_API_URL"]);
+        assert_eq!(ENV_URL, resolve(&env, Some(ENV_URL)));
+        let config = opts(&defaults, &["helium-wallet"], &[]);
+        assert_eq!("https://config.example.com/v1", resolve(&config, None));
+
+        // config > default
+        assert!(matches!(config.format, OutputFormat::Json));
+        assert_eq!(4, config.fiat_decimals);
+        let default = opts(&Defaults::default(), &["helium-wallet"], &[]);
+        assert_eq!(2, default.fiat_decimals);
+        assert_eq!(None, default.api_url);
+
+        // flag > config, in both forms
+        let flag = opts(
+            &defaults,
+            &["helium-wallet", "--format", "table", "--fiat-decimals=0"],
+            &[],
+        );
+        assert!(matches!(flag.format, OutputFormat::Table));
+        assert_eq!(0, flag.fiat_decimals);
+    }
+
+    #[test]
+    fn subcommand_options() {
+        let defaults = Defaults {
+            format: Some("json".to_string()),
+            ..Default::default()
+        };
+        let apply = |args: &[&str]| -> Vec<String> {
+            let args = args.iter().map(OsString::from).collect();
+            defaults
+                .apply(args, |_| false)
+                .into_iter()
+                .map(|arg| arg.into_string().expect("utf8"))
+                .collect()
+        };
+
+        // The --format of a subcommand leaves the global default in place
+        assert_eq!(
+            vec![
+                "helium-wallet",
+                "--format",
+                "json",
+                "-f",
+                "pay",
+                "--no-color",
+                "export",
+                "pubkey",
+                "--format",
+                "der"
+            ],
+            apply(&[
+                "helium-wallet",
+                "-f",
+                "pay",
+                "--no-color",
+                "export",
+                "pubkey",
+                "--format",
+                "der"
+            ])
+        );
+        // A global --format replaces it
+        assert_eq!(
+            vec!["helium-wallet", "--format=table", "balance"],
+            apply(&["helium-wallet", "--format=table", "balance"])
+        );
+    }
+}
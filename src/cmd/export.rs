@@ -0,0 +1,114 @@
+use crate::{
+    cmd::{load_wallet, open_output_file, Opts},
+    keypair::{key_type, public_key_der, public_key_pem, KeyType, PublicKey},
+    result::{bail, Result},
+};
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+/// Export wallet keys for use with other tools
+#[derive(Debug, StructOpt)]
+pub enum Cmd {
+    Pubkey(Pubkey),
+}
+
+arg_enum! {
+    /// The encodings a public key can be exported in
+    #[derive(Debug, Clone, Copy)]
+    pub enum KeyEncoding {
+        Pem,
+        Der,
+        B58,
+    }
+}
+
+#[derive(Debug, StructOpt)]
+/// Export the public key of the wallet. "pem" and "der" write the key as a
+/// SubjectPublicKeyInfo structure, which only ed25519 keys support, and
+/// "b58" writes the Helium address. An ecc_compact key can only be
+/// exported as "b58". No password is needed.
+pub struct Pubkey {
+    /// The encoding to export the public key in
+    #[structopt(long,
+                possible_values = &["pem", "der", "b58"],
+                case_insensitive = true,
+                default_value = "pem")]
+    format: KeyEncoding,
+
+    /// File to write the public key to. If not given the key is written to
+    /// stdout
+    #[structopt(long, short = "o")]
+    output: Option<PathBuf>,
+
+    /// Overwrite an existing output file
+    #[structopt(long)]
+    force: bool,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Pubkey(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+impl Pubkey {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        let exported = export_pubkey(&wallet.public_key, self.format)?;
+        match &self.output {
+            Some(path) => open_output_file(path, !self.force)?.write_all(&exported)?,
+            None => io::stdout().write_all(&exported)?,
+        }
+        Ok(())
+    }
+}
+
+/// Encodes the public key in the given encoding. An ecc_compact key only
+/// holds the x-coordinate of its point, so it is refused for the
+/// SubjectPublicKeyInfo encodings rather than exported in a form other
+/// tools can not read.
+fn export_pubkey(public_key: &PublicKey, encoding: KeyEncoding) -> Result<Vec<u8>> {
+    if key_type(public_key)? == KeyType::EccCompact && !matches!(encoding, KeyEncoding::B58) {
+        bail!(
+            "ecc_compact keys can not be exported as {}, use --format b58 for the address",
+            encoding.to_string().to_lowercase()
+        );
+    }
+    match encoding {
+        KeyEncoding::Pem => Ok(public_key_pem(public_key)?.into_bytes()),
+        KeyEncoding::Der => public_key_der(public_key),
+        KeyEncoding::B58 => Ok(format!("{}\n", public_key).into_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::{KeyTag, Keypair, Network};
+
+    #[test]
+    fn ecc_compact_pubkey() {
+        let ecc = Keypair::generate(KeyTag {
+            network: Network::MainNet,
+            key_type: KeyType::EccCompact,
+        });
+        for encoding in &[KeyEncoding::Pem, KeyEncoding::Der] {
+            let err = export_pubkey(ecc.public_key(), *encoding).expect_err("refused");
+            assert!(err
+                .to_string()
+                .starts_with("ecc_compact keys can not be exported"));
+        }
+        assert_eq!(
+            format!("{}\n", ecc.public_key()).into_bytes(),
+            export_pubkey(ecc.public_key(), KeyEncoding::B58).expect("b58")
+        );
+
+        let keypair = Keypair::default();
+        assert!(export_pubkey(keypair.public_key(), KeyEncoding::Der).is_ok());
+    }
+}
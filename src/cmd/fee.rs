@@ -0,0 +1,111 @@
+use crate::{
+    cmd::{api_url, get_txn_fees, print_json, print_table, Opts, OutputFormat},
+    keypair::{Keypair, Network},
+    result::{bail, Result},
+    traits::{TxnFee, TxnFeeConfig},
+    txn::TxnBuilder,
+};
+use serde_json::json;
+use structopt::StructOpt;
+
+/// The amount in bones of each placeholder payment, 1 HNT
+const PLACEHOLDER_AMOUNT: u64 = 100_000_000;
+/// The nonce of the placeholder payment
+const PLACEHOLDER_NONCE: u64 = 1;
+
+#[derive(Debug, StructOpt)]
+/// Estimate transaction fees before all details of a transaction are known
+pub enum Cmd {
+    Payment(Payment),
+}
+
+#[derive(Debug, StructOpt)]
+/// Estimate the fee of a payment to a given number of payees. A payment
+/// with generated placeholder payees of 1 HNT each is encoded to calculate
+/// the fee with the current fee configuration. The fee of the actual
+/// payment can differ slightly since the encoded size of amounts and the
+/// nonce depends on their values.
+pub struct Payment {
+    /// The number of payees of the payment
+    #[structopt(long)]
+    payees: usize,
+
+    /// The network to get the fee configuration for (mainnet/testnet)
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Payment(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+impl Payment {
+    pub fn run(&self, opts: Opts) -> Result {
+        let client = opts.timeouts.client(api_url(&opts.api_url, self.network))?;
+        let fee_config = get_txn_fees(&client, &opts.submit)?;
+        let (size, fee) = payment_fee(self.payees, &fee_config)?;
+        print_estimate(self.payees, size, fee, opts.format)
+    }
+}
+
+/// Builds a payment to the given number of placeholder payees and returns
+/// its encoded size in bytes and fee in DC
+fn payment_fee(payees: usize, fee_config: &TxnFeeConfig) -> Result<(usize, u64)> {
+    if payees == 0 {
+        bail!("At least one payee is required");
+    }
+    let payees: Vec<_> = (0..payees)
+        .map(|_| (Keypair::default().public_key().clone(), PLACEHOLDER_AMOUNT))
+        .collect();
+    let payer = Keypair::default().public_key().clone();
+    let txn = TxnBuilder::payment(&payees)
+        .nonce(PLACEHOLDER_NONCE)
+        .fee_config(fee_config)
+        .build(&payer)?;
+    Ok((txn.txn_size()?, txn.fee))
+}
+
+fn print_estimate(payees: usize, size: usize, fee: u64, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Payees", payees],
+                ["Size (bytes)", size],
+                ["Estimated Fee (DC)", fee]
+            ))?;
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "payees": payees,
+                "size": size,
+                "fee": fee,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payment_fee_estimate() {
+        let fee_config = TxnFeeConfig::with_txn_fees(1, 24);
+        let (size, fee) = payment_fee(1, &fee_config).expect("estimate");
+        assert_eq!(((size + 23) / 24) as u64, fee);
+
+        // Every payee adds its address and amount to the encoding
+        let (size_10, fee_10) = payment_fee(10, &fee_config).expect("estimate");
+        assert!(size_10 > size + 9 * 33);
+        assert!(fee_10 > fee);
+
+        assert!(payment_fee(0, &fee_config).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{
+    cmd::{print_json, print_table, Opts, OutputFormat},
+    keypair::Keypair,
+    result::{anyhow, Result},
+    traits::{TxnFee, TxnFeeConfig, TxnStakingFee},
+};
+use helium_api::{
+    BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1, BlockchainTxnOuiV1,
+    BlockchainTxnPaymentV2, Payment,
+};
+use prettytable::{format, Table};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Commands for transaction fee configurations
+pub enum Cmd {
+    Diff(Diff),
+}
+
+#[derive(Debug, StructOpt)]
+/// Compare two json encoded fee configurations. The changed fields are
+/// listed along with the resulting fees for a set of sample transactions.
+/// The fee configuration can be the json output of "vars current".
+pub struct Diff {
+    /// The old fee configuration
+    old: PathBuf,
+
+    /// The new fee configuration
+    new: PathBuf,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Diff(cmd) => cmd.run(opts),
+        }
+    }
+}
+
+impl Diff {
+    pub fn run(&self, opts: Opts) -> Result {
+        let old = read_config(&self.old)?;
+        let new = read_config(&self.new)?;
+        let changes = config_changes(&old, &new)?;
+        let fees = sample_fees(&old, &new)?;
+        print_diff(&changes, &fees, opts.format)
+    }
+}
+
+fn read_config(path: &Path) -> Result<TxnFeeConfig> {
+    let file =
+        fs::File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+#[derive(Debug, Serialize)]
+struct Change {
+    field: String,
+    old: Value,
+    new: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct SampleFee {
+    txn: &'static str,
+    size: usize,
+    old: u64,
+    new: u64,
+}
+
+fn config_changes(old: &TxnFeeConfig, new: &TxnFeeConfig) -> Result<Vec<Change>> {
+    let old = serde_json::to_value(old)?;
+    let new = serde_json::to_value(new)?;
+    let mut changes = vec![];
+    if let (Value::Object(old), Value::Object(new)) = (old, new) {
+        for (field, old_value) in old {
+            let new_value = new.get(&field).cloned().unwrap_or(Value::Null);
+            if old_value != new_value {
+                changes.push(Change {
+                    field,
+                    old: old_value,
+                    new: new_value,
+                });
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Calculates the transaction and staking fees of a set of representative
+/// transactions under both the old and the new fee configuration.
+fn sample_fees(old: &TxnFeeConfig, new: &TxnFeeConfig) -> Result<Vec<SampleFee>> {
+    let key = || Keypair::default().public_key().to_vec();
+    let payment = BlockchainTxnPaymentV2 {
+        payer: key(),
+        payments: vec![Payment {
+            payee: key(),
+            amount: 100_000_000,
+        }],
+        nonce: 1,
+        fee: 0,
+        signature: vec![],
+    };
+    let add_gateway = BlockchainTxnAddGatewayV1 {
+        owner: key(),
+        gateway: key(),
+        payer: key(),
+        ..Default::default()
+    };
+    let assert_location = BlockchainTxnAssertLocationV1 {
+        owner: key(),
+        gateway: key(),
+        payer: key(),
+        location: "8c28308280c15ff".to_string(),
+        nonce: 1,
+        ..Default::default()
+    };
+    let oui = BlockchainTxnOuiV1 {
+        owner: key(),
+        addresses: vec![key()],
+        requested_subnet_size: 8,
+        oui: 1,
+        ..Default::default()
+    };
+    Ok(vec![
+        SampleFee {
+            txn: "payment_v2",
+            size: payment.txn_size()?,
+            old: payment.txn_fee(old)?,
+            new: payment.txn_fee(new)?,
+        },
+        SampleFee {
+            txn: "add_gateway_v1",
+            size: add_gateway.txn_size()?,
+            old: add_gateway.txn_fee(old)?,
+            new: add_gateway.txn_fee(new)?,
+        },
+        SampleFee {
+            txn: "add_gateway_v1 staking",
+            size: add_gateway.txn_size()?,
+            old: add_gateway.txn_staking_fee(old)?,
+            new: add_gateway.txn_staking_fee(new)?,
+        },
+        SampleFee {
+            txn: "assert_location_v1",
+            size: assert_location.txn_size()?,
+            old: assert_location.txn_fee(old)?,
+            new: assert_location.txn_fee(new)?,
+        },
+        SampleFee {
+            txn: "assert_location_v1 staking",
+            size: assert_location.txn_size()?,
+            old: assert_location.txn_staking_fee(old)?,
+            new: assert_location.txn_staking_fee(new)?,
+        },
+        SampleFee {
+            txn: "oui_v1",
+            size: oui.txn_size()?,
+            old: oui.txn_fee(old)?,
+            new: oui.txn_fee(new)?,
+        },
+        SampleFee {
+            txn: "oui_v1 staking",
+            size: oui.txn_size()?,
+            old: oui.txn_staking_fee(old)?,
+            new: oui.txn_staking_fee(new)?,
+        },
+    ])
+}
+
+fn print_diff(changes: &[Change], fees: &[SampleFee], format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Field", "Old", "New"]);
+            for change in changes {
+                table.add_row(row![change.field, change.old, change.new]);
+            }
+            print_table(&table)?;
+
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row![
+                "Transaction",
+                "Size (bytes)",
+                "Old Fee (DC)",
+                "New Fee (DC)"
+            ]);
+            for fee in fees {
+                table.add_row(row![fee.txn, fee.size, fee.old, fee.new]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "changes": changes,
+                "fees": fees,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(multiplier: u64) -> TxnFeeConfig {
+        serde_json::from_value(json!({
+            "txn_fees": true,
+            "txn_fee_multiplier": multiplier,
+            "staking_fee_txn_oui_v1": 10_000_000,
+            "staking_fee_txn_oui_v1_per_address": 10_000_000,
+            "staking_fee_txn_add_gateway_v1": 4_000_000,
+            "staking_fee_txn_assert_location_v1": 1_000_000,
+        }))
+        .expect("fee config")
+    }
+
+    #[test]
+    fn diff_changes() {
+        let changes = config_changes(&config(5000), &config(5000)).expect("changes");
+        assert!(changes.is_empty());
+
+        let changes = config_changes(&config(5000), &config(10000)).expect("changes");
+        assert_eq!(1, changes.len());
+        assert_eq!("txn_fee_multiplier", changes[0].field);
+        assert_eq!(json!(5000), changes[0].old);
+        assert_eq!(json!(10000), changes[0].new);
+    }
+
+    #[test]
+    fn diff_sample_fees() {
+        let fees = sample_fees(&config(5000), &config(10000)).expect("fees");
+        for fee in fees {
+            if fee.txn.ends_with("staking") {
+                assert_eq!(fee.old, fee.new);
+            } else {
+                assert_eq!(fee.old * 2, fee.new);
+            }
+        }
+    }
+}
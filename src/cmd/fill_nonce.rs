@@ -0,0 +1,175 @@
+use crate::{
+    cmd::{
+        api_url, check_batch, decrypt_wallet, fetch_payer_account, get_txn_fees, load_wallet,
+        print_json, print_table, submit_batch, Opts, OutputFormat,
+    },
+    keypair::{Network, PublicKey},
+    result::{bail, Result},
+    traits::{TxnEnvelope, TxnSign},
+    txn::TxnBuilder,
+};
+use dialoguer::Confirm;
+use prettytable::{format, Table};
+use serde::Serialize;
+use std::ops::RangeInclusive;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Fill a gap in the nonces of the wallet account. When a pending
+/// transaction is dropped all later transactions stall until its nonce is
+/// used. This submits a minimal payment of one bone to the given payee
+/// for every nonce in the given range. The chain rejects payments to the
+/// payer itself, so the payee has to be another address, like a second
+/// wallet of your own. Each payment costs a transaction fee, so committing
+/// asks for confirmation. The nonces of dropped transactions are expected
+/// in the submit log, so they are filled without --force-nonce. If a
+/// payment fails the payments for later nonces are not submitted and the
+/// command fails after showing the outcome of every nonce.
+pub struct Cmd {
+    /// First nonce to fill
+    #[structopt(long)]
+    from: u64,
+
+    /// Last nonce to fill
+    #[structopt(long)]
+    to: u64,
+
+    /// Address to send the one bone payments to. Must not be the wallet
+    /// address
+    #[structopt(long)]
+    payee: PublicKey,
+
+    /// Commit the payments to the API
+    #[structopt(long)]
+    commit: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Filled {
+    nonce: u64,
+    fee: u64,
+    hash: Option<String>,
+    status: &'static str,
+    reason: Option<String>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
+        if self.payee == wallet.public_key {
+            bail!("The payee can not be the wallet itself");
+        }
+        let account = fetch_payer_account(&client, &wallet.public_key)?;
+        let nonces = nonce_range(self.from, self.to, account.nonce)?;
+
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
+        let fee_config = get_txn_fees(&client, &opts.submit)?;
+        let mut txns = Vec::with_capacity(nonces.clone().count());
+        for nonce in nonces {
+            let mut txn = TxnBuilder::payment(&[(self.payee.clone(), 1)])
+                .nonce(nonce)
+                .fee_config(&fee_config)
+                .build(keypair.public_key())?;
+            txn.signature = txn.sign(&keypair)?;
+            txns.push(txn);
+        }
+
+        let total_fee: u64 = txns.iter().map(|txn| txn.fee).sum();
+        if self.commit
+            && !opts.submit.dry_run
+            && !Confirm::new()
+                .with_prompt(format!(
+                    "Submit {} payments for a total fee of {} DC?",
+                    txns.len(),
+                    total_fee
+                ))
+                .interact()?
+        {
+            bail!("Not filling nonces");
+        }
+        let envelopes: Vec<_> = txns.iter().map(|txn| txn.in_envelope()).collect();
+        let results = submit_batch(&client, &envelopes, self.commit, &opts.submit)?;
+        let filled: Vec<Filled> = txns
+            .iter()
+            .zip(&results)
+            .map(|(txn, result)| Filled {
+                nonce: txn.nonce,
+                fee: txn.fee,
+                hash: result.hash().map(str::to_string),
+                status: result.status(),
+                reason: result.reason(),
+            })
+            .collect();
+        print_filled(
+            &filled,
+            wallet.public_key.network,
+            self.commit && !opts.submit.dry_run,
+            opts.format,
+        )?;
+        check_batch(&results)
+    }
+}
+
+/// Validates the range of nonces to fill against the last confirmed nonce
+/// of the account.
+fn nonce_range(from: u64, to: u64, account_nonce: u64) -> Result<RangeInclusive<u64>> {
+    if from > to {
+        bail!("Invalid nonce range {} to {}", from, to);
+    }
+    if from <= account_nonce {
+        bail!(
+            "Nonce {} is already used, the account nonce is {}",
+            from,
+            account_nonce
+        );
+    }
+    Ok(from..=to)
+}
+
+fn print_filled(
+    filled: &[Filled],
+    network: Network,
+    committed: bool,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Network", "Nonce", "Fee", "Hash", "Status", "Reason"]);
+            for entry in filled {
+                table.add_row(row![
+                    network,
+                    entry.nonce,
+                    entry.fee,
+                    entry.hash.as_deref().unwrap_or("none"),
+                    entry.status,
+                    entry.reason.as_deref().unwrap_or("")
+                ]);
+            }
+            print_table(&table)?;
+            if !committed {
+                println!("\nPreview mode: use --commit to submit the transactions to the network");
+            }
+            Ok(())
+        }
+        OutputFormat::Json => print_json(filled),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_nonce_range() {
+        assert_eq!(6..=8, nonce_range(6, 8, 5).expect("range"));
+        assert_eq!(6..=6, nonce_range(6, 6, 5).expect("range"));
+        // Nonces at or before the account nonce are used
+        assert!(nonce_range(5, 8, 5).is_err());
+        assert!(nonce_range(8, 6, 5).is_err());
+    }
+}
@@ -0,0 +1,568 @@
+use crate::{
+    api,
+    cmd::{
+        api_url, labels, load_wallet, payee::format_usd, write_json, write_table, Opts, OutputFile,
+        OutputFormat,
+    },
+    keypair::PublicKey,
+    result::{anyhow, bail, Error, Result},
+};
+use dialoguer::Confirm;
+use prettytable::{format, Table};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use structopt::StructOpt;
+
+/// The number of blocks in a block range above which listing the activity
+/// asks for confirmation, about two months of blocks
+const MAX_BLOCK_RANGE: u64 = 100_000;
+
+#[derive(Debug, StructOpt)]
+/// List recent activity for a wallet, newest first. Amounts are given in
+/// HNT as seen from the wallet, with outgoing amounts being negative.
+pub struct Cmd {
+    /// Address to list activity for. If not given the address of the
+    /// wallet is used.
+    #[structopt(short = "a", long = "address")]
+    address: Option<PublicKey>,
+
+    /// Only list transactions of the given type (e.g. payment_v2). Use this
+    /// option multiple times for multiple types.
+    #[structopt(long = "type", number_of_values(1))]
+    types: Vec<String>,
+
+    /// Maximum number of transactions to list
+    #[structopt(long, default_value = "50")]
+    limit: usize,
+
+    /// Only list transactions since the given block height or UTC date
+    /// (YYYY-MM-DD)
+    #[structopt(long)]
+    since: Option<Since>,
+
+    /// Only list transactions at or after the given block height
+    #[structopt(long, conflicts_with = "since")]
+    from_block: Option<u64>,
+
+    /// Only list transactions at or before the given block height
+    #[structopt(long)]
+    to_block: Option<u64>,
+
+    /// Output the activity as CSV instead of the selected output format
+    #[structopt(long)]
+    csv: bool,
+
+    /// Export the activity with an amount to the given CSV file for tax
+    /// reporting, with the USD value of each amount at the oracle price of
+    /// its block
+    #[structopt(long, conflicts_with = "csv")]
+    export: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    output: OutputFile,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let address = match &self.address {
+            Some(address) => address.clone(),
+            None => load_wallet(opts.files)?.public_key,
+        };
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, address.network))?;
+        let blocks = block_range(self.from_block, self.to_block)?;
+        if let (Some(from), Some(to)) = (self.from_block, self.to_block) {
+            if to - from > MAX_BLOCK_RANGE
+                && !Confirm::new()
+                    .with_prompt(format!(
+                        "List activity for a range of {} blocks?",
+                        to - from + 1
+                    ))
+                    .interact()?
+            {
+                bail!("Not listing activity");
+            }
+        }
+
+        let mut activity = Vec::new();
+        let mut cursor: Option<String> = None;
+        'fetch: loop {
+            let page = client.get_account_activity(
+                &address.to_string(),
+                &self.types,
+                cursor.as_deref(),
+            )?;
+            for txn in &page.data {
+                let entry = Activity::from_txn(&address, txn)?;
+                // Activity is returned newest first
+                if entry.block > *blocks.end() {
+                    continue;
+                }
+                if entry.block < *blocks.start() {
+                    break 'fetch;
+                }
+                if let Some(since) = self.since {
+                    // Activity is returned newest first
+                    if !since.includes(&entry) {
+                        break 'fetch;
+                    }
+                }
+                activity.push(entry);
+                if activity.len() >= self.limit {
+                    break 'fetch;
+                }
+            }
+            match page.cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        // Local labels given with --label when the transactions were
+        // submitted
+        if let Some(path) = labels::labels_path() {
+            let labels = labels::read(&path)?;
+            for entry in activity.iter_mut() {
+                entry.label = labels.get(&entry.hash).cloned();
+            }
+        }
+
+        if let Some(path) = &self.export {
+            let rows = export_tax_csv(&client, path, &activity, opts.fiat_decimals)?;
+            eprintln!("Exported {} transactions to {}", rows, path.display());
+            return Ok(());
+        }
+
+        let mut writer = self.output.writer()?;
+        if self.csv {
+            write_csv(&mut writer, &activity)?;
+        } else {
+            write_activity(&mut writer, &activity, opts.format)?;
+        }
+        writer.finish()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Activity {
+    hash: String,
+    #[serde(rename = "type")]
+    kind: String,
+    amount: Option<Decimal>,
+    counterparty: Option<String>,
+    block: u64,
+    time: u64,
+    /// The fee in DC if the address paid for the transaction
+    #[serde(skip)]
+    fee: Option<u64>,
+    /// The local label of the transaction
+    label: Option<String>,
+}
+
+impl Activity {
+    fn from_txn(address: &PublicKey, txn: &Value) -> Result<Self> {
+        let address = address.to_string();
+        let kind = txn["type"].as_str().unwrap_or("unknown").to_string();
+        let (amount, counterparty) = match kind.as_str() {
+            "payment_v1" => transfer(&address, &txn["payer"], &txn["payee"], &txn["amount"]),
+            "payment_v2" => payment_v2(&address, txn),
+            "token_burn_v1" => transfer(&address, &txn["payer"], &txn["payee"], &txn["amount"]),
+            "rewards_v1" | "rewards_v2" => {
+                let amount = txn["rewards"]
+                    .as_array()
+                    .map(|rewards| sum_for(&address, rewards, "account"));
+                (amount, Some("rewards".to_string()))
+            }
+            _ => (None, None),
+        };
+        let fee = if txn["payer"].as_str() == Some(address.as_str()) {
+            txn["fee"].as_u64()
+        } else {
+            None
+        };
+        Ok(Self {
+            hash: txn["hash"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Missing transaction hash in {}", txn))?
+                .to_string(),
+            kind,
+            amount: amount.map(|bones| Decimal::new(bones, 8)),
+            counterparty,
+            block: txn["height"].as_u64().unwrap_or(0),
+            time: txn["time"].as_u64().unwrap_or(0),
+            fee,
+            label: None,
+        })
+    }
+}
+
+/// Returns the signed amount and counterparty for a transaction moving
+/// funds from a payer to a payee.
+fn transfer(
+    address: &str,
+    payer: &Value,
+    payee: &Value,
+    amount: &Value,
+) -> (Option<i64>, Option<String>) {
+    let amount = amount.as_i64();
+    if payer.as_str() == Some(address) {
+        (amount.map(|v| -v), payee.as_str().map(String::from))
+    } else {
+        (amount, payer.as_str().map(String::from))
+    }
+}
+
+fn payment_v2(address: &str, txn: &Value) -> (Option<i64>, Option<String>) {
+    let payments = match txn["payments"].as_array() {
+        Some(payments) => payments,
+        None => return (None, None),
+    };
+    if txn["payer"].as_str() == Some(address) {
+        let total: i64 = payments.iter().filter_map(|p| p["amount"].as_i64()).sum();
+        let counterparty = match payments.as_slice() {
+            [payment] => payment["payee"].as_str().map(String::from),
+            _ => Some(format!("{} payees", payments.len())),
+        };
+        (Some(-total), counterparty)
+    } else {
+        (
+            Some(sum_for(address, payments, "payee")),
+            txn["payer"].as_str().map(String::from),
+        )
+    }
+}
+
+/// Sums the amounts of all entries where the given key matches the given
+/// address.
+fn sum_for(address: &str, entries: &[Value], key: &str) -> i64 {
+    entries
+        .iter()
+        .filter(|entry| entry[key].as_str() == Some(address))
+        .filter_map(|entry| entry["amount"].as_i64())
+        .sum()
+}
+
+/// Validates the block range to list activity for. An open end of the
+/// range is unbounded.
+fn block_range(from: Option<u64>, to: Option<u64>) -> Result<RangeInclusive<u64>> {
+    let from = from.unwrap_or(0);
+    let to = to.unwrap_or(u64::MAX);
+    if from > to {
+        bail!("Invalid block range {} to {}", from, to);
+    }
+    Ok(from..=to)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Since {
+    Block(u64),
+    Time(u64),
+}
+
+impl Since {
+    fn includes(self, activity: &Activity) -> bool {
+        match self {
+            Self::Block(block) => activity.block >= block,
+            Self::Time(time) => activity.time >= time,
+        }
+    }
+}
+
+impl FromStr for Since {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.parse::<u64>() {
+            Ok(block) => Ok(Self::Block(block)),
+            Err(_) => Ok(Self::Time(parse_date(s)?)),
+        }
+    }
+}
+
+/// Parses a YYYY-MM-DD date into seconds since the unix epoch at the
+/// start of that day in UTC.
+pub(crate) fn parse_date(s: &str) -> Result<u64> {
+    let parts: Vec<&str> = s.split('-').collect();
+    if parts.len() != 3 {
+        bail!("Invalid date {}, expected YYYY-MM-DD", s);
+    }
+    let year: i64 = parts[0].parse()?;
+    let month: u32 = parts[1].parse()?;
+    let day: u32 = parts[2].parse()?;
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        bail!("Invalid date {}, expected YYYY-MM-DD", s);
+    }
+    Ok(days_from_civil(year, month, day) as u64 * 86_400)
+}
+
+/// Formats seconds since the unix epoch as a UTC date and time.
+pub(crate) fn format_time(time: u64) -> String {
+    let (year, month, day) = civil_from_days((time / 86_400) as i64);
+    let secs = time % 86_400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+// Date conversions from: http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = (if z >= 0 { z } else { z - 146_096 }) / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+fn opt_string<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(String::new, |v| v.to_string())
+}
+
+fn write_activity(
+    writer: &mut dyn io::Write,
+    activity: &[Activity],
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row![
+                "Hash",
+                "Type",
+                "Amount",
+                "Counterparty",
+                "Block",
+                "Time",
+                "Label"
+            ]);
+            for entry in activity {
+                table.add_row(row![
+                    entry.hash,
+                    entry.kind,
+                    opt_string(&entry.amount),
+                    opt_string(&entry.counterparty),
+                    entry.block,
+                    format_time(entry.time),
+                    opt_string(&entry.label)
+                ]);
+            }
+            write_table(writer, &table)
+        }
+        OutputFormat::Json => write_json(writer, activity),
+    }
+}
+
+fn write_csv(writer: &mut dyn io::Write, activity: &[Activity]) -> Result {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(&[
+        "hash",
+        "type",
+        "amount",
+        "counterparty",
+        "block",
+        "time",
+        "label",
+    ])?;
+    for entry in activity {
+        writer.write_record(&[
+            entry.hash.clone(),
+            entry.kind.clone(),
+            opt_string(&entry.amount),
+            opt_string(&entry.counterparty),
+            entry.block.to_string(),
+            format_time(entry.time),
+            opt_string(&entry.label),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the activity entries with an amount as tax report CSV to the
+/// given file and returns the number of rows written. The oracle price of
+/// each block is looked up once. Rows for blocks without a known oracle
+/// price are flagged instead of failing the export.
+fn export_tax_csv(
+    client: &api::Client,
+    path: &Path,
+    activity: &[Activity],
+    fiat_decimals: u32,
+) -> Result<usize> {
+    let mut writer = csv::Writer::from_writer(fs::File::create(path)?);
+    writer.write_record(&[
+        "date",
+        "type",
+        "counterparty",
+        "hnt_amount",
+        "usd_value",
+        "fee_dc",
+        "note",
+    ])?;
+    let mut prices: HashMap<u64, Option<u64>> = HashMap::new();
+    let mut rows = 0;
+    for entry in activity.iter().filter(|entry| entry.amount.is_some()) {
+        let price = match prices.get(&entry.block) {
+            Some(price) => *price,
+            None => {
+                let price = client
+                    .get_oracle_price_at(entry.block)?
+                    .map(|price| price.price);
+                prices.insert(entry.block, price);
+                price
+            }
+        };
+        writer.write_record(&tax_record(entry, price, fiat_decimals))?;
+        rows += 1;
+    }
+    writer.flush()?;
+    Ok(rows)
+}
+
+/// Builds a tax report row for an activity entry, valuing the amount at the
+/// given oracle price in 1/100_000_000 USD per HNT.
+fn tax_record(entry: &Activity, oracle_price: Option<u64>, fiat_decimals: u32) -> Vec<String> {
+    let usd_value = match (entry.amount, oracle_price) {
+        (Some(amount), Some(price)) => Some(format_usd(
+            amount * Decimal::new(price as i64, 8),
+            fiat_decimals,
+        )),
+        _ => None,
+    };
+    let note = if oracle_price.is_none() {
+        format!("missing oracle price for block {}", entry.block)
+    } else {
+        String::new()
+    };
+    vec![
+        format_time(entry.time),
+        entry.kind.clone(),
+        opt_string(&entry.counterparty),
+        opt_string(&entry.amount),
+        opt_string(&usd_value),
+        opt_string(&entry.fee),
+        note,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn date_roundtrip() {
+        assert_eq!(0, days_from_civil(1970, 1, 1));
+        assert_eq!(18_695, days_from_civil(2021, 3, 9));
+        assert_eq!((2000, 2, 29), civil_from_days(11_016));
+        assert_eq!("2021-03-09 14:26:40", format_time(1_615_300_000));
+        assert_eq!(18_695 * 86_400, parse_date("2021-03-09").unwrap());
+        assert!(parse_date("2021-13-01").is_err());
+        assert!(parse_date("yesterday").is_err());
+    }
+
+    #[test]
+    fn block_ranges() {
+        assert_eq!(10..=20, block_range(Some(10), Some(20)).expect("range"));
+        assert_eq!(10..=10, block_range(Some(10), Some(10)).expect("range"));
+        assert_eq!(0..=20, block_range(None, Some(20)).expect("range"));
+        assert_eq!(10..=u64::MAX, block_range(Some(10), None).expect("range"));
+        assert!(block_range(Some(20), Some(10)).is_err());
+    }
+
+    #[test]
+    fn payment_v2_activity() {
+        let payer = Keypair::default().public_key().to_string();
+        let wallet = Keypair::default();
+        let address = wallet.public_key().to_string();
+        let txn = json!({
+            "type": "payment_v2",
+            "hash": "hash",
+            "height": 10,
+            "time": 20,
+            "payer": payer,
+            "payments": [
+                {"payee": address, "amount": 150_000_000},
+                {"payee": payer, "amount": 1},
+            ]
+        });
+        let incoming = Activity::from_txn(wallet.public_key(), &txn).expect("activity");
+        assert_eq!(Some(Decimal::new(150_000_000, 8)), incoming.amount);
+        assert_eq!(Some(payer.clone()), incoming.counterparty);
+
+        let outgoing = Activity::from_txn(&payer.parse().expect("payer"), &txn).expect("activity");
+        assert_eq!(Some(Decimal::new(-150_000_001, 8)), outgoing.amount);
+        assert_eq!(Some("2 payees".to_string()), outgoing.counterparty);
+    }
+
+    #[test]
+    fn tax_rows() {
+        let payer = Keypair::default().public_key().to_string();
+        let payee = Keypair::default().public_key().to_string();
+        let txn = json!({
+            "type": "payment_v1",
+            "hash": "hash",
+            "height": 10,
+            "time": 1_615_300_000,
+            "payer": payer,
+            "payee": payee,
+            "amount": 150_000_000,
+            "fee": 35_000,
+        });
+        let outgoing = Activity::from_txn(&payer.parse().expect("payer"), &txn).expect("activity");
+        // $2.00 per HNT
+        assert_eq!(
+            vec![
+                "2021-03-09 14:26:40".to_string(),
+                "payment_v1".to_string(),
+                payee.clone(),
+                "-1.50000000".to_string(),
+                "-3.00".to_string(),
+                "35000".to_string(),
+                String::new(),
+            ],
+            tax_record(&outgoing, Some(200_000_000), 2)
+        );
+        // Rounded half up to the requested decimals
+        assert_eq!("-3", tax_record(&outgoing, Some(200_000_000), 0)[4]);
+        assert_eq!("-3.000", tax_record(&outgoing, Some(200_000_000), 3)[4]);
+
+        // The payee did not pay the fee, and a missing price is flagged
+        let incoming = Activity::from_txn(&payee.parse().expect("payee"), &txn).expect("activity");
+        let row = tax_record(&incoming, None, 2);
+        assert_eq!("1.50000000", row[3]);
+        assert_eq!("", row[4]);
+        assert_eq!("", row[5]);
+        assert_eq!("missing oracle price for block 10", row[6]);
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     staking,
     traits::{TxnEnvelope, TxnSign},
 };
-use helium_api::{BlockchainTxnAddGatewayV1, PendingTxnStatus};
+use helium_api::{BlockchainTxn, BlockchainTxnAddGatewayV1, PendingTxnStatus};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -12,7 +12,7 @@ use structopt::StructOpt;
 /// hotspot miner and supplied here for owner signing. Use an onboarding key to
 /// get the transaction signed by the DeWi staking server.
 pub struct Cmd {
-    /// Base64 encoded transaction to sign. If no transaction if given stdin is
+    /// Encoded transaction (see --encoding) to sign. If no transaction if given stdin is
     /// read for the transaction. Note that the stdin feature only works if the
     /// wallet password is set in the HELIUM_WALLET_PASSWORD environment
     /// variable
@@ -24,20 +24,27 @@ pub struct Cmd {
     #[structopt(long)]
     onboarding: Option<String>,
 
+    /// Sign as owner only and leave the payer signature empty for the
+    /// payer to fill in using the cosign command
+    #[structopt(long, conflicts_with_all = &["onboarding", "commit"])]
+    partial: bool,
+
     #[structopt(long)]
     commit: bool,
 }
 
 impl Cmd {
     pub fn run(self, opts: Opts) -> Result {
-        let mut txn = BlockchainTxnAddGatewayV1::from_envelope(&read_txn(&self.txn)?)?;
+        let mut txn =
+            BlockchainTxnAddGatewayV1::from_envelope(&read_txn(&self.txn, opts.encoding)?)?;
 
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
 
         let staking_client = staking::Client::default();
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
         let wallet_key = keypair.public_key();
 
@@ -47,6 +54,7 @@ impl Cmd {
                 txn.payer_signature = txn.owner_signature.clone();
                 Ok(txn.in_envelope())
             }
+            _payer_key if self.partial => Ok(txn.in_envelope()),
             _maker_key => {
                 if self.onboarding.is_none() {
                     bail!("Staking server requires an onboarding key");
@@ -57,18 +65,24 @@ impl Cmd {
             }
         }?;
 
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-        print_txn(&txn, &status, opts.format)
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            &txn,
+            &envelope,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 fn print_txn(
     txn: &BlockchainTxnAddGatewayV1,
+    envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     let address = PublicKey::from_bytes(&txn.gateway)?.to_string();
@@ -79,23 +93,26 @@ fn print_txn(
     };
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Address", address],
                 ["Payer", payer],
                 ["Fee", txn.fee],
                 ["Staking fee", txn.staking_fee],
                 ["Hash", status_str(status)]
-            );
+            ))?;
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "address": address,
                 "payer": payer,
                 "fee": txn.fee,
                 "staking fee": txn.staking_fee,
-                "hash": status_json(status)
+                "hash": status_json(status),
+                "txn": encoding.encode(envelope)?,
             });
             print_json(&table)
         }
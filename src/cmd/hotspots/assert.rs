@@ -1,10 +1,11 @@
 use crate::{
     cmd::*,
+    h3,
     result::{bail, Result},
     staking,
     traits::{TxnEnvelope, TxnSign},
 };
-use helium_api::{BlockchainTxnAssertLocationV1, PendingTxnStatus};
+use helium_api::{BlockchainTxn, BlockchainTxnAssertLocationV1, PendingTxnStatus};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -12,7 +13,7 @@ use structopt::StructOpt;
 /// created by the hotspot miner and supplied here for owner signing. Use an
 /// onboarding key to get the transaction signed by the DeWi staking server.
 pub struct Cmd {
-    /// Base64 encoded transaction to sign. If no transaction if given stdin is
+    /// Encoded transaction (see --encoding) to sign. If no transaction if given stdin is
     /// read for the transaction. Note that the stdin feature only works if the
     /// wallet password is set in the HELIUM_WALLET_PASSWORD environment
     /// variable
@@ -24,20 +25,28 @@ pub struct Cmd {
     #[structopt(long)]
     onboarding: Option<String>,
 
+    /// Sign as owner only and leave the payer signature empty for the
+    /// payer to fill in using the cosign command
+    #[structopt(long, conflicts_with_all = &["onboarding", "commit"])]
+    partial: bool,
+
     #[structopt(long)]
     commit: bool,
 }
 
 impl Cmd {
     pub fn run(self, opts: Opts) -> Result {
-        let mut txn = BlockchainTxnAssertLocationV1::from_envelope(&read_txn(&self.txn)?)?;
+        let mut txn =
+            BlockchainTxnAssertLocationV1::from_envelope(&read_txn(&self.txn, opts.encoding)?)?;
+        validate_location(&txn.location)?;
 
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
 
         let staking_client = staking::Client::default();
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
         let wallet_key = keypair.public_key();
 
@@ -47,6 +56,7 @@ impl Cmd {
                 txn.payer_signature = txn.owner_signature.clone();
                 Ok(txn.in_envelope())
             }
+            _payer_key if self.partial => Ok(txn.in_envelope()),
             _maker_key => {
                 if self.onboarding.is_none() {
                     bail!("Staking server requires an onboarding key");
@@ -57,18 +67,44 @@ impl Cmd {
             }
         }?;
 
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-        print_txn(&txn, &status, opts.format)
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            &txn,
+            &envelope,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
+    }
+}
+
+/// The h3 resolution required for asserted hotspot locations
+const LOCATION_RESOLUTION: u8 = 12;
+
+/// Checks that the given location is a well-formed h3 index at the
+/// resolution the blockchain expects, to avoid signing (and paying the
+/// staking fee for) a transaction that will be rejected.
+fn validate_location(location: &str) -> Result {
+    let index = h3::parse_cell(location)?;
+    let resolution = h3::resolution(index);
+    if resolution != LOCATION_RESOLUTION {
+        bail!(
+            "Invalid location {}: expected h3 resolution {}, got {}",
+            location,
+            LOCATION_RESOLUTION,
+            resolution
+        );
     }
+    Ok(())
 }
 
 fn print_txn(
     txn: &BlockchainTxnAssertLocationV1,
+    envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     let address = PublicKey::from_bytes(&txn.gateway)?.to_string();
@@ -79,27 +115,42 @@ fn print_txn(
     };
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Address", address],
                 ["Location", txn.location],
                 ["Payer", payer],
                 ["Fee", txn.fee],
                 ["Staking fee", txn.staking_fee],
                 ["Hash", status_str(status)]
-            );
+            ))?;
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "address": address,
                 "location": txn.location,
                 "payer": payer,
                 "fee": txn.fee,
                 "staking fee": txn.staking_fee,
-                "hash": status_json(status)
+                "hash": status_json(status),
+                "txn": encoding.encode(envelope)?,
             });
             print_json(&table)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_resolution() {
+        assert!(validate_location("8c28308280c15ff").is_ok());
+        assert!(validate_location("8928308280fffff").is_err());
+        assert!(validate_location("not a location").is_err());
+    }
+}
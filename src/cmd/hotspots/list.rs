@@ -3,7 +3,7 @@ use crate::{
     keypair::PublicKey,
     result::{anyhow, Result},
 };
-use helium_api::{Client, Hotspot};
+use helium_api::Hotspot;
 use prettytable::{format, Table};
 use serde_json::json;
 
@@ -19,21 +19,17 @@ impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
         let addresses = collect_addresses(opts.files, self.addresses.clone())?;
         let api_url = api_url(
+            &opts.api_url,
             addresses
                 .first()
                 .map(|key| key.network)
                 .ok_or_else(|| anyhow!("at least one address expected"))?,
         );
-        let client = Client::new_with_base_url(api_url);
+        let client = opts.timeouts.client(api_url)?;
         let mut results: Vec<(PublicKey, Result<Vec<Hotspot>>)> =
             Vec::with_capacity(self.addresses.len());
         for address in addresses {
-            results.push((
-                address.clone(),
-                client
-                    .get_hotspots(&address.to_string())
-                    .map_err(|e| e.into()),
-            ));
+            results.push((address.clone(), client.get_hotspots(&address.to_string())));
         }
         print_results(results, opts.format)
     }
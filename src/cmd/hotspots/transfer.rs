@@ -2,11 +2,9 @@ use crate::{
     cmd::*,
     keypair::PublicKey,
     result::{anyhow, bail, Result},
-    traits::{TxnEnvelope, TxnFee, TxnSign, B64},
-};
-use helium_api::{
-    BlockchainTxn, BlockchainTxnTransferHotspotV1, Client, Hnt, PendingTxnStatus, Txn,
+    traits::{TxnEnvelope, TxnFee, TxnSign},
 };
+use helium_api::{BlockchainTxn, BlockchainTxnTransferHotspotV1, Hnt, PendingTxnStatus, Txn};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -31,7 +29,7 @@ pub struct Sell {
 
 #[derive(Debug, StructOpt)]
 pub struct Buy {
-    /// Base64 encoded transaction to sign. If no transaction if given
+    /// Encoded transaction (see --encoding) to sign. If no transaction if given
     /// stdin is read for the transaction. Note that the stdin feature
     /// only works if the wallet password is set in the
     /// HELIUM_WALLET_PASSWORD environment variable
@@ -45,7 +43,9 @@ pub struct Buy {
 impl Cmd {
     pub fn run(self, opts: Opts) -> Result {
         let wallet = load_wallet(opts.files)?;
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
         match self {
             Self::Sell(sell) => {
@@ -61,16 +61,15 @@ impl Cmd {
                     amount_to_seller: sell.price.unwrap_or_else(|| Hnt::from_bones(0)).to_bones(),
                     buyer_nonce: buyer_account.speculative_nonce + 1,
                 };
-                txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
-                let password = get_password(false)?;
-                let keypair = wallet.decrypt(password.as_bytes())?;
+                txn.fee = txn.txn_fee(&get_txn_fees(&client, &opts.submit)?)?;
+                let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
                 txn.seller_signature = txn.sign(&keypair)?;
-                println!("{}", txn.in_envelope().to_b64()?);
+                println!("{}", opts.encoding.encode(&txn.in_envelope())?);
                 Ok(())
             }
 
             Self::Buy(buy) => {
-                let mut envelope = read_txn(&buy.txn)?;
+                let mut envelope = read_txn(&buy.txn, opts.encoding)?;
 
                 match &mut envelope.txn {
                     Some(Txn::TransferHotspot(t)) => {
@@ -88,15 +87,16 @@ impl Cmd {
                             bail!("Hotspot transfer nonce no longer valid");
                         }
 
-                        let password = get_password(false)?;
-                        let keypair = wallet.decrypt(password.as_bytes())?;
+                        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
                         t.buyer_signature = t.sign(&keypair)?;
-                        let status = if buy.commit {
-                            Some(client.submit_txn(&envelope)?)
-                        } else {
-                            None
-                        };
-                        print_txn(&envelope, &status, opts.format)
+                        let status = submit_txn(&client, &envelope, buy.commit, &opts.submit)?;
+                        print_txn(
+                            &envelope,
+                            wallet.public_key.network,
+                            &status,
+                            opts.encoding,
+                            opts.format,
+                        )
                     }
                     _ => Err(anyhow!("Unsupported transaction for transfer_hotspot")),
                 }
@@ -107,14 +107,17 @@ impl Cmd {
 
 fn print_txn(
     envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
-    let encoded = envelope.to_b64()?;
+    let encoded = encoding.encode(envelope)?;
     match format {
         OutputFormat::Table => Err(anyhow!("Table format not supported for transaction output")),
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "txn": encoded,
                 "hash": status_json(status)
             });
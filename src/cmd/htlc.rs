@@ -1,15 +1,15 @@
 use crate::{
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_footer, print_json, status_json,
-        status_str, Opts, OutputFormat,
+        api_url, check_balance, check_logged_nonce, decrypt_wallet, fetch_payer_account,
+        get_txn_fees, load_wallet, print_footer, print_json, print_table, status_json, status_str,
+        submit_txn, Encoding, Opts, OutputFormat,
     },
-    keypair::{Keypair, PublicKey},
+    keypair::{Keypair, Network, PublicKey},
     result::Result,
-    traits::{TxnEnvelope, TxnFee, TxnSign, B64},
+    traits::{BalanceType, TxnEnvelope, TxnFee, TxnSign},
 };
 use helium_api::{
-    BlockchainTxn, BlockchainTxnCreateHtlcV1, BlockchainTxnRedeemHtlcV1, Client, Hnt,
-    PendingTxnStatus,
+    BlockchainTxn, BlockchainTxnCreateHtlcV1, BlockchainTxnRedeemHtlcV1, Hnt, PendingTxnStatus,
 };
 use serde_json::json;
 use structopt::StructOpt;
@@ -75,13 +75,20 @@ impl Cmd {
 
 impl Create {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
-
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
+
+        let account = fetch_payer_account(&client, &wallet.public_key)?;
+        check_logged_nonce(
+            &wallet.public_key,
+            account.speculative_nonce + 1,
+            self.commit,
+            &opts.submit,
+        )?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
         let wallet_address = keypair.public_key();
-        let account = client.get_account(&wallet_address.to_string())?;
         let address = Keypair::generate(wallet_address.tag());
 
         let mut txn = BlockchainTxnCreateHtlcV1 {
@@ -95,30 +102,37 @@ impl Create {
             nonce: account.speculative_nonce + 1,
             signature: Vec::new(),
         };
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
+        txn.fee = txn.txn_fee(&get_txn_fees(&client, &opts.submit)?)?;
+        check_balance(&account, &txn.in_envelope(), BalanceType::Hnt)?;
         txn.signature = txn.sign(&keypair)?;
         let envelope = txn.in_envelope();
 
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
 
-        print_create_txn(&txn, &envelope, &status, opts.format)
+        print_create_txn(
+            &txn,
+            &envelope,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 fn print_create_txn(
     txn: &BlockchainTxnCreateHtlcV1,
     envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Address", PublicKey::from_bytes(&txn.address)?.to_string()],
                 ["Payee", PublicKey::from_bytes(&txn.payee)?.to_string()],
                 ["Amount", txn.amount],
@@ -126,11 +140,12 @@ fn print_create_txn(
                 ["Timelock", txn.timelock],
                 ["Nonce", txn.nonce],
                 ["Hash", status_str(status)]
-            );
+            ))?;
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "address": PublicKey::from_bytes(&txn.address)?.to_string(),
                 "payee": PublicKey::from_bytes(&txn.payee)?.to_string(),
                 "amount": txn.amount,
@@ -138,7 +153,7 @@ fn print_create_txn(
                 "timelock": txn.timelock,
                 "nonce": txn.nonce,
                 "hash": status_json(status),
-                "txn": envelope.to_b64()?,
+                "txn": encoding.encode(envelope)?,
             });
             print_json(&table)
         }
@@ -147,10 +162,11 @@ fn print_create_txn(
 
 impl Redeem {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
         let mut txn = BlockchainTxnRedeemHtlcV1 {
             fee: 0,
@@ -159,43 +175,50 @@ impl Redeem {
             preimage: self.preimage.clone().into_bytes(),
             signature: Vec::new(),
         };
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
+        txn.fee = txn.txn_fee(&get_txn_fees(&client, &opts.submit)?)?;
         txn.signature = txn.sign(&keypair)?;
         let envelope = txn.in_envelope();
 
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
 
-        print_redeem_txn(&txn, &envelope, &status, opts.format)
+        print_redeem_txn(
+            &txn,
+            &envelope,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 fn print_redeem_txn(
     txn: &BlockchainTxnRedeemHtlcV1,
     envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Payee", PublicKey::from_bytes(&txn.payee)?.to_string()],
                 ["Address", PublicKey::from_bytes(&txn.payee)?.to_string()],
                 ["Preimage", std::str::from_utf8(&txn.preimage)?],
                 ["Hash", status_str(status)]
-            );
+            ))?;
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "address": PublicKey::from_bytes(&txn.address)?.to_string(),
                 "payee": PublicKey::from_bytes(&txn.payee)?.to_string(),
                 "hash": status_json(status),
-                "txn": envelope.to_b64()?,
+                "txn": encoding.encode(envelope)?,
             });
             print_json(&table)
         }
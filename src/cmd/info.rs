@@ -1,9 +1,13 @@
 use crate::{
-    cmd::{api_url, load_wallet, print_json, print_table, Opts, OutputFormat},
+    cmd::{
+        api_url, fetch_account, load_wallet, print_json, print_table, verify, Opts, OutputFormat,
+        NOT_ON_CHAIN,
+    },
+    mnemonic::account_derivation,
     result::Result,
     wallet::Wallet,
 };
-use helium_api::{Account, Client, Hnt, Hst};
+use helium_api::{Account, Hnt, Hst};
 use prettytable::Table;
 use qr2term::print_qr;
 use serde_json::json;
@@ -25,29 +29,43 @@ impl Cmd {
             print_qr(&address)?;
             Ok(())
         } else {
-            let client = Client::new_with_base_url(api_url(wallet.public_key.network));
-            let account = client.get_account(&wallet.address()?)?;
-            print_wallet(&wallet, &account, opts.format)
+            let client = opts
+                .timeouts
+                .client(api_url(&opts.api_url, wallet.public_key.network))?;
+            let account = fetch_account(&client, &wallet.public_key)?;
+            print_wallet(&wallet, account.as_ref(), opts.format)
         }
     }
 }
 
-fn print_wallet(wallet: &Wallet, account: &Account, format: OutputFormat) -> Result {
+fn print_wallet(wallet: &Wallet, account: Option<&Account>, format: OutputFormat) -> Result {
     match format {
         OutputFormat::Table => {
             let mut table = Table::new();
             table.add_row(row!["Key", "Value"]);
-            table.add_row(row!["Address", account.address]);
+            table.add_row(row!["Address", wallet.address()?]);
             table.add_row(row!["Network", wallet.public_key.tag().network]);
             table.add_row(row!["Type", wallet.public_key.tag().key_type]);
             table.add_row(row!["Sharded", wallet.is_sharded()]);
-            table.add_row(row!["PwHash", wallet.pwhash()]);
-            table.add_row(row!["Balance", Hnt::from_bones(account.balance)]);
-            table.add_row(row!["DC Balance", account.dc_balance]);
-            table.add_row(row![
-                "Securities Balance",
-                Hst::from_bones(account.sec_balance)
-            ]);
+            table.add_row(row!["Watch Only", wallet.is_watch_only()]);
+            table.add_row(row!["PwHash", verify::pwhash_str(wallet.pwhash())]);
+            if let Some(index) = wallet.account {
+                table.add_row(row!["Account Index", index]);
+                table.add_row(row!["Derivation", account_derivation(index)]);
+            }
+            match account {
+                Some(account) => {
+                    table.add_row(row!["Balance", Hnt::from_bones(account.balance)]);
+                    table.add_row(row!["DC Balance", account.dc_balance]);
+                    table.add_row(row![
+                        "Securities Balance",
+                        Hst::from_bones(account.sec_balance)
+                    ]);
+                }
+                None => {
+                    table.add_row(row!["Balance", NOT_ON_CHAIN]);
+                }
+            }
             print_table(&table)
         }
         OutputFormat::Json => {
@@ -55,7 +73,11 @@ fn print_wallet(wallet: &Wallet, account: &Account, format: OutputFormat) -> Res
                 "sharded": wallet.is_sharded(),
                 "network": wallet.public_key.tag().network.to_string(),
                 "type": wallet.public_key.tag().key_type.to_string(),
-                "pwhash": wallet.pwhash().to_string(),
+                "watch_only": wallet.is_watch_only(),
+                "pwhash": wallet.pwhash().map(|pwhash| pwhash.to_string()),
+                "account_index": wallet.account,
+                "derivation": wallet.account.map(account_derivation),
+                "on_chain": account.is_some(),
                 "account": account,
             });
             print_json(&table)
@@ -0,0 +1,180 @@
+use crate::result::{bail, Result};
+use std::{
+    process,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// Exit code for a process terminated by SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static CRITICAL_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    // The state of the running multi-step command, reported when an
+    // interrupt arrives between steps
+    static ref PROGRESS: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// Installs the interrupt (Ctrl-C) handler. Outside of a critical section
+/// an interrupt exits right away as before, reporting the state of a
+/// running multi-step command. Inside a critical section the interrupt is
+/// deferred until the section completes so the command can report what it
+/// finished.
+pub fn install() -> Result {
+    ctrlc::set_handler(|| {
+        if CRITICAL_DEPTH.load(Ordering::SeqCst) == 0 {
+            if let Ok(progress) = PROGRESS.lock() {
+                if let Some(progress) = progress.as_ref() {
+                    eprintln!("\nInterrupted: {}", progress);
+                }
+            }
+            process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        if !INTERRUPTED.swap(true, Ordering::SeqCst) {
+            eprintln!("\nInterrupted, finishing the current step");
+        }
+    })?;
+    Ok(())
+}
+
+/// Whether an interrupt was received during a critical section
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+struct CriticalGuard;
+
+impl CriticalGuard {
+    fn enter() -> Self {
+        CRITICAL_DEPTH.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for CriticalGuard {
+    fn drop(&mut self) {
+        CRITICAL_DEPTH.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Runs the given function as a critical section which an interrupt does
+/// not abort. Critical sections can be nested, an interrupt is only acted
+/// on once the outermost section completes.
+pub fn critical<T>(f: impl FnOnce() -> T) -> T {
+    let _guard = CriticalGuard::enter();
+    f()
+}
+
+/// Whether the caller is running inside a (nested) critical section
+fn in_critical() -> bool {
+    CRITICAL_DEPTH.load(Ordering::SeqCst) > 0
+}
+
+/// A step of a multi-step command, described by its subject and the
+/// action that completes it, like "wallet" and "created".
+struct Step {
+    subject: &'static str,
+    action: &'static str,
+    done: bool,
+}
+
+/// Tracks the steps of a multi-step command. Each step runs as a critical
+/// section. When an interrupt was received during a step the command is
+/// aborted after the step completes with an error listing exactly which
+/// steps completed, like "wallet created; sweep NOT submitted".
+pub struct Steps {
+    steps: Vec<Step>,
+}
+
+impl Steps {
+    pub fn new(steps: &[(&'static str, &'static str)]) -> Self {
+        let steps = Self {
+            steps: steps
+                .iter()
+                .map(|&(subject, action)| Step {
+                    subject,
+                    action,
+                    done: false,
+                })
+                .collect(),
+        };
+        steps.publish();
+        steps
+    }
+
+    fn publish(&self) {
+        if let Ok(mut progress) = PROGRESS.lock() {
+            *progress = Some(self.summary());
+        }
+    }
+
+    /// Runs the step with the given subject. The step is marked completed
+    /// if the given function succeeds.
+    pub fn run<T>(&mut self, subject: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let result = critical(f);
+        if result.is_ok() {
+            if let Some(step) = self.steps.iter_mut().find(|s| s.subject == subject) {
+                step.done = true;
+            }
+            self.publish();
+        }
+        if interrupted() && !in_critical() {
+            bail!("Interrupted: {}", self.summary());
+        }
+        result
+    }
+
+    /// Describes the state of all steps
+    pub fn summary(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| {
+                if step.done {
+                    format!("{} {}", step.subject, step.action)
+                } else {
+                    format!("{} NOT {}", step.subject, step.action)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("; ")
+    }
+}
+
+impl Drop for Steps {
+    fn drop(&mut self) {
+        if let Ok(mut progress) = PROGRESS.lock() {
+            *progress = None;
+        }
+    }
+}
+
+/// Aborts with the given description of the completed work if an interrupt
+/// was received and the caller is not part of an enclosing critical
+/// section which reports its own state.
+pub fn check(completed: impl FnOnce() -> String) -> Result {
+    if interrupted() && !in_critical() {
+        bail!("Interrupted: {}", completed());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_summary() {
+        let mut steps = Steps::new(&[("wallet", "created"), ("sweep", "submitted")]);
+        assert_eq!("wallet NOT created; sweep NOT submitted", steps.summary());
+        steps.run("wallet", || Ok(())).expect("wallet step");
+        assert_eq!("wallet created; sweep NOT submitted", steps.summary());
+        assert!(steps
+            .run("sweep", || -> Result { bail!("failed") })
+            .is_err());
+        assert_eq!("wallet created; sweep NOT submitted", steps.summary());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{cmd::config_dir, result::Result};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A local label for a submitted transaction. Labels are kept for the
+/// user's own bookkeeping and never leave the machine.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Label {
+    pub hash: String,
+    pub label: String,
+    pub time: u64,
+}
+
+/// The location of the transaction labels in the configuration directory
+pub fn labels_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("labels.json"))
+}
+
+/// Appends a label for the transaction with the given hash as a json line
+/// to the labels file at the given path. The file is only ever appended to.
+/// Each line is written with a single write under an exclusive lock on the
+/// file so concurrent commands do not interleave their lines.
+pub fn append(path: &Path, hash: &str, label: &str) -> Result {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let entry = Label {
+        hash: hash.to_string(),
+        label: label.to_string(),
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+    };
+    let line = format!("{}\n", serde_json::to_string(&entry)?);
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.lock_exclusive()?;
+    let result = file.write_all(line.as_bytes());
+    file.unlock()?;
+    Ok(result?)
+}
+
+/// Reads the labels file at the given path into a map from transaction
+/// hash to label. A later label for the same transaction replaces an
+/// earlier one. Lines that can not be parsed, like a line cut short by a
+/// crash, are skipped. A missing file has no labels.
+pub fn read(path: &Path) -> Result<HashMap<String, String>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut labels = HashMap::new();
+    for line in io::BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str::<Label>(&line?) {
+            labels.insert(entry.hash, entry.label);
+        }
+    }
+    Ok(labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_and_read() {
+        let dir = std::env::temp_dir().join(format!("helium-wallet-labels-{}", std::process::id()));
+        let path = dir.join("labels.json");
+        let _ = fs::remove_file(&path);
+        assert!(read(&path).expect("missing labels").is_empty());
+
+        append(&path, "hash1", "rent").expect("append");
+        append(&path, "hash2", "invoice 42").expect("append");
+        // A line cut short is skipped
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(b"{\"hash\":\"hash3\""))
+            .expect("partial line");
+        let labels = read(&path).expect("labels");
+        assert_eq!(2, labels.len());
+        assert_eq!("rent", labels["hash1"]);
+        assert_eq!("invoice 42", labels["hash2"]);
+
+        fs::remove_dir_all(&dir).expect("cleanup");
+    }
+}
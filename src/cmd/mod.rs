@@ -1,33 +1,74 @@
 use crate::{
-    keypair::{Network, PublicKey},
+    api::{self, Client},
+    cmd::{receipt::Receipt, submit_log::SubmitLog},
+    keypair::{Keypair, Network, PublicKey},
     mnemonic,
-    result::{bail, Error, Result},
-    traits::{TxnFeeConfig, B64},
+    result::{anyhow, bail, is_not_found, Error, Result},
+    signer::{CommandSigner, Signer},
+    traits::{BalanceType, Hex, TxnBalance, TxnFeeConfig, TxnPayer, B64},
     wallet::Wallet,
 };
-use helium_api::{BlockchainTxn, Client, PendingTxnStatus};
+use flate2::{write::GzEncoder, Compression};
+use helium_api::{Account, BlockchainTxn, Hnt, Hst, PendingTxnStatus, Txn};
 use std::{
-    env, fs, io,
+    env, error,
+    ffi::OsString,
+    fmt, fs,
+    io::{self, Write},
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 use structopt::{clap::arg_enum, StructOpt};
+use zeroize::Zeroize;
 
+pub mod accounts;
+pub mod audit_log;
 pub mod balance;
+pub mod build;
 pub mod burn;
+pub mod consolidate;
+pub mod convert;
+pub mod cosign;
 pub mod create;
+pub mod dc;
+pub mod dc_cost;
+pub mod defaults;
+pub mod export;
+pub mod fee;
+pub mod fee_config;
+pub mod fill_nonce;
+pub mod history;
 pub mod hotspots;
 pub mod htlc;
 pub mod info;
+pub mod interrupt;
+pub mod labels;
 pub mod multisig;
+pub mod nonce_lock;
 pub mod oracle;
 pub mod oui;
 pub mod pay;
+pub mod payee;
+pub mod receipt;
+pub mod replace;
 pub mod request;
+pub mod rotate;
+pub mod sealed;
 pub mod securities;
+pub mod submit;
+pub mod submit_log;
+pub mod track;
 pub mod upgrade;
 pub mod validators;
 pub mod vars;
 pub mod verify;
+pub mod verify_address;
+pub mod wallet;
 
 arg_enum! {
     #[derive(Debug)]
@@ -49,62 +90,628 @@ pub struct Opts {
     )]
     files: Vec<PathBuf>,
 
-    /// Output formwat to use
+    /// Output formwat to use. Defaults to table when stdout is a terminal
+    /// and json otherwise.
     #[structopt(long = "format",
                 possible_values = &["table", "json"],
                 case_insensitive = true,
-                default_value = "table")]
+                default_value = default_format())]
     format: OutputFormat,
+
+    /// API URL to use. Overrides the HELIUM_WALLET_API_URL environment
+    /// variable and the network derived default.
+    #[structopt(long = "api-url")]
+    api_url: Option<String>,
+
+    /// Accept any TLS certificate of the API, like a self-signed one. This
+    /// disables verifying the identity of the API.
+    #[structopt(long = "allow-insecure-tls")]
+    allow_insecure_tls: bool,
+
+    /// Also trust the given PEM or DER encoded CA certificate for the API,
+    /// like the CA of a private API deployment.
+    #[structopt(long = "ca-cert")]
+    ca_cert: Option<PathBuf>,
+
+    /// Encoding of transactions that are read or emitted
+    #[structopt(long = "encoding",
+                possible_values = &["base64", "hex"],
+                case_insensitive = true,
+                default_value = "base64")]
+    encoding: Encoding,
+
+    /// Number of decimal places to show USD values with. Values are
+    /// rounded half up.
+    #[structopt(long = "fiat-decimals", default_value = "2")]
+    fiat_decimals: u32,
+
+    /// Sign with the key in the given encrypted key file instead of the
+    /// key in the wallet. The wallet still provides the address and
+    /// network and can be watch-only. The key has to match the wallet.
+    #[structopt(long = "key-file")]
+    key_file: Option<PathBuf>,
+
+    /// Sign by running the given shell command instead of decrypting a
+    /// key. The wallet provides the address and can be watch-only. The
+    /// command reads one line with the base64 encoded protobuf of the bare
+    /// transaction to sign, not of the envelope that other commands read
+    /// and write, since the signature covers the transaction alone. It
+    /// writes the base64 encoded signature to stdout. See the signer
+    /// module for the full contract. Only supported by pay and build.
+    #[structopt(long = "signer-command", conflicts_with = "key-file")]
+    signer_command: Option<String>,
+
+    /// Print tables without terminal styling. Styling is also left out
+    /// when the NO_COLOR environment variable is set or stdout is not a
+    /// terminal.
+    #[structopt(long = "no-color")]
+    no_color: bool,
+
+    #[structopt(flatten)]
+    submit: SubmitOpts,
+
+    #[structopt(flatten)]
+    timeouts: Timeouts,
+}
+
+/// Options that apply to every transaction submission
+#[derive(Debug, Default, Clone, StructOpt)]
+pub struct SubmitOpts {
+    /// Build and print transactions without submitting them, even when
+    /// --commit is given
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+
+    /// Append every submitted transaction as a json line to the given
+    /// file
+    #[structopt(long = "audit-log", env = "HELIUM_WALLET_AUDIT_LOG")]
+    audit_log: Option<PathBuf>,
+
+    /// Label submitted transactions for your own bookkeeping, like "rent".
+    /// Labels are stored locally in ~/.config/helium-wallet/labels.json and
+    /// listed by the history command.
+    #[structopt(long = "label")]
+    label: Option<String>,
+
+    /// Write a receipt with the hash, nonce, submission time and signed
+    /// transaction to the given file after submitting. Use the track
+    /// command to check the status of the transaction later. Commands that
+    /// submit several transactions write one receipt per transaction, with
+    /// the nonce (or the wallet address for consolidate) added to the file
+    /// name, like receipt-42.json.
+    #[structopt(long = "receipt")]
+    receipt: Option<PathBuf>,
+
+    /// Use the next nonce for a transaction even if the submit log has
+    /// another transaction of the same payer at that nonce
+    #[structopt(long = "force-nonce")]
+    force_nonce: bool,
+
+    /// Replace the transaction fee multiplier chain variable when
+    /// computing fees. For testing fee changes only, transactions with a
+    /// fee computed from a different multiplier are rejected by the chain.
+    #[structopt(long = "fee-multiplier", hidden = true)]
+    fee_multiplier: Option<u64>,
+}
+
+/// Timeouts for API requests and for waiting on submitted transactions
+#[derive(Debug, Clone, Copy, StructOpt)]
+pub struct Timeouts {
+    /// Seconds to wait for a connection to the API. By default only the
+    /// request timeout applies.
+    #[structopt(long = "connect-timeout")]
+    connect_timeout: Option<u64>,
+
+    /// Seconds a single API request may take, including connecting
+    #[structopt(long = "request-timeout", default_value = "120")]
+    request_timeout: u64,
+
+    /// Seconds to wait for a submitted transaction to clear with --wait
+    #[structopt(long = "wait-timeout", default_value = "600")]
+    wait_timeout: u64,
+}
+
+impl Timeouts {
+    /// An API client for the given base URL
+    pub fn client(&self, base_url: String) -> Result<Client> {
+        Client::new_with_timeouts(
+            base_url,
+            self.connect_timeout.map(Duration::from_secs),
+            Duration::from_secs(self.request_timeout),
+        )
+    }
+
+    /// How long to wait for a submitted transaction to clear
+    pub fn wait(&self) -> Duration {
+        Duration::from_secs(self.wait_timeout)
+    }
+}
+
+impl Opts {
+    /// Whether output is requested in json format
+    pub fn is_json(&self) -> bool {
+        matches!(self.format, OutputFormat::Json)
+    }
+
+    /// The TLS settings for API clients. Warns when certificates are not
+    /// verified.
+    pub fn tls(&self) -> Result<api::Tls> {
+        if self.allow_insecure_tls {
+            eprintln!(
+                "WARNING: --allow-insecure-tls given, TLS certificates of the API are NOT verified"
+            );
+        }
+        let ca_cert = match &self.ca_cert {
+            Some(path) => Some(api::Tls::read_ca_cert(path)?),
+            None => None,
+        };
+        Ok(api::Tls {
+            allow_insecure: self.allow_insecure_tls,
+            ca_cert,
+        })
+    }
+
+    /// Whether an external signer command is used instead of a key
+    pub fn has_signer_command(&self) -> bool {
+        self.signer_command.is_some()
+    }
+
+    /// Whether tables are printed with terminal styling
+    pub fn color(&self) -> bool {
+        use_color(
+            self.no_color,
+            env::var_os("NO_COLOR").map_or(false, |value| !value.is_empty()),
+            atty::is(atty::Stream::Stdout),
+        )
+    }
+}
+
+/// Whether printed tables use terminal styling. Set once from the global
+/// options before a command runs.
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_color(color: bool) {
+    COLOR.store(color, Ordering::SeqCst);
+}
+
+fn use_color(no_color: bool, no_color_env: bool, is_tty: bool) -> bool {
+    !no_color && !no_color_env && is_tty
+}
+
+fn default_format() -> &'static str {
+    format_for_tty(atty::is(atty::Stream::Stdout))
+}
+
+fn format_for_tty(is_tty: bool) -> &'static str {
+    if is_tty {
+        "table"
+    } else {
+        "json"
+    }
+}
+
+/// Options for commands that can write bulk output to a file
+#[derive(Debug, StructOpt)]
+pub struct OutputFile {
+    /// Write the output to the given file instead of stdout
+    #[structopt(long = "output-file")]
+    output_file: Option<PathBuf>,
+
+    /// Gzip compress the output. Implied when the output file has a .gz
+    /// extension
+    #[structopt(long)]
+    gzip: bool,
+
+    /// Overwrite an existing output file
+    #[structopt(long, requires = "output-file")]
+    force: bool,
+}
+
+impl OutputFile {
+    pub fn is_gzip(&self) -> bool {
+        self.gzip
+            || self
+                .output_file
+                .as_ref()
+                .map_or(false, |path| get_file_extension(path) == "gz")
+    }
+
+    /// Returns a writer for the selected output file, or stdout if no
+    /// output file was given. The output is gzip compressed if requested.
+    /// An existing output file is only overwritten with --force. The
+    /// returned writer must be completed with `OutputWriter::finish`.
+    pub fn writer(&self) -> Result<OutputWriter> {
+        let writer: Box<dyn io::Write> = match &self.output_file {
+            Some(path) => {
+                let file = fs::OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .create_new(!self.force)
+                    .truncate(self.force)
+                    .open(path)
+                    .map_err(|err| match err.kind() {
+                        io::ErrorKind::AlreadyExists => anyhow!(
+                            "Output file {} exists, use --force to overwrite it",
+                            path.display()
+                        ),
+                        _ => err.into(),
+                    })?;
+                Box::new(file)
+            }
+            None => Box::new(io::stdout()),
+        };
+        if self.is_gzip() {
+            Ok(OutputWriter::Gzip(GzEncoder::new(
+                writer,
+                Compression::default(),
+            )))
+        } else {
+            Ok(OutputWriter::Plain(writer))
+        }
+    }
+}
+
+/// A writer for bulk output, see `OutputFile::writer`
+pub enum OutputWriter {
+    Plain(Box<dyn io::Write>),
+    Gzip(GzEncoder<Box<dyn io::Write>>),
 }
 
+impl OutputWriter {
+    /// Completes the output. For gzip output this writes the gzip trailer,
+    /// reporting any error doing so instead of losing it on drop.
+    pub fn finish(self) -> Result {
+        match self {
+            Self::Plain(mut writer) => writer.flush()?,
+            Self::Gzip(encoder) => encoder.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+impl io::Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(writer) => writer.write(buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(writer) => writer.flush(),
+            Self::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+arg_enum! {
+    /// The encoding of transaction envelopes that are read or emitted
+    #[derive(Debug, Clone, Copy)]
+    pub enum Encoding {
+        Base64,
+        Hex,
+    }
+}
+
+impl Encoding {
+    pub fn encode(self, envelope: &BlockchainTxn) -> Result<String> {
+        match self {
+            Self::Base64 => envelope.to_b64(),
+            Self::Hex => envelope.to_hex(),
+        }
+    }
+
+    pub fn decode(self, encoded: &str) -> Result<BlockchainTxn> {
+        match self {
+            Self::Base64 => BlockchainTxn::from_b64(encoded),
+            Self::Hex => BlockchainTxn::from_hex(encoded),
+        }
+    }
+}
+
+/// An encoded transaction given on the command line. It is decoded with
+/// the selected encoding by `read_txn`.
 #[derive(Debug, Clone)]
-pub struct Transaction(BlockchainTxn);
+pub struct Transaction(String);
 
 impl std::str::FromStr for Transaction {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        Ok(Self(BlockchainTxn::from_b64(s)?))
+        Ok(Self(s.to_string()))
     }
 }
 
 fn load_wallet(files: Vec<PathBuf>) -> Result<Wallet> {
     let mut files_iter = files.iter();
     let mut first_wallet = match files_iter.next() {
-        Some(path) => {
-            let mut reader = fs::File::open(path)?;
-            Wallet::read(&mut reader)?
-        }
+        Some(path) => read_wallet_file(path)?,
         None => bail!("At least one wallet file expected"),
     };
 
     for path in files_iter {
-        let mut reader = fs::File::open(path)?;
-        let w = Wallet::read(&mut reader)?;
+        let w = read_wallet_file(path)?;
         first_wallet.absorb_shard(&w)?;
     }
 
     Ok(first_wallet)
 }
 
-fn get_password(confirm: bool) -> std::io::Result<String> {
-    match env::var("HELIUM_WALLET_PASSWORD") {
-        Ok(str) => Ok(str),
-        _ => {
-            use dialoguer::Password;
-            let mut builder = Password::new();
-            builder.with_prompt("Password");
-            if confirm {
-                builder.with_confirmation("Confirm password", "Passwords do not match");
-            };
-            builder.interact()
+fn read_wallet_file(path: &Path) -> Result<Wallet> {
+    #[cfg(test)]
+    {
+        if let Some(wallet) = testing::wallet(path) {
+            return wallet;
         }
     }
+    let mut reader = fs::File::open(path)?;
+    Wallet::read(&mut reader)
+}
+
+/// Support for driving commands in tests without wallet files or a
+/// password prompt
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use std::{cell::RefCell, collections::HashMap};
+
+    thread_local! {
+        static WALLETS: RefCell<HashMap<PathBuf, Vec<u8>>> = RefCell::new(HashMap::new());
+    }
+
+    /// Makes load_wallet return the given in-memory wallet for the given
+    /// file on the current thread instead of reading the file. Wallets are
+    /// decrypted with the in-memory wallet password without a prompt.
+    pub fn use_wallet(path: &Path, wallet: &Wallet) {
+        let mut buffer = vec![];
+        wallet.write(&mut buffer).expect("wallet write");
+        WALLETS.with(|wallets| wallets.borrow_mut().insert(path.to_path_buf(), buffer));
+    }
+
+    pub(super) fn wallet(path: &Path) -> Option<Result<Wallet>> {
+        WALLETS.with(|wallets| {
+            wallets
+                .borrow()
+                .get(path)
+                .map(|buffer| Wallet::read(&mut io::Cursor::new(buffer)))
+        })
+    }
+
+    /// The password to use instead of prompting when in-memory wallets are
+    /// used on the current thread
+    pub(super) fn password() -> Option<String> {
+        WALLETS.with(|wallets| {
+            if wallets.borrow().is_empty() {
+                None
+            } else {
+                Some(crate::wallet::IN_MEMORY_PASSWORD.to_string())
+            }
+        })
+    }
+
+    /// An account with the given HNT balance in bones and DC balance at
+    /// nonce 4, with a speculative nonce of 5
+    pub fn account(address: &PublicKey, hnt: u64, dc: u64) -> Account {
+        serde_json::from_value(json!({
+            "address": address.to_string(),
+            "balance": hnt,
+            "dc_balance": dc,
+            "dc_nonce": 0,
+            "sec_balance": 0,
+            "sec_nonce": 0,
+            "speculative_sec_nonce": 0,
+            "nonce": 4,
+            "speculative_nonce": 5,
+            "block": 100,
+            "staked_balance": 0,
+        }))
+        .expect("account")
+    }
+
+    /// An API for pay that returns the given account, an oracle price of
+    /// $1.00 per HNT at block 100 and fixed transaction fees. Submitting
+    /// panics, so only previews can be run against it.
+    pub struct MockApi {
+        pub account: Account,
+    }
+
+    impl SubmitTxn for MockApi {
+        fn submit(&self, _envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+            panic!("preview submitted")
+        }
+
+        fn account_nonce(&self, _address: &PublicKey) -> Result<u64> {
+            Ok(self.account.nonce)
+        }
+    }
+
+    impl pay::PayApi for MockApi {
+        fn account(&self, _address: &PublicKey) -> Result<Account> {
+            Ok(self.account.clone())
+        }
+
+        fn oracle_price(&self) -> Result<api::OraclePrice> {
+            Ok(api::OraclePrice {
+                price: 100_000_000,
+                block: 100,
+                timestamp: None,
+            })
+        }
+
+        fn height(&self) -> Result<u64> {
+            Ok(100)
+        }
+
+        fn txn_fees(&self) -> Result<TxnFeeConfig> {
+            Ok(TxnFeeConfig::with_txn_fees(5000, 24))
+        }
+    }
+
+    /// Runs pay with the given arguments as a json preview against a
+    /// `MockApi` for the given account. The wallet is the in-memory wallet
+    /// given to `use_wallet` for "in-memory.key".
+    pub fn run_pay(args: &[&str], account: Account) -> Result<Vec<BlockchainTxn>> {
+        let cmd = pay::Cmd::from_iter(std::iter::once("pay").chain(args.iter().copied()));
+        let opts = Opts::from_iter(&["helium-wallet", "-f", "in-memory.key", "--format", "json"]);
+        cmd.run_with(opts, |_| Ok(MockApi { account }))
+    }
+}
+
+/// The number of times a new password is prompted for when the
+/// confirmation does not match
+const PASSWORD_ATTEMPTS: usize = 3;
+
+/// Prompts for a password unless it is set in the environment. With
+/// `confirm` set, used when a wallet is about to be encrypted, the
+/// password has to be entered twice.
+fn get_password(confirm: bool) -> Result<String> {
+    #[cfg(test)]
+    {
+        if let Some(password) = testing::password() {
+            return Ok(password);
+        }
+    }
+    if let Ok(password) = env::var("HELIUM_WALLET_PASSWORD") {
+        return Ok(password);
+    }
+    let prompt = |prompt: &str| dialoguer::Password::new().with_prompt(prompt).interact();
+    if confirm {
+        confirm_password(PASSWORD_ATTEMPTS, prompt)
+    } else {
+        Ok(prompt("Password")?)
+    }
+}
+
+fn confirm_password<F>(attempts: usize, mut prompt: F) -> Result<String>
+where
+    F: FnMut(&str) -> io::Result<String>,
+{
+    for _ in 0..attempts {
+        let mut password = prompt("Password")?;
+        let mut confirmation = prompt("Confirm password")?;
+        let matches = password == confirmation;
+        confirmation.zeroize();
+        if matches {
+            return Ok(password);
+        }
+        password.zeroize();
+        eprintln!("Passwords do not match");
+    }
+    bail!("Passwords did not match after {} attempts", attempts)
+}
+
+/// Prompts for the password of a wallet that is going to be decrypted.
+/// Watch-only wallets can not be decrypted and fail without prompting.
+fn get_wallet_password(wallet: &Wallet) -> Result<String> {
+    if wallet.is_watch_only() {
+        bail!("watch-only wallet cannot sign");
+    }
+    get_password(false)
+}
+
+/// Decrypts the keypair of a given wallet, prompting for the password.
+/// With a key file the keypair is decrypted from the key file instead.
+fn decrypt_wallet(wallet: &Wallet, key_file: &Option<PathBuf>) -> Result<Keypair> {
+    KeySource::new(key_file.as_deref()).decrypt(wallet)
+}
+
+/// The signer for a wallet. With a signer command the command signs,
+/// otherwise the key is decrypted like with `decrypt_wallet`.
+fn wallet_signer(
+    wallet: &Wallet,
+    key_file: &Option<PathBuf>,
+    signer_command: &Option<String>,
+) -> Result<Box<dyn Signer>> {
+    match signer_command {
+        Some(command) => Ok(Box::new(CommandSigner::new(
+            command,
+            wallet.public_key.clone(),
+        ))),
+        None => Ok(Box::new(decrypt_wallet(wallet, key_file)?)),
+    }
+}
+
+/// Where the key that signs for a wallet comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeySource {
+    /// The key encrypted in the wallet file(s)
+    Wallet,
+    /// A separately stored encrypted key, like a basic wallet file, for the
+    /// public key of the wallet. The wallet itself may be watch-only.
+    KeyFile(PathBuf),
+}
+
+impl KeySource {
+    pub fn new(key_file: Option<&Path>) -> Self {
+        match key_file {
+            Some(path) => Self::KeyFile(path.to_path_buf()),
+            None => Self::Wallet,
+        }
+    }
+
+    /// Decrypts the keypair for the given wallet, prompting for the
+    /// password of the key. Fails if the key is not the key of the wallet.
+    pub fn decrypt(&self, wallet: &Wallet) -> Result<Keypair> {
+        match self {
+            Self::Wallet => decrypt_key(wallet),
+            Self::KeyFile(path) => {
+                let key = read_wallet_file(path)?;
+                check_key_matches(&key, wallet, path)?;
+                decrypt_key(&key)
+            }
+        }
+    }
+}
+
+/// Checks that a key file holds the key for the address of the wallet
+fn check_key_matches(key: &Wallet, wallet: &Wallet, path: &Path) -> Result {
+    if key.public_key != wallet.public_key {
+        bail!(
+            "Key file {} is for {}, not for the wallet address {}",
+            path.display(),
+            key.public_key,
+            wallet.public_key
+        );
+    }
+    Ok(())
+}
+
+fn decrypt_key(wallet: &Wallet) -> Result<Keypair> {
+    let mut password = get_wallet_password(wallet)?;
+    let keypair = wallet.decrypt(password.as_bytes());
+    password.zeroize();
+    keypair
 }
 
 const DEFAULT_TESTNET_BASE_URL: &str = "https://testnet-api.helium.wtf/v1";
 
-fn api_url(network: Network) -> String {
+/// Resolves the API URL to use for a given network. An explicit
+/// `--api-url` takes precedence over the `HELIUM_WALLET_API_URL`
+/// environment variable, which in turn takes precedence over the network
+/// derived default.
+fn api_url(url: &Option<String>, network: Network) -> String {
+    resolve_api_url(url.clone(), env::var("HELIUM_WALLET_API_URL").ok(), network)
+}
+
+fn resolve_api_url(url: Option<String>, env_url: Option<String>, network: Network) -> String {
+    if let Some(url) = url {
+        return url;
+    }
+    if let Some(url) = env_url {
+        match url_network(&url) {
+            Some(url_network) if url_network != network => eprintln!(
+                "warning: HELIUM_WALLET_API_URL {} looks like a {} API but the wallet is on {}",
+                url, url_network, network
+            ),
+            _ => (),
+        }
+        return url;
+    }
+    network_api_url(network)
+}
+
+fn network_api_url(network: Network) -> String {
     match network {
         Network::MainNet => {
             env::var("HELIUM_API_URL").unwrap_or_else(|_| helium_api::DEFAULT_BASE_URL.to_string())
@@ -114,13 +721,25 @@ fn api_url(network: Network) -> String {
     }
 }
 
-fn read_txn(txn: &Option<Transaction>) -> Result<BlockchainTxn> {
+/// Makes a best effort guess at the network a given API URL serves. Returns
+/// None if the network can not be determined from the URL.
+fn url_network(url: &str) -> Option<Network> {
+    if url.contains("testnet") {
+        Some(Network::TestNet)
+    } else if url.starts_with(helium_api::DEFAULT_BASE_URL) {
+        Some(Network::MainNet)
+    } else {
+        None
+    }
+}
+
+fn read_txn(txn: &Option<Transaction>, encoding: Encoding) -> Result<BlockchainTxn> {
     match txn {
-        Some(txn) => Ok(txn.0.clone()),
+        Some(txn) => encoding.decode(&txn.0),
         None => {
             let mut buffer = String::new();
             io::stdin().read_line(&mut buffer)?;
-            Ok(buffer.trim().parse::<Transaction>()?.0)
+            encoding.decode(buffer.trim())
         }
     }
 }
@@ -137,14 +756,19 @@ fn collect_addresses(files: Vec<PathBuf>, mut addresses: Vec<PublicKey>) -> Resu
     Ok(addresses)
 }
 
-fn get_seed_words() -> Result<Vec<String>> {
+/// Prompts for seed words until they convert to entropy with the given
+/// checksum and word matching.
+fn get_seed_words(
+    checksum: mnemonic::Checksum,
+    matching: mnemonic::WordMatch,
+) -> Result<Vec<String>> {
     use dialoguer::Input;
     let split_str = |s: &String| s.split_whitespace().map(|w| w.to_string()).collect();
     let word_string = Input::<String>::new()
         .with_prompt("Seed Words")
         .validate_with(|v: &String| {
             let word_list = split_str(v);
-            match mnemonic::mnemonic_to_entropy(word_list) {
+            match mnemonic::mnemonic_to_entropy_checked(word_list, checksum, matching) {
                 Ok(_) => Ok(()),
                 Err(err) => Err(err),
             }
@@ -156,6 +780,81 @@ fn get_seed_words() -> Result<Vec<String>> {
         .collect())
 }
 
+/// Reads seed words from the given file, or from stdin for "-", so they
+/// never appear on the command line. A file that other users can read is
+/// refused.
+fn read_seed_words(path: &Path) -> Result<Vec<String>> {
+    let mut contents = String::new();
+    if path == Path::new("-") {
+        io::Read::read_to_string(&mut io::stdin(), &mut contents)?;
+    } else {
+        check_private_file(path)?;
+        contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read seed file {}: {}", path.display(), e))?;
+    }
+    let words = parse_seed_words(&contents);
+    contents.zeroize();
+    Ok(words)
+}
+
+/// Splits seed words on any whitespace, including line breaks, and
+/// lowercases them
+fn parse_seed_words(contents: &str) -> Vec<String> {
+    contents
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+#[cfg(unix)]
+fn check_private_file(path: &Path) -> Result {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(path)
+        .map_err(|e| anyhow!("Failed to read seed file {}: {}", path.display(), e))?
+        .permissions()
+        .mode();
+    if mode & 0o004 != 0 {
+        bail!(
+            "Seed file {} is readable by other users, restrict it with chmod 600",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_private_file(_path: &Path) -> Result {
+    Ok(())
+}
+
+/// Prompts for SLIP-0039 mnemonic shares until the number of shares
+/// required by the first share is reached, and an optional passphrase, and
+/// returns the entropy for the recovered master secret.
+#[cfg(feature = "slip39")]
+fn get_slip39_entropy() -> Result<[u8; 32]> {
+    use crate::mnemonic::slip39;
+    use dialoguer::{Input, Password};
+    let mut shares = vec![];
+    let mut required = 1;
+    while shares.len() < required {
+        let share = Input::<String>::new()
+            .with_prompt(format!("Share {}", shares.len() + 1))
+            .validate_with(|v: &String| slip39::Share::from_mnemonic(v).map(|_| ()))
+            .interact()?;
+        if shares.is_empty() {
+            required = slip39::required_shares(&slip39::Share::from_mnemonic(&share)?);
+        }
+        shares.push(share);
+    }
+    let mut passphrase = Password::new()
+        .with_prompt("Share passphrase (empty for none)")
+        .allow_empty_password(true)
+        .interact()?;
+    let entropy = slip39::shares_to_entropy(&shares, passphrase.as_bytes());
+    passphrase.zeroize();
+    entropy
+}
+
 pub fn get_payer(staking_address: PublicKey, payer: &Option<String>) -> Result<Option<PublicKey>> {
     match payer {
         Some(s) if s == "staking" => Ok(Some(staking_address)),
@@ -167,7 +866,27 @@ pub fn get_payer(staking_address: PublicKey, payer: &Option<String>) -> Result<O
     }
 }
 
-pub fn get_txn_fees(client: &Client) -> Result<TxnFeeConfig> {
+/// Fetches the transaction fee configuration from the chain variables,
+/// applying a fee multiplier override from the given options
+pub fn get_txn_fees(client: &Client, opts: &SubmitOpts) -> Result<TxnFeeConfig> {
+    Ok(override_txn_fees(fetch_txn_fees(client)?, opts))
+}
+
+/// Replaces the fee multiplier of the given fee configuration when
+/// --fee-multiplier is given. Nothing else in the configuration changes.
+pub fn override_txn_fees(mut config: TxnFeeConfig, opts: &SubmitOpts) -> TxnFeeConfig {
+    if let Some(multiplier) = opts.fee_multiplier {
+        eprintln!(
+            "WARNING: Using fee multiplier {} instead of the chain value {}. This is for testing only, transactions with these fees will be rejected.",
+            multiplier,
+            config.txn_fee_multiplier()
+        );
+        config.set_txn_fee_multiplier(multiplier);
+    }
+    config
+}
+
+fn fetch_txn_fees(client: &Client) -> Result<TxnFeeConfig> {
     let vars = client.get_vars()?;
     if vars.contains_key("txn_fees") {
         match vars["txn_fees"].as_bool() {
@@ -182,6 +901,652 @@ pub fn get_txn_fees(client: &Client) -> Result<TxnFeeConfig> {
     }
 }
 
+/// Re-reads the speculative nonce of the given account and fails if it
+/// changed from the nonce a transaction was built against. This detects
+/// transactions submitted for the same account by other tools in the
+/// meantime.
+pub fn check_nonce(client: &Client, address: &str, expected: u64) -> Result {
+    let current = client.get_account(address)?.speculative_nonce;
+    verify_nonce(expected, current)
+}
+
+/// Shown for an account that is not on chain yet
+pub const NOT_ON_CHAIN: &str = "account not yet on chain (no activity, zero balance)";
+
+/// Fetches the account for the given address. Returns None for an account
+/// that is not on chain yet, which has no activity and a zero balance.
+pub fn fetch_account(client: &Client, address: &PublicKey) -> Result<Option<Account>> {
+    match client.get_account(&address.to_string()) {
+        Ok(account) => Ok(Some(account)),
+        Err(err) => {
+            let err = Error::from(err);
+            if is_not_found(&err) {
+                Ok(None)
+            } else {
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Fetches the account a transaction spends from. Fails for an account
+/// that is not on chain yet since it has nothing to spend.
+pub fn fetch_payer_account(client: &Client, address: &PublicKey) -> Result<Account> {
+    fetch_account(client, address)?.ok_or_else(|| {
+        anyhow!(
+            "Cannot spend from account {}, it has no on-chain balance",
+            address
+        )
+    })
+}
+
+fn verify_nonce(expected: u64, current: u64) -> Result {
+    if expected != current {
+        bail!(
+            "Speculative nonce changed from {} to {}, not submitting to avoid a double spend",
+            expected,
+            current
+        );
+    }
+    Ok(())
+}
+
+/// Checks that the given transaction draws from the expected balance type
+/// and that the matching balance of the account covers its amount.
+pub fn check_balance(account: &Account, envelope: &BlockchainTxn, expected: BalanceType) -> Result {
+    let (balance_type, required) = envelope.txn_balance()?;
+    if balance_type != expected {
+        bail!(
+            "Transaction draws from the {} balance, expected the {} balance",
+            balance_type,
+            expected
+        );
+    }
+    let available = match balance_type {
+        BalanceType::Hnt => account.balance,
+        BalanceType::Hst => account.sec_balance,
+    };
+    verify_balance(balance_type, available, required)
+}
+
+fn verify_balance(balance_type: BalanceType, available: u64, required: u64) -> Result {
+    if available < required {
+        let (available, required) = match balance_type {
+            BalanceType::Hnt => (
+                Hnt::from_bones(available).to_string(),
+                Hnt::from_bones(required).to_string(),
+            ),
+            BalanceType::Hst => (
+                Hst::from_bones(available).to_string(),
+                Hst::from_bones(required).to_string(),
+            ),
+        };
+        bail!(
+            "Insufficient {} balance {} for an amount of {}",
+            balance_type,
+            available,
+            required
+        );
+    }
+    Ok(())
+}
+
+/// Submits transactions to the API
+pub trait SubmitTxn {
+    fn submit(&self, envelope: &BlockchainTxn) -> Result<PendingTxnStatus>;
+    /// The current (confirmed) nonce of the given account
+    fn account_nonce(&self, address: &PublicKey) -> Result<u64>;
+}
+
+impl SubmitTxn for Client {
+    fn submit(&self, envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+        self.submit_txn(envelope)
+            .map_err(|err| SubmitError::classify(err).into())
+    }
+
+    fn account_nonce(&self, address: &PublicKey) -> Result<u64> {
+        Ok(self.get_account(&address.to_string())?.nonce)
+    }
+}
+
+/// Why submitting a transaction to the API failed
+#[derive(Debug)]
+pub enum SubmitError {
+    /// The API could not be reached or failed to handle the request. The
+    /// same transaction can be submitted again.
+    Network(Error),
+    /// The API rejected the transaction. It has to be fixed and rebuilt
+    /// before it is submitted again.
+    Rejected(Error),
+}
+
+impl SubmitError {
+    /// Classifies a submit failure by the first http or io error in its
+    /// chain of causes. Connection failures, timeouts and server errors
+    /// (5xx) are network errors, client errors (4xx) and anything else
+    /// are rejections.
+    pub fn classify(err: Error) -> Self {
+        let transient = err
+            .chain()
+            .find_map(|cause| {
+                if let Some(http_err) = cause.downcast_ref::<reqwest::Error>() {
+                    Some(match http_err.status() {
+                        Some(status) => status.is_server_error(),
+                        None => !http_err.is_decode(),
+                    })
+                } else if cause.is::<io::Error>() {
+                    Some(true)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(false);
+        if transient {
+            Self::Network(err)
+        } else {
+            Self::Rejected(err)
+        }
+    }
+
+    /// Whether the failure is transient and submitting the same
+    /// transaction again may succeed
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Network(_))
+    }
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Network(_) => f.write_str("Failed to reach the API to submit the transaction"),
+            Self::Rejected(_) => f.write_str("The transaction was rejected"),
+        }
+    }
+}
+
+impl error::Error for SubmitError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Network(err) | Self::Rejected(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// Returns the payer and nonce of transactions that use the account nonce
+fn txn_nonce(envelope: &BlockchainTxn) -> Result<Option<(PublicKey, u64)>> {
+    let nonce = match &envelope.txn {
+        Some(Txn::Payment(t)) => t.nonce,
+        Some(Txn::PaymentV2(t)) => t.nonce,
+        Some(Txn::TokenBurn(t)) => t.nonce,
+        Some(Txn::CreateHtlc(t)) => t.nonce,
+        _ => return Ok(None),
+    };
+    Ok(envelope.payer()?.map(|payer| (payer, nonce)))
+}
+
+/// Checks the nonce of a transaction against the freshly fetched account
+/// nonce. A transaction with a nonce that was already used is rejected by
+/// the chain.
+fn check_stale_nonce(client: &dyn SubmitTxn, envelope: &BlockchainTxn) -> Result {
+    if let Some((payer, nonce)) = txn_nonce(envelope)? {
+        let account_nonce = client.account_nonce(&payer)?;
+        if nonce < account_nonce {
+            bail!(
+                "Transaction nonce {} is below current account nonce {}",
+                nonce,
+                account_nonce
+            );
+        }
+        if nonce == account_nonce {
+            bail!("Transaction nonce {} was already used", nonce);
+        }
+    }
+    Ok(())
+}
+
+/// Checks the nonce chosen for a transaction of the given payer against
+/// the submit log in the user configuration directory. Called before the
+/// transaction is signed, so a nonce that another logged transaction was
+/// submitted at is refused before asking for the wallet password. Nothing
+/// is checked without `commit`, in a dry run or with `--force-nonce`.
+pub fn check_logged_nonce(
+    payer: &PublicKey,
+    nonce: u64,
+    commit: bool,
+    opts: &SubmitOpts,
+) -> Result {
+    if !commit || opts.dry_run || opts.force_nonce {
+        return Ok(());
+    }
+    match config_dir() {
+        Some(dir) => SubmitLog::load(&dir.join("submitted.json"))?.check_nonce(payer, nonce),
+        None => Ok(()),
+    }
+}
+
+/// Checks the nonce of an already built transaction against the submit
+/// log like `check_logged_nonce`.
+pub fn check_logged_txn_nonce(envelope: &BlockchainTxn, commit: bool, opts: &SubmitOpts) -> Result {
+    match txn_nonce(envelope)? {
+        Some((payer, nonce)) => check_logged_nonce(&payer, nonce, commit, opts),
+        None => Ok(()),
+    }
+}
+
+/// Submits the given transaction if `commit` is set. A dry run never
+/// submits, regardless of `commit`. Returns None if the transaction was not
+/// submitted. Submitted transactions are recorded in the submit log in the
+/// user configuration directory and an identical transaction is refused.
+/// If an audit log is given successfully submitted transactions are
+/// appended to it.
+pub fn submit_txn(
+    client: &dyn SubmitTxn,
+    envelope: &BlockchainTxn,
+    commit: bool,
+    opts: &SubmitOpts,
+) -> Result<Option<PendingTxnStatus>> {
+    let mut log = match config_dir() {
+        Some(dir) if commit && !opts.dry_run => SubmitLog::load(&dir.join("submitted.json"))?,
+        _ => SubmitLog::default(),
+    };
+    submit_txn_logged(client, &mut log, envelope, commit, opts)
+}
+
+fn submit_txn_logged(
+    client: &dyn SubmitTxn,
+    log: &mut SubmitLog,
+    envelope: &BlockchainTxn,
+    commit: bool,
+    opts: &SubmitOpts,
+) -> Result<Option<PendingTxnStatus>> {
+    match (commit, opts.dry_run) {
+        (true, false) => {
+            log.check(envelope)?;
+            check_stale_nonce(client, envelope)?;
+            // Recording a submission must not be cut short by an interrupt
+            let status = interrupt::critical(|| -> Result<PendingTxnStatus> {
+                let status = client.submit(envelope)?;
+                // The transaction is on its way to the chain once submitted,
+                // so failing to record it only warns
+                if let Err(err) = log.record(envelope, &status) {
+                    eprintln!(
+                        "Warning: failed to record transaction {} in the submit log: {:#}",
+                        status.hash, err
+                    );
+                }
+                if let Some(path) = &opts.audit_log {
+                    if let Err(err) = audit_log::append(path, envelope, &status) {
+                        eprintln!(
+                            "Warning: failed to append transaction {} to the audit log {}: {:#}",
+                            status.hash,
+                            path.display(),
+                            err
+                        );
+                    }
+                }
+                if let Some(path) = &opts.receipt {
+                    if let Err(err) =
+                        Receipt::new(envelope, &status).and_then(|receipt| receipt.write(path))
+                    {
+                        eprintln!(
+                            "Warning: failed to write the receipt for transaction {} to {}: {:#}",
+                            status.hash,
+                            path.display(),
+                            err
+                        );
+                    }
+                }
+                if let Some(label) = &opts.label {
+                    match labels::labels_path() {
+                        Some(path) => {
+                            if let Err(err) = labels::append(&path, &status.hash, label) {
+                                eprintln!(
+                                    "Warning: failed to store the label for transaction {} in {}: {:#}",
+                                    status.hash,
+                                    path.display(),
+                                    err
+                                );
+                            }
+                        }
+                        None => {
+                            eprintln!("Warning: no configuration directory to store the label in")
+                        }
+                    }
+                }
+                Ok(status)
+            })?;
+            interrupt::check(|| format!("transaction {} submitted", status.hash))?;
+            Ok(Some(status))
+        }
+        (true, true) => {
+            eprintln!("Dry run: not submitting the transaction");
+            Ok(None)
+        }
+        (false, _) => Ok(None),
+    }
+}
+
+/// The outcome of one transaction of a batch submitted with submit_batch
+#[derive(Debug)]
+pub enum BatchOutcome {
+    /// The transaction was submitted, or None if it was not submitted
+    /// without commit or in a dry run
+    Submitted(Option<PendingTxnStatus>),
+    /// Submitting the transaction failed
+    Failed(Error),
+    /// The transaction was not submitted since an earlier nonce of its
+    /// payer failed. It would be stranded behind the nonce gap.
+    Skipped,
+}
+
+#[derive(Debug)]
+pub struct BatchResult {
+    pub nonce: Option<u64>,
+    pub outcome: BatchOutcome,
+}
+
+impl BatchResult {
+    pub fn status(&self) -> &'static str {
+        match &self.outcome {
+            BatchOutcome::Submitted(Some(_)) => "submitted",
+            BatchOutcome::Submitted(None) => "preview",
+            BatchOutcome::Failed(_) => "failed",
+            BatchOutcome::Skipped => "not submitted",
+        }
+    }
+
+    pub fn hash(&self) -> Option<&str> {
+        match &self.outcome {
+            BatchOutcome::Submitted(Some(status)) => Some(&status.hash),
+            _ => None,
+        }
+    }
+
+    /// Why the transaction failed or was not submitted
+    pub fn reason(&self) -> Option<String> {
+        match &self.outcome {
+            BatchOutcome::Failed(err) => Some(format!("{:#}", err)),
+            BatchOutcome::Skipped => Some("an earlier nonce failed".to_string()),
+            BatchOutcome::Submitted(_) => None,
+        }
+    }
+}
+
+/// Submits the given transactions in order and collects the outcome of
+/// each. A failed transaction does not abort the batch, but later
+/// transactions of the same payer are not submitted once one of its
+/// nonces failed, since the chain would never clear them.
+pub fn submit_batch(
+    client: &dyn SubmitTxn,
+    envelopes: &[BlockchainTxn],
+    commit: bool,
+    opts: &SubmitOpts,
+) -> Result<Vec<BatchResult>> {
+    let mut log = match config_dir() {
+        Some(dir) if commit && !opts.dry_run => SubmitLog::load(&dir.join("submitted.json"))?,
+        _ => SubmitLog::default(),
+    };
+    submit_batch_logged(client, &mut log, envelopes, commit, opts)
+}
+
+fn submit_batch_logged(
+    client: &dyn SubmitTxn,
+    log: &mut SubmitLog,
+    envelopes: &[BlockchainTxn],
+    commit: bool,
+    opts: &SubmitOpts,
+) -> Result<Vec<BatchResult>> {
+    let mut failed: Vec<(PublicKey, u64)> = vec![];
+    let mut results: Vec<BatchResult> = Vec::with_capacity(envelopes.len());
+    for (index, envelope) in envelopes.iter().enumerate() {
+        let payer_nonce = txn_nonce(envelope)?;
+        // Every transaction of a batch gets its own receipt
+        let receipt_opts;
+        let txn_opts = match &opts.receipt {
+            Some(path) if envelopes.len() > 1 => {
+                let suffix = payer_nonce
+                    .as_ref()
+                    .map_or(index as u64 + 1, |(_, nonce)| *nonce);
+                receipt_opts = SubmitOpts {
+                    receipt: Some(batch_receipt_path(path, suffix)),
+                    ..opts.clone()
+                };
+                &receipt_opts
+            }
+            _ => opts,
+        };
+        let blocked = payer_nonce.as_ref().map_or(false, |(payer, nonce)| {
+            failed.iter().any(|(p, n)| p == payer && n < nonce)
+        });
+        let outcome = if blocked {
+            BatchOutcome::Skipped
+        } else {
+            // The batch reports its own state when interrupted below
+            match interrupt::critical(|| submit_txn_logged(client, log, envelope, commit, txn_opts))
+            {
+                Ok(status) => BatchOutcome::Submitted(status),
+                Err(err) => {
+                    if let Some(payer_nonce) = &payer_nonce {
+                        failed.push(payer_nonce.clone());
+                    }
+                    BatchOutcome::Failed(err)
+                }
+            }
+        };
+        results.push(BatchResult {
+            nonce: payer_nonce.map(|(_, nonce)| nonce),
+            outcome,
+        });
+        interrupt::check(|| {
+            format!(
+                "{} of {} transactions handled; the rest NOT submitted",
+                results.len(),
+                envelopes.len()
+            )
+        })?;
+    }
+    Ok(results)
+}
+
+/// The receipt path for one transaction of several: the given path with
+/// the given suffix, like the nonce of the transaction, added to the file
+/// name, like receipt-42.json for receipt.json
+pub fn batch_receipt_path(path: &Path, suffix: impl fmt::Display) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, suffix, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+    path.with_file_name(name)
+}
+
+/// Fails with a report of where a submitted batch stopped if any of its
+/// transactions failed
+pub fn check_batch(results: &[BatchResult]) -> Result {
+    let describe = |index: usize, result: &BatchResult| match result.nonce {
+        Some(nonce) => format!("nonce {}", nonce),
+        None => format!("transaction {}", index + 1),
+    };
+    let failures: Vec<String> = results
+        .iter()
+        .enumerate()
+        .filter_map(|(index, result)| match &result.outcome {
+            BatchOutcome::Failed(err) => {
+                Some(format!("{} failed: {:#}", describe(index, result), err))
+            }
+            _ => None,
+        })
+        .collect();
+    if failures.is_empty() {
+        return Ok(());
+    }
+    let skipped: Vec<String> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, result)| matches!(result.outcome, BatchOutcome::Skipped))
+        .map(|(index, result)| describe(index, result))
+        .collect();
+    let mut report = format!("Submission stopped, {}", failures.join("; "));
+    if !skipped.is_empty() {
+        report.push_str(&format!("; later {} NOT submitted", skipped.join(", ")));
+    }
+    bail!("{}", report)
+}
+
+/// The helium-wallet directory in the user configuration directory
+/// ($XDG_CONFIG_HOME or ~/.config).
+pub fn config_dir() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("helium-wallet"))
+}
+
+/// Writes data to the given file by writing a temporary file next to it
+/// and renaming it over the file, so readers never see a partially
+/// written file.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or_default());
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    let result = (|| -> Result {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Polls the status of a submitted transaction until it cleared, failed or
+/// the given timeout passed.
+pub fn wait_for_txn(client: &api::Client, hash: &str, timeout: Duration) -> Result {
+    let start = Instant::now();
+    loop {
+        if let Some(txn) = client.get_pending_txn(hash)? {
+            match txn.status.as_str() {
+                "cleared" => return Ok(()),
+                "failed" => bail!(
+                    "Transaction {} failed: {}",
+                    hash,
+                    txn.failed_reason.unwrap_or_default()
+                ),
+                _ => (),
+            }
+        }
+        if start.elapsed() >= timeout {
+            bail!("Timed out waiting for transaction {} to clear", hash);
+        }
+        thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
+
+/// Waits for a submitted transaction to clear and then for the chain to
+/// grow until the transaction has at least the given number of
+/// confirmations. Returns the number of confirmations reached.
+pub fn wait_for_confirmations(
+    client: &api::Client,
+    hash: &str,
+    confirmations: u64,
+    timeout: Duration,
+) -> Result<u64> {
+    let start = Instant::now();
+    wait_for_txn(client, hash, timeout)?;
+    loop {
+        let mut depth = 0;
+        if let Some(txn_height) = client.get_txn_height(hash)? {
+            depth = confirmation_depth(client.get_height()?, txn_height);
+            if depth >= confirmations {
+                return Ok(depth);
+            }
+        }
+        if start.elapsed() >= timeout {
+            bail!(
+                "Timed out waiting for transaction {} to reach {} confirmations, it has {}",
+                hash,
+                confirmations,
+                depth
+            );
+        }
+        thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
+
+/// The number of confirmations of a transaction in the block at
+/// `txn_height`. The block with the transaction is the first confirmation.
+pub fn confirmation_depth(height: u64, txn_height: u64) -> u64 {
+    (height + 1).saturating_sub(txn_height)
+}
+
+/// Applies the given function to all items using at most `concurrency`
+/// worker threads. The results are returned in the order of the given
+/// items. Errors are expected to be part of the result type so a single
+/// failing item does not abort the others. A panicking worker thread
+/// results in an error for the whole map.
+pub fn map_concurrent<T, R, F>(items: Vec<T>, concurrency: usize, f: F) -> Result<Vec<R>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let count = items.len();
+    let queue = Arc::new(Mutex::new(items.into_iter().enumerate()));
+    let f = Arc::new(f);
+    let (tx, rx) = mpsc::channel();
+    let workers: Vec<_> = (0..concurrency.max(1).min(count))
+        .map(|_| {
+            let queue = queue.clone();
+            let f = f.clone();
+            let tx = tx.clone();
+            thread::spawn(move || loop {
+                // Release the queue lock before running the item
+                let next = queue.lock().expect("queue lock").next();
+                match next {
+                    Some((index, item)) => {
+                        if tx.send((index, f(item))).is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+    for (index, result) in rx {
+        results[index] = Some(result);
+    }
+    // A panicking worker has already reported the panic
+    let failed = workers
+        .into_iter()
+        .map(|worker| worker.join())
+        .filter(|joined| joined.is_err())
+        .count();
+    if failed > 0 {
+        bail!("{} worker thread(s) failed", failed);
+    }
+    results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| result.ok_or_else(|| anyhow!("Missing result for item {}", index)))
+        .collect()
+}
+
 pub fn open_output_file(filename: &Path, create: bool) -> io::Result<fs::File> {
     fs::OpenOptions::new()
         .write(true)
@@ -212,8 +1577,25 @@ pub fn print_json<T: ?Sized + serde::Serialize>(value: &T) -> Result {
     Ok(())
 }
 
+/// Prints the table to stdout. Without color the table is written as
+/// plain text with no terminal control sequences.
 pub fn print_table(table: &prettytable::Table) -> Result {
-    table.printstd();
+    if COLOR.load(Ordering::SeqCst) {
+        table.printstd();
+    } else {
+        table.print(&mut io::stdout())?;
+    }
+    Ok(())
+}
+
+pub fn write_json<T: ?Sized + serde::Serialize>(writer: &mut dyn io::Write, value: &T) -> Result {
+    serde_json::to_writer_pretty(&mut *writer, value)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+pub fn write_table(writer: &mut dyn io::Write, table: &prettytable::Table) -> Result {
+    table.print(writer)?;
     Ok(())
 }
 
@@ -224,3 +1606,730 @@ pub fn status_str(status: &Option<PendingTxnStatus>) -> &str {
 pub fn status_json(status: &Option<PendingTxnStatus>) -> serde_json::Value {
     status.as_ref().map_or(json!(null), |s| json!(s.hash))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAG_URL: &str = "https://flag.example.com/v1";
+    const ENV_URL: &str = "https://env.example.com/v1";
+
+    #[test]
+    fn api_url_flag_overrides_env() {
+        let url = resolve_api_url(
+            Some(FLAG_URL.to_string()),
+            Some(ENV_URL.to_string()),
+            Network::MainNet,
+        );
+        assert_eq!(FLAG_URL, url);
+    }
+
+    #[test]
+    fn api_url_env_overrides_network() {
+        let url = resolve_api_url(None, Some(ENV_URL.to_string()), Network::TestNet);
+        assert_eq!(ENV_URL, url);
+    }
+
+    #[test]
+    fn api_url_network_default() {
+        assert_eq!(
+            network_api_url(Network::MainNet),
+            resolve_api_url(None, None, Network::MainNet)
+        );
+        assert_eq!(
+            network_api_url(Network::TestNet),
+            resolve_api_url(None, None, Network::TestNet)
+        );
+    }
+
+    #[test]
+    fn api_url_network_guess() {
+        assert_eq!(
+            Some(Network::TestNet),
+            url_network(DEFAULT_TESTNET_BASE_URL)
+        );
+        assert_eq!(
+            Some(Network::MainNet),
+            url_network(helium_api::DEFAULT_BASE_URL)
+        );
+        assert_eq!(None, url_network(ENV_URL));
+    }
+    #[test]
+    fn output_file_gzip() {
+        let output = OutputFile::from_iter(&["test", "--output-file", "report.csv.gz"]);
+        assert!(output.is_gzip());
+        let output = OutputFile::from_iter(&["test", "--output-file", "report.csv"]);
+        assert!(!output.is_gzip());
+        let output = OutputFile::from_iter(&["test", "--output-file", "report.csv", "--gzip"]);
+        assert!(output.is_gzip());
+        let output = OutputFile::from_iter(&["test"]);
+        assert!(!output.is_gzip());
+    }
+
+    #[test]
+    fn output_file_finish() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let path = env::temp_dir().join(format!(
+            "helium-wallet-output-{}.json.gz",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let file = path.to_str().expect("path");
+        let output = OutputFile::from_iter(&["test", "--output-file", file]);
+        let mut writer = output.writer().expect("writer");
+        writer.write_all(b"report").expect("write");
+        writer.finish().expect("finish");
+
+        let mut decoded = String::new();
+        GzDecoder::new(fs::File::open(&path).expect("open"))
+            .read_to_string(&mut decoded)
+            .expect("decode");
+        assert_eq!("report", decoded);
+
+        // An existing file is only overwritten with --force
+        assert!(output.writer().is_err());
+        let output = OutputFile::from_iter(&["test", "--output-file", file, "--force"]);
+        output.writer().expect("writer").finish().expect("finish");
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn concurrent_map_bounded() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let items: Vec<u64> = (0..200).collect();
+        let results = {
+            let active = active.clone();
+            let peak = peak.clone();
+            map_concurrent(items, 4, move |item| {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(1));
+                active.fetch_sub(1, Ordering::SeqCst);
+                if item % 10 == 0 {
+                    Err(item)
+                } else {
+                    Ok(item * 2)
+                }
+            })
+            .expect("map")
+        };
+        assert!(peak.load(Ordering::SeqCst) <= 4);
+        assert_eq!(200, results.len());
+        for (item, result) in results.into_iter().enumerate() {
+            let item = item as u64;
+            if item % 10 == 0 {
+                assert_eq!(Err(item), result);
+            } else {
+                assert_eq!(Ok(item * 2), result);
+            }
+        }
+        assert!(map_concurrent(Vec::<u64>::new(), 0, |item| item)
+            .expect("map")
+            .is_empty());
+    }
+
+    #[test]
+    fn concurrent_map_worker_panic() {
+        let result = map_concurrent(vec![1u64, 2, 3], 2, |item| {
+            if item == 2 {
+                panic!("worker panic");
+            }
+            item
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn dry_run_never_submits() {
+        use crate::traits::TxnEnvelope;
+        use helium_api::{BlockchainTxnPaymentV2, Payment};
+        use std::cell::Cell;
+
+        #[derive(Default)]
+        struct Submitter {
+            calls: Cell<usize>,
+        }
+
+        impl SubmitTxn for Submitter {
+            fn submit(&self, _envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+                self.calls.set(self.calls.get() + 1);
+                Ok(PendingTxnStatus {
+                    hash: "hash".to_string(),
+                })
+            }
+
+            fn account_nonce(&self, _address: &PublicKey) -> Result<u64> {
+                Ok(0)
+            }
+        }
+
+        let submitter = Submitter::default();
+        let dry_run = SubmitOpts {
+            dry_run: true,
+            ..Default::default()
+        };
+        let live = SubmitOpts::default();
+        let mut log = SubmitLog::default();
+        let envelope = BlockchainTxn::default();
+        assert!(
+            submit_txn_logged(&submitter, &mut log, &envelope, true, &dry_run)
+                .expect("dry run")
+                .is_none()
+        );
+        assert!(
+            submit_txn_logged(&submitter, &mut log, &envelope, false, &dry_run)
+                .expect("dry run")
+                .is_none()
+        );
+        assert!(
+            submit_txn_logged(&submitter, &mut log, &envelope, false, &live)
+                .expect("preview")
+                .is_none()
+        );
+        assert_eq!(0, submitter.calls.get());
+
+        let status =
+            submit_txn_logged(&submitter, &mut log, &envelope, true, &live).expect("commit");
+        assert_eq!("hash", status.expect("status").hash);
+        assert_eq!(1, submitter.calls.get());
+
+        // Submitting the same transaction again is refused
+        assert!(submit_txn_logged(&submitter, &mut log, &envelope, true, &live).is_err());
+        assert_eq!(1, submitter.calls.get());
+
+        // Another transaction at a nonce that was submitted before is
+        // refused before signing, not when submitting
+        let payer = Keypair::default();
+        let payment = |amount| {
+            BlockchainTxnPaymentV2 {
+                payer: payer.public_key().to_vec(),
+                payments: vec![Payment {
+                    payee: payer.public_key().to_vec(),
+                    amount,
+                }],
+                nonce: 1,
+                ..Default::default()
+            }
+            .in_envelope()
+        };
+        submit_txn_logged(&submitter, &mut log, &payment(1), true, &live).expect("commit");
+        assert!(log.check_nonce(payer.public_key(), 1).is_err());
+        submit_txn_logged(&submitter, &mut log, &payment(2), true, &live).expect("same nonce");
+        assert_eq!(3, submitter.calls.get());
+        // The submit log is only read when committing without --force-nonce
+        check_logged_nonce(payer.public_key(), 1, false, &live).expect("not committed");
+        check_logged_nonce(payer.public_key(), 1, true, &dry_run).expect("dry run");
+        let forced = SubmitOpts {
+            force_nonce: true,
+            ..live
+        };
+        check_logged_nonce(payer.public_key(), 1, true, &forced).expect("forced");
+
+        let opts = Opts::from_iter(&["helium-wallet", "--dry-run"]);
+        assert!(opts.submit.dry_run);
+    }
+
+    #[test]
+    fn submitted_despite_bookkeeping_errors() {
+        use crate::traits::TxnEnvelope;
+        use helium_api::BlockchainTxnPaymentV2;
+
+        struct Client;
+
+        impl SubmitTxn for Client {
+            fn submit(&self, _envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+                Ok(PendingTxnStatus {
+                    hash: "hash".to_string(),
+                })
+            }
+
+            fn account_nonce(&self, _address: &PublicKey) -> Result<u64> {
+                Ok(0)
+            }
+        }
+
+        // The audit log and receipt can not be written below a missing
+        // directory
+        let missing = env::temp_dir().join(format!("helium-wallet-missing-{}", std::process::id()));
+        let opts = SubmitOpts {
+            audit_log: Some(missing.join("audit.log")),
+            receipt: Some(missing.join("receipt.json")),
+            ..Default::default()
+        };
+        let payer = Keypair::default();
+        let payment = |nonce| {
+            BlockchainTxnPaymentV2 {
+                payer: payer.public_key().to_vec(),
+                nonce,
+                ..Default::default()
+            }
+            .in_envelope()
+        };
+        let mut log = SubmitLog::default();
+        let status = submit_txn_logged(&Client, &mut log, &payment(1), true, &opts)
+            .expect("submitted")
+            .expect("status");
+        assert_eq!("hash", status.hash);
+
+        let results =
+            submit_batch_logged(&Client, &mut log, &[payment(2), payment(3)], true, &opts)
+                .expect("batch");
+        assert!(results.iter().all(|result| result.status() == "submitted"));
+        check_batch(&results).expect("no failures");
+    }
+
+    #[test]
+    fn batch_receipts() {
+        assert_eq!(
+            Path::new("out/receipt-42.json"),
+            batch_receipt_path(Path::new("out/receipt.json"), 42)
+        );
+        assert_eq!(
+            Path::new("receipt-7"),
+            batch_receipt_path(Path::new("receipt"), 7)
+        );
+
+        let dir = env::temp_dir().join(format!("helium-wallet-receipts-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("receipt dir");
+        let opts = SubmitOpts {
+            receipt: Some(dir.join("receipt.json")),
+            ..Default::default()
+        };
+        let payer = Keypair::default();
+        let payment = |nonce| {
+            use crate::traits::TxnEnvelope;
+            helium_api::BlockchainTxnPaymentV2 {
+                payer: payer.public_key().to_vec(),
+                nonce,
+                ..Default::default()
+            }
+            .in_envelope()
+        };
+
+        struct Client;
+
+        impl SubmitTxn for Client {
+            fn submit(&self, envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+                Ok(PendingTxnStatus {
+                    hash: format!("{:?}", txn_nonce(envelope)?.map(|(_, nonce)| nonce)),
+                })
+            }
+
+            fn account_nonce(&self, _address: &PublicKey) -> Result<u64> {
+                Ok(0)
+            }
+        }
+
+        let mut log = SubmitLog::default();
+        let envelopes = [payment(1), payment(2)];
+        submit_batch_logged(&Client, &mut log, &envelopes, true, &opts).expect("batch");
+        assert!(!dir.join("receipt.json").exists());
+        for nonce in &[1, 2] {
+            let path = dir.join(format!("receipt-{}.json", nonce));
+            let receipt = fs::read_to_string(&path).expect("receipt");
+            assert!(receipt.contains(&format!("Some({})", nonce)));
+        }
+        fs::remove_dir_all(&dir).expect("remove receipts");
+    }
+
+    #[test]
+    fn confirmations() {
+        assert_eq!(1, confirmation_depth(100, 100));
+        assert_eq!(6, confirmation_depth(105, 100));
+        // The API may report a height that lags the transaction's block
+        assert_eq!(0, confirmation_depth(99, 100));
+    }
+
+    #[test]
+    fn insufficient_balance() {
+        assert!(verify_balance(BalanceType::Hnt, 100, 100).is_ok());
+        assert!(verify_balance(BalanceType::Hst, 100, 0).is_ok());
+        let err = verify_balance(BalanceType::Hst, 0, 100).unwrap_err();
+        assert!(err.to_string().starts_with("Insufficient HST balance"));
+    }
+
+    #[test]
+    fn seed_file() {
+        assert_eq!(
+            vec!["abandon", "ability", "able"],
+            parse_seed_words("  Abandon ability\n\tABLE \r\n")
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("helium-wallet-seed-{}.txt", std::process::id()));
+        fs::write(&path, "abandon ability able\n").expect("write");
+        #[cfg(unix)]
+        let world_readable = {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).expect("chmod");
+            let result = read_seed_words(&path);
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).expect("chmod");
+            result
+        };
+        let words = read_seed_words(&path);
+        let _ = fs::remove_file(&path);
+        #[cfg(unix)]
+        assert!(world_readable.is_err());
+        assert_eq!(
+            vec!["abandon", "ability", "able"],
+            words.expect("seed words")
+        );
+    }
+
+    #[test]
+    fn password_confirmation() {
+        let scripted = |entries: &'static [&'static str]| {
+            let mut entries = entries.iter();
+            move |_: &str| Ok(entries.next().expect("prompt").to_string())
+        };
+        assert_eq!(
+            "secret",
+            confirm_password(3, scripted(&["secret", "secret"])).unwrap()
+        );
+        // A mismatch prompts again
+        assert_eq!(
+            "secret",
+            confirm_password(3, scripted(&["secret", "typo", "secret", "secret"])).unwrap()
+        );
+        assert!(confirm_password(2, scripted(&["a", "b", "c", "d"])).is_err());
+    }
+
+    #[test]
+    fn txn_encodings() {
+        let envelope = BlockchainTxn::default();
+        for encoding in &[Encoding::Base64, Encoding::Hex] {
+            let encoded = encoding.encode(&envelope).expect("encode");
+            assert_eq!(envelope, encoding.decode(&encoded).expect("decode"));
+        }
+        let opts = Opts::from_iter(&["helium-wallet"]);
+        assert!(matches!(opts.encoding, Encoding::Base64));
+        let opts = Opts::from_iter(&["helium-wallet", "--encoding", "hex"]);
+        assert!(matches!(opts.encoding, Encoding::Hex));
+    }
+
+    #[test]
+    fn stale_nonce() {
+        use crate::traits::TxnEnvelope;
+        use helium_api::BlockchainTxnPaymentV2;
+
+        struct Nonce(u64);
+
+        impl SubmitTxn for Nonce {
+            fn submit(&self, _envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+                panic!("stale transaction submitted")
+            }
+
+            fn account_nonce(&self, _address: &PublicKey) -> Result<u64> {
+                Ok(self.0)
+            }
+        }
+
+        let payment = |nonce| {
+            BlockchainTxnPaymentV2 {
+                payer: Keypair::default().public_key().to_vec(),
+                nonce,
+                ..Default::default()
+            }
+            .in_envelope()
+        };
+        let client = Nonce(5);
+        let err = check_stale_nonce(&client, &payment(3)).unwrap_err();
+        assert_eq!(
+            "Transaction nonce 3 is below current account nonce 5",
+            err.to_string()
+        );
+        assert!(check_stale_nonce(&client, &payment(5)).is_err());
+        assert!(check_stale_nonce(&client, &payment(6)).is_ok());
+        assert!(check_stale_nonce(&client, &payment(8)).is_ok());
+        // Transactions without an account nonce are not checked
+        assert!(check_stale_nonce(&client, &BlockchainTxn::default()).is_ok());
+
+        let mut log = SubmitLog::default();
+        let opts = SubmitOpts::default();
+        assert!(submit_txn_logged(&client, &mut log, &payment(5), true, &opts).is_err());
+    }
+
+    #[test]
+    fn batch_stops_at_nonce_gap() {
+        use crate::traits::TxnEnvelope;
+        use helium_api::BlockchainTxnPaymentV2;
+        use std::cell::RefCell;
+
+        /// Rejects the transaction with the given nonce and records the
+        /// nonces of all submitted transactions
+        struct RejectNonce {
+            reject: u64,
+            submitted: RefCell<Vec<u64>>,
+        }
+
+        impl SubmitTxn for RejectNonce {
+            fn submit(&self, envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+                let (_, nonce) = txn_nonce(envelope)?.expect("nonce");
+                self.submitted.borrow_mut().push(nonce);
+                if nonce == self.reject {
+                    bail!("invalid transaction");
+                }
+                Ok(PendingTxnStatus {
+                    hash: format!("hash{}", nonce),
+                })
+            }
+
+            fn account_nonce(&self, _address: &PublicKey) -> Result<u64> {
+                Ok(5)
+            }
+        }
+
+        let payer = Keypair::default().public_key().clone();
+        let other = Keypair::default().public_key().clone();
+        let payment = |payer: &PublicKey, nonce| {
+            BlockchainTxnPaymentV2 {
+                payer: payer.to_vec(),
+                nonce,
+                ..Default::default()
+            }
+            .in_envelope()
+        };
+        let mut envelopes: Vec<_> = (6..=10).map(|nonce| payment(&payer, nonce)).collect();
+        // Another payer does not depend on the failed nonce
+        envelopes.push(payment(&other, 12));
+        let client = RejectNonce {
+            reject: 7,
+            submitted: RefCell::new(vec![]),
+        };
+        let opts = SubmitOpts::default();
+        let results =
+            submit_batch_logged(&client, &mut SubmitLog::default(), &envelopes, true, &opts)
+                .expect("batch results");
+
+        assert_eq!(vec![6, 7, 12], *client.submitted.borrow());
+        let statuses: Vec<_> = results.iter().map(|result| result.status()).collect();
+        assert_eq!(
+            vec![
+                "submitted",
+                "failed",
+                "not submitted",
+                "not submitted",
+                "not submitted",
+                "submitted"
+            ],
+            statuses
+        );
+        assert_eq!(Some("hash6"), results[0].hash());
+        let err = check_batch(&results).unwrap_err();
+        assert_eq!(
+            "Submission stopped, nonce 7 failed: invalid transaction; later nonce 8, nonce 9, nonce 10 NOT submitted",
+            err.to_string()
+        );
+        assert!(check_batch(&results[..1]).is_ok());
+    }
+
+    #[test]
+    fn timeout_options() {
+        let opts = Opts::from_iter(&["helium-wallet"]);
+        assert_eq!(None, opts.timeouts.connect_timeout);
+        assert_eq!(120, opts.timeouts.request_timeout);
+        assert_eq!(Duration::from_secs(600), opts.timeouts.wait());
+
+        let opts = Opts::from_iter(&[
+            "helium-wallet",
+            "--connect-timeout",
+            "5",
+            "--request-timeout",
+            "30",
+            "--wait-timeout",
+            "1800",
+        ]);
+        assert_eq!(Some(5), opts.timeouts.connect_timeout);
+        assert_eq!(30, opts.timeouts.request_timeout);
+        assert_eq!(Duration::from_secs(1800), opts.timeouts.wait());
+    }
+
+    #[test]
+    fn tls_options() {
+        let opts = Opts::from_iter(&["helium-wallet"]);
+        let tls = opts.tls().expect("tls");
+        assert!(!tls.allow_insecure);
+        assert!(tls.ca_cert.is_none());
+        let opts = Opts::from_iter(&["helium-wallet", "--allow-insecure-tls"]);
+        assert!(opts.tls().expect("tls").allow_insecure);
+
+        // A CA certificate that can not be read fails before any request
+        let path = env::temp_dir().join(format!("helium-wallet-ca-{}.pem", std::process::id()));
+        fs::write(&path, "-----BEGIN CERTIFICATE-----\nnot a certificate\n").expect("ca");
+        let invalid =
+            Opts::from_iter(&["helium-wallet", "--ca-cert", path.to_str().unwrap()]).tls();
+        let _ = fs::remove_file(&path);
+        assert!(invalid.is_err());
+        let missing =
+            Opts::from_iter(&["helium-wallet", "--ca-cert", path.to_str().unwrap()]).tls();
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn key_file_source() {
+        let keypair = Keypair::default();
+        let watch = Wallet::watch(keypair.public_key().clone());
+        testing::use_wallet(Path::new("key-file.key"), &Wallet::in_memory(&keypair));
+        testing::use_wallet(
+            Path::new("other.key"),
+            &Wallet::in_memory(&Keypair::default()),
+        );
+
+        assert!(KeySource::Wallet.decrypt(&watch).is_err());
+        let key_file = KeySource::new(Some(Path::new("key-file.key")));
+        assert_eq!(keypair, key_file.decrypt(&watch).expect("key file"));
+        let err = KeySource::new(Some(Path::new("other.key")))
+            .decrypt(&watch)
+            .expect_err("mismatch");
+        assert!(err.to_string().contains("not for the wallet address"));
+    }
+
+    #[test]
+    fn nonce_changed() {
+        assert!(verify_nonce(5, 5).is_ok());
+        assert!(verify_nonce(5, 6).is_err());
+    }
+
+    #[test]
+    fn format_tty_default() {
+        assert_eq!("table", format_for_tty(true));
+        assert_eq!("json", format_for_tty(false));
+
+        // An explicit format always wins over the tty based default
+        let opts = Opts::from_iter(&["helium-wallet", "--format", "table"]);
+        assert!(matches!(opts.format, OutputFormat::Table));
+        let opts = Opts::from_iter(&["helium-wallet", "--format", "json"]);
+        assert!(matches!(opts.format, OutputFormat::Json));
+    }
+
+    #[test]
+    fn color_preference() {
+        assert!(use_color(false, false, true));
+        assert!(!use_color(true, false, true));
+        assert!(!use_color(false, true, true));
+        assert!(!use_color(false, false, false));
+
+        let opts = Opts::from_iter(&["helium-wallet", "--no-color"]);
+        assert!(opts.no_color);
+        assert!(!opts.color());
+    }
+
+    /// Returns the error of a request to a local server that answers with
+    /// the given status line
+    fn http_status_error(status_line: &str) -> Error {
+        use std::{io::Read, net::TcpListener};
+        let listener = TcpListener::bind("127.0.0.1:0").expect("listener");
+        let url = format!(
+            "http://{}/pending_transactions",
+            listener.local_addr().expect("address")
+        );
+        let response = format!(
+            "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            status_line
+        );
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("connection");
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            stream.write_all(response.as_bytes()).expect("response");
+        });
+        let err = reqwest::blocking::Client::new()
+            .post(&url)
+            .body("{}")
+            .send()
+            .and_then(|response| response.error_for_status())
+            .expect_err("status error");
+        server.join().expect("server");
+        err.into()
+    }
+
+    #[test]
+    fn account_not_found() {
+        use std::{io::Read, net::TcpListener};
+        let listener = TcpListener::bind("127.0.0.1:0").expect("listener");
+        let url = format!("http://{}", listener.local_addr().expect("address"));
+        let server = thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.expect("connection");
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request);
+                stream
+                    .write_all(
+                        b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    )
+                    .expect("response");
+            }
+        });
+        let client = Client::new_with_timeout(url, 10).expect("client");
+        let address = Keypair::default().public_key().clone();
+        assert!(fetch_account(&client, &address)
+            .expect("not found")
+            .is_none());
+        let err = fetch_payer_account(&client, &address).expect_err("no balance");
+        assert!(err.to_string().starts_with("Cannot spend from account"));
+        server.join().expect("server");
+
+        assert!(is_not_found(&http_status_error("404 Not Found")));
+        assert!(!is_not_found(&http_status_error("503 Service Unavailable")));
+    }
+
+    #[test]
+    fn classify_submit_errors() {
+        use crate::result::ErrorKind;
+
+        // Nothing listens on a port after its listener is dropped
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("address")
+            .port();
+        let refused: Error = reqwest::blocking::get(&format!("http://127.0.0.1:{}", port))
+            .expect_err("connection error")
+            .into();
+        assert!(SubmitError::classify(refused).is_transient());
+        assert!(SubmitError::classify(http_status_error("503 Service Unavailable")).is_transient());
+
+        let rejected = SubmitError::classify(http_status_error("400 Bad Request"));
+        assert!(matches!(rejected, SubmitError::Rejected(_)));
+        // The http error remains in the chain of causes
+        let rejected: Error = rejected.into();
+        assert_eq!(ErrorKind::Api, ErrorKind::of(&rejected));
+        assert!(!SubmitError::classify(anyhow!("invalid transaction")).is_transient());
+    }
+
+    #[test]
+    fn submit_error_variants() {
+        use crate::traits::TxnEnvelope;
+        use helium_api::BlockchainTxnPaymentV2;
+
+        struct Failing;
+
+        impl SubmitTxn for Failing {
+            fn submit(&self, _envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+                Err(SubmitError::classify(http_status_error("400 Bad Request")).into())
+            }
+
+            fn account_nonce(&self, _address: &PublicKey) -> Result<u64> {
+                Ok(0)
+            }
+        }
+
+        let opts = SubmitOpts::default();
+        let envelope = BlockchainTxnPaymentV2 {
+            payer: Keypair::default().public_key().to_vec(),
+            nonce: 1,
+            ..Default::default()
+        }
+        .in_envelope();
+        let err = submit_txn_logged(&Failing, &mut SubmitLog::default(), &envelope, true, &opts)
+            .expect_err("rejected");
+        assert!(matches!(
+            err.downcast_ref::<SubmitError>(),
+            Some(SubmitError::Rejected(_))
+        ));
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     result::{bail, Result},
     traits::{ToJson, TxnSign, B64},
 };
-use helium_api::{BlockchainTxn, Client, PendingTxnStatus, Txn};
+use helium_api::{BlockchainTxn, PendingTxnStatus, Txn};
 use serde::{Deserialize, Serialize};
 use std::{
     fs::File,
@@ -87,15 +87,14 @@ struct Proofs {
 impl Inspect {
     pub fn run(&self, _opts: Opts) -> Result {
         let txn = Artifact::load_txn(&self.artifact)?;
-        print_txn(&txn, &None)
+        print_txn(&txn, None, &None)
     }
 }
 
 impl Prove {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
 
         let txn = Artifact::load_txn(&self.artifact)?;
         let mut proofs = Proofs::new();
@@ -110,7 +109,7 @@ impl Prove {
 }
 
 impl Combine {
-    pub fn run(&self, _opts: Opts) -> Result {
+    pub fn run(&self, opts: Opts) -> Result {
         let mut envelope = Artifact::load_txn(&self.artifact)?;
         // Load proofs and key_proof maps from txn
         let mut combined_proofs = Proofs::from_txn(&envelope)?;
@@ -119,21 +118,25 @@ impl Combine {
             combined_proofs.merge_proofs(&proofs);
         }
         combined_proofs.apply(&mut envelope)?;
-        let status = if self.commit {
-            let client = Client::new_with_base_url(api_url(self.network));
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-        print_txn(&envelope, &status)
+        check_logged_txn_nonce(&envelope, self.commit, &opts.submit)?;
+        let client = opts.timeouts.client(api_url(&opts.api_url, self.network))?;
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(&envelope, Some(self.network), &status)
     }
 }
 
-fn print_txn(envelope: &BlockchainTxn, status: &Option<PendingTxnStatus>) -> Result {
+fn print_txn(
+    envelope: &BlockchainTxn,
+    network: Option<Network>,
+    status: &Option<PendingTxnStatus>,
+) -> Result {
     let mut json = match &envelope.txn {
         Some(Txn::Vars(t)) => t.to_json()?,
         _ => bail!("Unsupported transaction for multisig"),
     };
+    if let Some(network) = network {
+        json["network"] = network.to_string().into();
+    }
     json["hash"] = status_json(status);
     json["txn"] = envelope.to_b64()?.into();
     print_json(&json)
@@ -0,0 +1,82 @@
+use crate::{cmd::config_dir, keypair::PublicKey, result::Result};
+use fs2::FileExt;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// An exclusive advisory lock on a per-wallet lock file in the user
+/// configuration directory. Commands hold it from reading the account
+/// nonce until the transaction is submitted, so concurrent commands for the
+/// same wallet take turns and each builds its transaction on the nonce the
+/// previous one used. The lock is released when dropped, and by the
+/// operating system when the process exits or crashes.
+pub struct NonceLock {
+    _file: fs::File,
+}
+
+impl NonceLock {
+    /// Acquires the lock for the given address, waiting for another command
+    /// holding it to finish. Returns None if there is no configuration
+    /// directory to keep the lock file in.
+    pub fn acquire(address: &PublicKey) -> Result<Option<Self>> {
+        let path = match config_dir() {
+            Some(dir) => lock_path(&dir, address),
+            None => return Ok(None),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if let Some(lock) = Self::try_acquire_at(&path)? {
+            return Ok(Some(lock));
+        }
+        eprintln!("Waiting for another command using this wallet to finish");
+        let file = open_lock_file(&path)?;
+        file.lock_exclusive()?;
+        Ok(Some(Self { _file: file }))
+    }
+
+    /// Acquires the lock at the given path without waiting. Returns None if
+    /// the lock is held elsewhere.
+    fn try_acquire_at(path: &Path) -> Result<Option<Self>> {
+        let file = open_lock_file(path)?;
+        match file.try_lock_exclusive() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(err) if err.kind() == fs2::lock_contended_error().kind() => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+fn open_lock_file(path: &Path) -> io::Result<fs::File> {
+    fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(path)
+}
+
+fn lock_path(config_dir: &Path, address: &PublicKey) -> PathBuf {
+    config_dir.join("locks").join(format!("{}.lock", address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+    use std::env;
+
+    #[test]
+    fn exclusive() {
+        let address = Keypair::default().public_key().clone();
+        let path = lock_path(&env::temp_dir(), &address);
+        fs::create_dir_all(path.parent().unwrap()).expect("lock dir");
+        let lock = NonceLock::try_acquire_at(&path)
+            .expect("lock")
+            .expect("acquired");
+        assert!(NonceLock::try_acquire_at(&path).expect("lock").is_none());
+        drop(lock);
+        assert!(NonceLock::try_acquire_at(&path).expect("lock").is_some());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,21 +1,50 @@
 use crate::{
-    cmd::*,
+    api::{Client, OraclePrediction, OraclePrice},
+    cmd::{history::format_time, *},
     result::{anyhow, Result},
-    traits::{TxnEnvelope, TxnSign, B64},
+    traits::{TxnEnvelope, TxnSign},
 };
-use helium_api::{BlockchainTxn, BlockchainTxnPriceOracleV1, Client, PendingTxnStatus};
+use helium_api::{BlockchainTxn, BlockchainTxnPriceOracleV1, PendingTxnStatus};
+use prettytable::{format, Table};
 use rust_decimal::{prelude::*, Decimal};
 use serde::Serialize;
 use serde_json::json;
-use std::str::FromStr;
+use std::{
+    io,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use structopt::StructOpt;
 
-/// Report an oracle price to the blockchain
+/// Show or report oracle prices
 #[derive(Debug, StructOpt)]
 pub enum Cmd {
+    Price(ShowPrice),
     Report(Report),
 }
 
+#[derive(Debug, StructOpt)]
+/// Show the current oracle price of HNT, and optionally the predicted
+/// prices that take effect in the near future.
+pub struct ShowPrice {
+    /// Also show the predicted oracle prices
+    #[structopt(long)]
+    predicted: bool,
+
+    /// Only show predicted prices that take effect within the given number
+    /// of minutes
+    #[structopt(long, requires = "predicted", default_value = "60")]
+    window: u64,
+
+    /// Output the prices as CSV instead of the selected output format
+    #[structopt(long)]
+    csv: bool,
+
+    /// The network to get the oracle prices for (mainnet/testnet)
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+}
+
 #[derive(Debug, StructOpt)]
 /// Construct an oracle price report and optionally commit it to the
 /// Helium Blockchain.
@@ -38,18 +67,118 @@ pub struct Report {
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
         match self {
+            Cmd::Price(cmd) => cmd.run(opts),
             Cmd::Report(cmd) => cmd.run(opts),
         }
     }
 }
 
+impl ShowPrice {
+    pub fn run(&self, opts: Opts) -> Result {
+        let client = opts.timeouts.client(api_url(&opts.api_url, self.network))?;
+        let current = client.get_oracle_price()?;
+        let predictions = if self.predicted {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            predictions_within(client.get_oracle_predictions()?, now, self.window * 60)
+        } else {
+            vec![]
+        };
+        if self.csv {
+            return write_prices_csv(&mut io::stdout(), &current, &predictions);
+        }
+        print_prices(&current, &predictions, opts.format)
+    }
+}
+
+/// Returns the predictions that take effect between now and the end of
+/// the given window in seconds, earliest first
+fn predictions_within(
+    mut predictions: Vec<OraclePrediction>,
+    now: u64,
+    window: u64,
+) -> Vec<OraclePrediction> {
+    predictions.retain(|p| p.time >= now && p.time - now <= window);
+    predictions.sort_by_key(|p| p.time);
+    predictions
+}
+
+fn print_prices(
+    current: &OraclePrice,
+    predictions: &[OraclePrediction],
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Price", "Block", "Time"]);
+            table.add_row(row![
+                Price::from_millis(current.price).to_string(),
+                current.block,
+                current.timestamp.as_deref().unwrap_or("")
+            ]);
+            for prediction in predictions {
+                table.add_row(row![
+                    Price::from_millis(prediction.price).to_string(),
+                    "predicted",
+                    format_time(prediction.time)
+                ]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "price": Price::from_millis(current.price).to_string(),
+                "block": current.block,
+                "timestamp": current.timestamp,
+                "predictions": predictions
+                    .iter()
+                    .map(|p| json!({
+                        "price": Price::from_millis(p.price).to_string(),
+                        "time": p.time,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            print_json(&table)
+        }
+    }
+}
+
+fn write_prices_csv(
+    writer: &mut dyn io::Write,
+    current: &OraclePrice,
+    predictions: &[OraclePrediction],
+) -> Result {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(&["price", "block", "time", "predicted"])?;
+    writer.write_record(&[
+        Price::from_millis(current.price).to_string(),
+        current.block.to_string(),
+        current.timestamp.clone().unwrap_or_default(),
+        "false".to_string(),
+    ])?;
+    for prediction in predictions {
+        writer.write_record(&[
+            Price::from_millis(prediction.price).to_string(),
+            String::new(),
+            format_time(prediction.time),
+            "true".to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
 impl Report {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
         let mut txn = BlockchainTxnPriceOracleV1 {
             public_key: keypair.public_key().into(),
@@ -59,36 +188,43 @@ impl Report {
         };
         txn.signature = txn.sign(&keypair)?;
         let envelope = txn.in_envelope();
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
 
-        print_txn(&txn, &envelope, &status, opts.format)
+        print_txn(
+            &txn,
+            &envelope,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 fn print_txn(
     txn: &BlockchainTxnPriceOracleV1,
     envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
-    let encoded = envelope.to_b64()?;
+    let encoded = encoding.encode(envelope)?;
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Block Height", txn.block_height],
                 ["Price", Price::from_millis(txn.price)],
                 ["Hash", status_str(status)]
-            );
+            ))?;
 
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "price": txn.price,
                 "block_height": txn.block_height,
                 "txn": encoded,
@@ -218,3 +354,47 @@ impl ToString for Price {
         self.0.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prediction_window() {
+        let prediction = |price, time| OraclePrediction { price, time };
+        let predictions = vec![
+            prediction(3, 1_000 + 3_600),
+            prediction(1, 1_000 + 60),
+            prediction(0, 999),
+            prediction(4, 1_000 + 3_601),
+            prediction(2, 1_000),
+        ];
+        let within = predictions_within(predictions, 1_000, 3_600);
+        assert_eq!(
+            vec![1_000, 1_060, 4_600],
+            within.iter().map(|p| p.time).collect::<Vec<_>>()
+        );
+        assert!(predictions_within(within, 5_000, 3_600).is_empty());
+    }
+
+    #[test]
+    fn prices_csv() {
+        let current = OraclePrice {
+            price: 150_000_000,
+            block: 100,
+            timestamp: Some("2021-05-01T00:00:00Z".to_string()),
+        };
+        let predictions = vec![OraclePrediction {
+            price: 160_000_000,
+            time: 0,
+        }];
+        let mut buffer = vec![];
+        write_prices_csv(&mut buffer, &current, &predictions).expect("csv");
+        assert_eq!(
+            "price,block,time,predicted\n\
+             1.50000000,100,2021-05-01T00:00:00Z,false\n\
+             1.60000000,,1970-01-01 00:00:00,true\n",
+            String::from_utf8(buffer).expect("utf8")
+        );
+    }
+}
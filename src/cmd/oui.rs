@@ -1,13 +1,13 @@
 use crate::{
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_footer, print_json, status_json,
-        status_str, Opts, OutputFormat,
+        api_url, decrypt_wallet, get_txn_fees, load_wallet, print_footer, print_json, print_table,
+        status_json, status_str, submit_txn, Encoding, Opts, OutputFormat,
     },
-    keypair::PublicKey,
+    keypair::{Network, PublicKey},
     result::{anyhow, Result},
-    traits::{TxnEnvelope, TxnFee, TxnSign, TxnStakingFee, B64},
+    traits::{TxnEnvelope, TxnFee, TxnSign, TxnStakingFee},
 };
-use helium_api::{BlockchainTxn, BlockchainTxnOuiV1, Client, PendingTxnStatus, Txn};
+use helium_api::{BlockchainTxn, BlockchainTxnOuiV1, PendingTxnStatus, Txn};
 use serde_json::json;
 use std::convert::TryInto;
 use structopt::StructOpt;
@@ -55,7 +55,7 @@ pub struct Create {
 /// transaction.
 #[derive(Debug, StructOpt)]
 pub struct Submit {
-    /// Base64 encoded transaction to submit.
+    /// Encoded transaction to submit.
     #[structopt(name = "TRANSACTION")]
     transaction: String,
 
@@ -77,12 +77,13 @@ impl Cmd {
 
 impl Create {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
         let wallet_key = keypair.public_key();
 
-        let api_client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let api_client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
         let mut txn = BlockchainTxnOuiV1 {
             addresses: map_addresses(self.addresses.clone(), |v| v.to_vec())?,
@@ -96,25 +97,35 @@ impl Create {
             requested_subnet_size: self.subnet_size,
             filter: base64::decode(&self.filter)?,
         };
-        txn.fee = txn.txn_fee(&get_txn_fees(&api_client)?)?;
-        txn.staking_fee = txn.txn_staking_fee(&get_txn_fees(&api_client)?)?;
+        txn.fee = txn.txn_fee(&get_txn_fees(&api_client, &opts.submit)?)?;
+        txn.staking_fee = txn.txn_staking_fee(&get_txn_fees(&api_client, &opts.submit)?)?;
         txn.owner_signature = txn.sign(&keypair)?;
         let envelope = txn.in_envelope();
 
         match self.payer.as_ref() {
             key if key == Some(&wallet_key) || key.is_none() => {
                 // Payer is the wallet submit if ready to commit
-                let status = if self.commit {
-                    Some(api_client.submit_txn(&envelope)?)
-                } else {
-                    None
-                };
-                print_txn(&txn, &envelope, &status, opts.format)
+                let status = submit_txn(&api_client, &envelope, self.commit, &opts.submit)?;
+                print_txn(
+                    &txn,
+                    &envelope,
+                    wallet.public_key.network,
+                    &status,
+                    opts.encoding,
+                    opts.format,
+                )
             }
             _ => {
                 // Payer is something else.
                 // can't commit this transaction but we can display it
-                print_txn(&txn, &envelope, &None, opts.format)
+                print_txn(
+                    &txn,
+                    &envelope,
+                    wallet.public_key.network,
+                    &None,
+                    opts.encoding,
+                    opts.format,
+                )
             }
         }
     }
@@ -122,16 +133,13 @@ impl Create {
 
 impl Submit {
     pub fn run(&self, opts: Opts) -> Result {
-        let envelope = BlockchainTxn::from_b64(&self.transaction)?;
+        let envelope = opts.encoding.decode(&self.transaction)?;
         if let Some(Txn::Oui(t)) = envelope.txn.clone() {
-            let api_url = api_url(PublicKey::from_bytes(&t.owner)?.network);
-            let api_client = helium_api::Client::new_with_base_url(api_url);
-            let status = if self.commit {
-                Some(api_client.submit_txn(&envelope)?)
-            } else {
-                None
-            };
-            print_txn(&t, &envelope, &status, opts.format)
+            let network = PublicKey::from_bytes(&t.owner)?.network;
+            let api_url = api_url(&opts.api_url, network);
+            let api_client = opts.timeouts.client(api_url)?;
+            let status = submit_txn(&api_client, &envelope, self.commit, &opts.submit)?;
+            print_txn(&t, &envelope, network, &status, opts.encoding, opts.format)
         } else {
             Err(anyhow!("Invalid OUI transaction"))
         }
@@ -141,13 +149,16 @@ impl Submit {
 fn print_txn(
     txn: &BlockchainTxnOuiV1,
     envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Requested OUI", txn.oui + 1],
                 ["Reqeuested Subnet Size", txn.requested_subnet_size],
                 [
@@ -155,17 +166,18 @@ fn print_txn(
                     map_addresses(txn.addresses.clone(), |v| v.to_string())?.join("\n")
                 ],
                 ["Hash", status_str(status)]
-            );
+            ))?;
 
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "requested_oui": txn.oui + 1,
                 "addresses": map_addresses(txn.addresses.clone(), |v| v.to_string())?,
                 "requested_subnet_size": txn.requested_subnet_size,
                 "hash": status_json(status),
-                "txn": envelope.to_b64()?,
+                "txn": encoding.encode(envelope)?,
             });
 
             print_json(&table)
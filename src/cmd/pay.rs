@@ -1,16 +1,33 @@
 use crate::{
+    api::{Client, OraclePrice},
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_footer, print_json, print_table,
-        status_json, status_str, Opts, OutputFormat,
+        api_url, check_balance, check_batch, check_logged_nonce, fetch_payer_account,
+        fetch_txn_fees, load_wallet,
+        nonce_lock::NonceLock,
+        override_txn_fees,
+        payee::{
+            allowlist_path, check_allowlist, check_zero_amounts, dc_to_hnt_bones, dc_to_usd,
+            format_usd, percent_of_bones, read_allowlist, read_payees_file, DecimalSeparator,
+            FeeAmount, Payee,
+        },
+        print_footer, print_json, print_table, status_json, status_str, submit_batch, submit_txn,
+        verify_nonce, wallet_signer, write_atomic, BatchResult, Encoding, Opts, OutputFormat,
+        SubmitTxn, Timeouts,
     },
-    keypair::PublicKey,
-    result::Result,
-    traits::{TxnEnvelope, TxnFee, TxnSign, B64},
+    keypair::{Network, PublicKey},
+    result::{anyhow, bail, Result},
+    traits::{BalanceType, TxnEnvelope, TxnFee, TxnFeeConfig, TxnSign},
+    txn::TxnBuilder,
+    wallet::Wallet,
+};
+use helium_api::{
+    Account, BlockchainTxn, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2, Hnt, Payment,
+    PendingTxnStatus,
 };
-use helium_api::{BlockchainTxn, BlockchainTxnPaymentV2, Client, Hnt, Payment, PendingTxnStatus};
 use prettytable::Table;
+use rust_decimal::Decimal;
 use serde_json::json;
-use std::str::FromStr;
+use std::{collections::HashMap, fmt, path::PathBuf};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -18,127 +35,1891 @@ use structopt::StructOpt;
 /// goes to 8 decimals of precision. The payment is not submitted to
 /// the system unless the '--commit' option is given.
 pub struct Cmd {
-    /// Address and amount of HNT to send in <address>=<amount> format.
-    #[structopt(long = "payee", short = "p", name = "payee=hnt", required = true)]
-    payees: Vec<Payee>,
+    /// Address and amount of HNT to send in <address>=<amount> format. Use
+    /// "sweep" as the amount to send the remaining balance after all other
+    /// payments and the fee to the address.
+    #[structopt(
+        long = "payee",
+        short = "p",
+        name = "payee=hnt",
+        required_unless = "payees-file"
+    )]
+    payees: Vec<String>,
+
+    /// Read payees from a CSV file with an address and an amount column,
+    /// in addition to any given --payee. Lines starting with '#' and a
+    /// header row starting with "address" are skipped.
+    #[structopt(long, name = "payees-file")]
+    payees_file: Option<PathBuf>,
+
+    /// Check all payees and the total against the balance of the payer and
+    /// report every problem found, without building or submitting the
+    /// payment. Fails if any problem was found.
+    #[structopt(long, conflicts_with = "commit")]
+    validate_only: bool,
+
+    /// Show the balances of the payer before and after the payment
+    /// without signing or submitting it. Fails if a balance would go
+    /// negative.
+    #[structopt(long, conflicts_with = "commit")]
+    estimate_only: bool,
+
+    /// The decimal separator used in payee amounts, "." or ",". With the
+    /// default "." an amount like "1,5" is accepted as well, but "1,500" is
+    /// rejected as ambiguous.
+    #[structopt(long, default_value = ".")]
+    decimal_separator: DecimalSeparator,
+
+    /// Allow payments with a zero amount
+    #[structopt(long)]
+    allow_zero: bool,
+
+    /// Allow payments with an amount below the HNT value of the fee at the
+    /// oracle price. Without the check the oracle price is only fetched
+    /// when the payment needs it otherwise.
+    #[structopt(long)]
+    allow_dust: bool,
+
+    /// Skip the check of payees against the payee allowlist in
+    /// ~/.config/helium-wallet/allowed_payees.txt
+    #[structopt(long)]
+    no_allowlist: bool,
+
+    /// Manually set fee to pay for the transaction. A plain integer is a
+    /// fee in DC, an amount with an "hnt" suffix like "0.01hnt" is
+    /// converted to DC at the current oracle price.
+    #[structopt(long)]
+    fee: Option<FeeAmount>,
+
+    /// Reject an oracle price set more than the given number of blocks ago.
+    /// The oracle price is fetched for the dust check, to convert USD and DC
+    /// amounts, an HNT fee and the fee when HNT is burned for it. By default
+    /// any oracle price is accepted.
+    #[structopt(long)]
+    max_oracle_age: Option<u64>,
+
+    /// Amount of HNT to leave behind in the account when sweeping
+    #[structopt(long)]
+    reserve: Option<Hnt>,
+
+    /// Reject the payment if any single payee would receive more than the
+    /// given amount of HNT, after percentages and sweeps are resolved
+    #[structopt(long)]
+    max_per_payee: Option<Hnt>,
+
+    /// Allow a manually set fee below the minimum fee for the transaction
+    #[structopt(long, requires = "fee")]
+    force: bool,
+
+    /// Fail instead of implicitly burning HNT for the fee when the DC
+    /// balance of the payer does not cover it
+    #[structopt(long, alias = "dc-only")]
+    no_hnt_burn: bool,
+
+    /// Build a version 1 payment transaction for compatibility with older
+    /// tooling. Version 1 payments support exactly one payee.
+    #[structopt(long)]
+    v1: bool,
+
+    /// Build the payment from the given payer account instead of this
+    /// wallet. The payment is emitted with an empty signature for the payer
+    /// to sign with the cosign command, and can not be committed.
+    #[structopt(long, conflicts_with = "commit")]
+    payer: Option<PublicKey>,
+
+    /// The most payees a single payment transaction may have. Defaults to
+    /// the max_payments chain variable and can not exceed it.
+    #[structopt(long)]
+    max_payees_per_txn: Option<u64>,
 
-    /// Manually set DC fee to pay for the transaction
+    /// Split more payees than --max-payees-per-txn, or the chain maximum,
+    /// into several payments at consecutive nonces. Each payment is
+    /// submitted on its own. Sweep and percentage amounts can not be split.
+    #[structopt(long, conflicts_with_all = &["v1", "payer", "estimate-only"])]
+    split: bool,
+
+    /// Write a plain language summary of the payment to the given file,
+    /// for review before the payment is signed or committed
     #[structopt(long)]
-    fee: Option<u64>,
+    summary: Option<PathBuf>,
 
     /// Commit the payment to the API
     #[structopt(long)]
     commit: bool,
+
+    /// Re-read the account nonce before committing and abort if it changed
+    /// since the transaction was built
+    #[structopt(long)]
+    check_nonce: bool,
+}
+
+/// The API calls made to build and submit a payment
+pub trait PayApi: SubmitTxn {
+    fn account(&self, address: &PublicKey) -> Result<Account>;
+    fn oracle_price(&self) -> Result<OraclePrice>;
+    fn height(&self) -> Result<u64>;
+    fn txn_fees(&self) -> Result<TxnFeeConfig>;
+    /// The max_payments chain variable, if it is set
+    fn max_payments(&self) -> Result<Option<u64>> {
+        Ok(None)
+    }
+}
+
+impl PayApi for Client {
+    fn account(&self, address: &PublicKey) -> Result<Account> {
+        fetch_payer_account(self, address)
+    }
+
+    fn oracle_price(&self) -> Result<OraclePrice> {
+        self.get_oracle_price()
+    }
+
+    fn height(&self) -> Result<u64> {
+        self.get_height()
+    }
+
+    fn txn_fees(&self) -> Result<TxnFeeConfig> {
+        fetch_txn_fees(self)
+    }
+
+    fn max_payments(&self) -> Result<Option<u64>> {
+        Ok(self
+            .get_vars()?
+            .get("max_payments")
+            .and_then(|value| value.as_u64()))
+    }
 }
 
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
-        let wallet = load_wallet(opts.files)?;
+        let url = opts.api_url.clone();
+        let timeouts = opts.timeouts;
+        if self.validate_only {
+            let problems =
+                self.validate_with(opts, |network| timeouts.client(api_url(&url, network)))?;
+            if !problems.is_empty() {
+                bail!("Found {} problem(s) with the payees", problems.len());
+            }
+            return Ok(());
+        }
+        self.run_with(opts, |network| timeouts.client(api_url(&url, network)))?;
+        Ok(())
+    }
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+    /// The location and <address>=<amount> text of the given payees
+    /// followed by those in the payees file
+    fn payee_entries(&self) -> Result<Vec<(String, String)>> {
+        let mut entries: Vec<(String, String)> = self
+            .payees
+            .iter()
+            .enumerate()
+            .map(|(i, payee)| (format!("--payee {}", i + 1), payee.clone()))
+            .collect();
+        if let Some(path) = &self.payees_file {
+            entries.extend(read_payees_file(path)?);
+        }
+        Ok(entries)
+    }
 
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let account = client.get_account(&keypair.public_key().to_string())?;
+    /// Checks every payee and the total of the payments against the balance
+    /// of the payer without building or submitting the payment, and prints
+    /// and returns the location and description of every problem found.
+    pub fn validate_with<A: PayApi>(
+        &self,
+        opts: Opts,
+        connect: impl FnOnce(Network) -> Result<A>,
+    ) -> Result<Vec<(String, String)>> {
+        let entries = self.payee_entries()?;
+        let payer = match &self.payer {
+            Some(payer) => payer.clone(),
+            None => load_wallet(opts.files)?.public_key,
+        };
+        let allowed = match allowlist_path() {
+            Some(path) if !self.no_allowlist => read_allowlist(&path)?,
+            _ => None,
+        };
 
-        let payments: Result<Vec<Payment>> = self
-            .payees
+        let mut problems = Vec::new();
+        let mut payees: Vec<(&String, Payee)> = Vec::with_capacity(entries.len());
+        let mut seen: HashMap<String, String> = HashMap::new();
+        for (location, text) in &entries {
+            let payee = match Payee::parse(text, self.decimal_separator) {
+                Ok(payee) => payee,
+                Err(err) => {
+                    problems.push((location.clone(), err.to_string()));
+                    continue;
+                }
+            };
+            if payee.address.network != payer.network {
+                problems.push((
+                    location.clone(),
+                    format!("Payee {} is not a {} address", payee.address, payer.network),
+                ));
+            }
+            if payee.is_zero() && !self.allow_zero {
+                problems.push((
+                    location.clone(),
+                    format!("Zero amount payment to {}", payee.address),
+                ));
+            }
+            if let Some(allowed) = &allowed {
+                if !allowed.contains(&payee.address) {
+                    problems.push((
+                        location.clone(),
+                        format!("Payee {} is not in the payee allowlist", payee.address),
+                    ));
+                }
+            }
+            match seen.get(&payee.address.to_string()) {
+                Some(first) => problems.push((
+                    location.clone(),
+                    format!(
+                        "Duplicate payee {}, also listed at {}",
+                        payee.address, first
+                    ),
+                )),
+                None => {
+                    seen.insert(payee.address.to_string(), location.clone());
+                }
+            }
+            payees.push((location, payee));
+        }
+        if payees.iter().filter(|(_, p)| p.amount.is_sweep()).count() > 1 {
+            problems.push((
+                "payees".to_string(),
+                "Only one sweep payee is supported".to_string(),
+            ));
+        }
+
+        let api = connect(payer.network)?;
+        let account = api.account(&payer)?;
+        let mut price = if payees.iter().any(|(_, p)| p.amount.needs_price())
+            || matches!(self.fee, Some(FeeAmount::Hnt(_)))
+        {
+            Some(self.oracle_price(&api)?)
+        } else {
+            None
+        };
+        let mut amounts = Vec::with_capacity(payees.len());
+        for (location, payee) in &payees {
+            match payee
+                .amount
+                .to_bones(price.as_ref().map(|price| price.price))
+            {
+                Ok(amount) => amounts.push(amount),
+                Err(err) => {
+                    problems.push((location.to_string(), err.to_string()));
+                    amounts.push(Some(0));
+                }
+            }
+        }
+        let fixed = amounts
+            .iter()
+            .flatten()
+            .fold(0u64, |total, amount| total.saturating_add(*amount));
+        let payments: Vec<(PublicKey, u64)> = payees
             .iter()
-            .map(|p| {
-                Ok(Payment {
-                    payee: p.address.to_vec(),
-                    amount: p.amount.to_bones(),
-                })
+            .zip(&amounts)
+            .map(|((_, p), amount)| {
+                (
+                    p.address.clone(),
+                    amount.unwrap_or_else(|| account.balance.saturating_sub(fixed)),
+                )
             })
             .collect();
-        let mut txn = BlockchainTxnPaymentV2 {
-            fee: 0,
-            payments: payments?,
-            payer: keypair.public_key().into(),
-            nonce: account.speculative_nonce + 1,
-            signature: Vec::new(),
+        let fee = match self.fee {
+            Some(fee) => fee.to_dc(price_or_zero(&price))?,
+            None => {
+                TxnBuilder::payment(&payments)
+                    .nonce(account.speculative_nonce + 1)
+                    .fee_config(&override_txn_fees(api.txn_fees()?, &opts.submit))
+                    .build(&payer)?
+                    .fee
+            }
         };
+        let fee_bones = match FeeSource::for_fee(account.dc_balance, fee) {
+            FeeSource::DcBalance => 0,
+            FeeSource::HntBurn => {
+                if price.is_none() {
+                    price = Some(self.oracle_price(&api)?);
+                }
+                dc_to_hnt_bones(fee, price_or_zero(&price))?
+            }
+        };
+        if self.no_hnt_burn {
+            if let Err(err) = check_dc_fee(account.dc_balance, fee) {
+                problems.push(("fee".to_string(), err.to_string()));
+            }
+        }
+        if fixed.saturating_add(fee_bones) > account.balance {
+            problems.push((
+                "total".to_string(),
+                format!(
+                    "Payments of {} and a fee of {} exceed the balance of {}",
+                    Hnt::from_bones(fixed),
+                    Hnt::from_bones(fee_bones),
+                    Hnt::from_bones(account.balance)
+                ),
+            ));
+        }
+
+        print_validation(entries.len(), fixed, fee, &problems, opts.format)?;
+        Ok(problems)
+    }
 
-        txn.fee = if let Some(fee) = self.fee {
+    /// Builds, signs and submits the payment using the API returned by
+    /// `connect` for the network of the payer, and returns the payment
+    /// envelopes. There is more than one envelope only when the payees are
+    /// split.
+    pub fn run_with<A: PayApi>(
+        &self,
+        opts: Opts,
+        connect: impl FnOnce(Network) -> Result<A>,
+    ) -> Result<Vec<BlockchainTxn>> {
+        let payees = self
+            .payee_entries()?
+            .iter()
+            .map(|(_, payee)| Payee::parse(payee, self.decimal_separator))
+            .collect::<Result<Vec<Payee>>>()?;
+        if self.v1 && payees.len() != 1 {
+            bail!("A version 1 payment requires exactly one payee");
+        }
+        match check_zero_amounts(&payees) {
+            Err(err) if self.allow_zero => eprintln!("Warning: {}", err),
+            Err(err) => bail!("{}, use --allow-zero to send anyway", err),
+            Ok(()) => (),
+        }
+        if self.no_allowlist {
+            eprintln!(
+                "WARNING: --no-allowlist given, payees are NOT checked against the payee allowlist"
+            );
+        } else if let Some(path) = allowlist_path() {
+            if let Some(allowed) = read_allowlist(&path)? {
+                check_allowlist(&payees, &allowed)?;
+            }
+        }
+        // A payment from another payer is left unsigned for the payer to
+        // sign with cosign. An estimate is never signed.
+        let (payer, wallet) = match &self.payer {
+            Some(payer) => (payer.clone(), None),
+            None if self.estimate_only => (load_wallet(opts.files)?.public_key, None),
+            None => {
+                let wallet = load_wallet(opts.files)?;
+                (wallet.public_key.clone(), Some(wallet))
+            }
+        };
+
+        let api = connect(payer.network)?;
+        // The chain maximum is only fetched when a maximum is given or the
+        // payees may be split
+        let max_payees = if self.split || self.max_payees_per_txn.is_some() {
+            check_max_payees(
+                payees.len(),
+                self.max_payees_per_txn,
+                api.max_payments()?,
+                self.split,
+            )?
+        } else {
+            None
+        };
+        if let Some(max) = max_payees.filter(|max| payees.len() as u64 > *max) {
+            let wallet =
+                wallet.ok_or_else(|| anyhow!("A split payment must be signed by this wallet"))?;
+            return self.run_split(opts, &api, &wallet, &payees, max);
+        }
+
+        // Hold the wallet's nonce lock from reading the nonce until the
+        // payment is submitted
+        let _lock = if self.commit && !opts.submit.dry_run {
+            NonceLock::acquire(&payer)?
+        } else {
+            None
+        };
+        let account = api.account(&payer)?;
+        check_logged_nonce(
+            &payer,
+            account.speculative_nonce + 1,
+            self.commit,
+            &opts.submit,
+        )?;
+
+        let sweep = payees.iter().position(|p| p.amount.is_sweep());
+        if payees.iter().filter(|p| p.amount.is_sweep()).count() > 1 {
+            bail!("Only one sweep payee is supported");
+        }
+        if self.reserve.is_some() && sweep.is_none() {
+            bail!("A reserve requires a sweep payee");
+        }
+        let percent_total = payees
+            .iter()
+            .filter_map(|p| p.amount.percent())
+            .fold(Decimal::from(0), |total, percent| total + percent);
+        if percent_total > Decimal::from(100) {
+            bail!("Percentages add up to {}%, more than 100%", percent_total);
+        }
+        // The oracle price is only fetched when it is needed for the dust
+        // check, to convert USD and DC amounts or an HNT fee, or below when
+        // HNT is burned for the fee
+        let mut price = if self.needs_price(&payees) {
+            Some(self.oracle_price(&api)?)
+        } else {
+            None
+        };
+        let amounts = payees
+            .iter()
+            .map(|p| p.amount.to_bones(price.as_ref().map(|price| price.price)))
+            .collect::<Result<Vec<Option<u64>>>>()?;
+        let fixed: u64 = amounts.iter().flatten().sum();
+        // Percent and sweep amounts start at the remaining balance to
+        // calculate the (upper bound of the) fee and are adjusted once the
+        // fee is known
+        let payments: Vec<(PublicKey, u64)> = payees
+            .iter()
+            .zip(amounts)
+            .map(|(p, amount)| {
+                (
+                    p.address.clone(),
+                    amount.unwrap_or_else(|| account.balance.saturating_sub(fixed)),
+                )
+            })
+            .collect();
+        let fee_config = override_txn_fees(api.txn_fees()?, &opts.submit);
+        let mut txn = TxnBuilder::payment(&payments)
+            .nonce(account.speculative_nonce + 1)
+            .fee_config(&fee_config)
+            .build(&payer)?;
+        let minimum_fee = if self.v1 {
+            to_v1(&txn)?.txn_fee(&fee_config)?
+        } else {
+            txn.fee
+        };
+        txn.fee = if let Some(requested) = self.fee {
+            let fee = requested.to_dc(price_or_zero(&price))?;
+            if let FeeAmount::Hnt(hnt) = requested {
+                eprintln!(
+                    "Fee of {} HNT is {} DC at the oracle price of ${}",
+                    hnt,
+                    fee,
+                    Decimal::new(price_or_zero(&price) as i64, 8)
+                );
+            }
+            match check_fee(fee, minimum_fee) {
+                Err(err) if self.force => eprintln!("Warning: {}", err),
+                Err(err) => bail!("{}, use --force to send anyway", err),
+                Ok(()) => (),
+            }
             fee
         } else {
-            txn.txn_fee(&get_txn_fees(&client)?)?
+            minimum_fee
+        };
+        if self.no_hnt_burn {
+            check_dc_fee(account.dc_balance, txn.fee)?;
+        }
+        let fee_source = FeeSource::for_fee(account.dc_balance, txn.fee);
+        if fee_source == FeeSource::HntBurn && price.is_none() {
+            price = Some(self.oracle_price(&api)?);
+        }
+        // Percentages are of the balance that remains after the fee
+        let mut percent_paid = 0;
+        if !percent_total.is_zero() {
+            let available = calculate_remaining_hnt(
+                account.balance,
+                account.dc_balance,
+                txn.fee,
+                price_or_zero(&price),
+                0,
+            )?;
+            for (payment, payee) in txn.payments.iter_mut().zip(&payees) {
+                if let Some(percent) = payee.amount.percent() {
+                    payment.amount = percent_of_bones(percent, available)?;
+                    percent_paid += payment.amount;
+                }
+            }
+        }
+        if let Some(index) = sweep {
+            let remaining = calculate_remaining_hnt(
+                account.balance.saturating_sub(fixed + percent_paid),
+                account.dc_balance,
+                txn.fee,
+                price_or_zero(&price),
+                self.reserve.map_or(0, |reserve| reserve.to_bones()),
+            )?;
+            if remaining == 0 {
+                bail!("No remaining balance to sweep");
+            }
+            txn.payments[index].amount = remaining;
+        }
+        if let Some(max) = self.max_per_payee {
+            check_max_per_payee(&txn.payments, max.to_bones())?;
+        }
+        if let Some(price) = &price {
+            match check_dust(&txn.payments, txn.fee, price.price, opts.fiat_decimals) {
+                Err(err) if self.allow_dust => eprintln!("Warning: {}", err),
+                Err(err) => bail!("{}, use --allow-dust to send anyway", err),
+                Ok(()) => (),
+            }
+        }
+        if self.estimate_only {
+            let projection = Projection::new(&account, &txn, fee_source, price_or_zero(&price))?;
+            print_projection(&txn, &payees, fee_source, &projection, opts.format)?;
+            projection.check()?;
+            return Ok(vec![txn.in_envelope()]);
+        }
+        check_balance(&account, &txn.in_envelope(), BalanceType::Hnt)?;
+        if fee_source == FeeSource::HntBurn {
+            eprintln!(
+                "Warning: DC balance {} does not cover the fee of {} DC, HNT will be implicitly burned to pay the fee",
+                account.dc_balance, txn.fee
+            );
+        }
+        if let Some(path) = &self.summary {
+            let summary = payment_summary(&txn, payer.network, fee_source, price_or_zero(&price))?;
+            write_atomic(path, summary.as_bytes())?;
+        }
+        // The wallet is only decrypted once the payment passed all checks
+        let signer = match &wallet {
+            Some(wallet) => Some(wallet_signer(wallet, &opts.key_file, &opts.signer_command)?),
+            None => None,
         };
-        txn.signature = txn.sign(&keypair)?;
-        let envelope = txn.in_envelope();
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
+        // The fee counts the signature at its full size, so it is the same
+        // for the unsigned and the signed payment
+        let (envelope, size) = if self.v1 {
+            let mut txn = to_v1(&txn)?;
+            if let Some(signer) = &signer {
+                txn.signature = txn.sign(signer)?;
+            }
+            (txn.in_envelope(), txn.txn_size()?)
+        } else {
+            if let Some(signer) = &signer {
+                txn.signature = txn.sign(signer)?;
+            }
+            (txn.in_envelope(), txn.txn_size()?)
+        };
+        if self.commit && self.check_nonce {
+            verify_nonce(
+                account.speculative_nonce,
+                api.account(&payer)?.speculative_nonce,
+            )?;
+        }
+        let status = submit_txn(&api, &envelope, self.commit, &opts.submit)?;
+
+        print_txn(
+            &txn,
+            &envelope,
+            size,
+            &payees,
+            payer.network,
+            fee_source,
+            price.as_ref(),
+            &status,
+            opts.encoding,
+            opts.format,
+        )?;
+        Ok(vec![envelope])
+    }
+
+    /// Builds, signs and submits the payees in payments of at most `max`
+    /// payees each at consecutive nonces, and returns the payment envelopes.
+    /// The fee of each payment is paid from what remains of the DC balance
+    /// after the payments before it.
+    fn run_split<A: PayApi>(
+        &self,
+        opts: Opts,
+        api: &A,
+        wallet: &Wallet,
+        payees: &[Payee],
+        max: u64,
+    ) -> Result<Vec<BlockchainTxn>> {
+        let payer = &wallet.public_key;
+        if self.reserve.is_some() {
+            bail!("A reserve requires a sweep payee");
+        }
+        let mut price = if self.needs_price(payees) {
+            Some(self.oracle_price(api)?)
         } else {
             None
         };
+        let mut payments = Vec::with_capacity(payees.len());
+        for payee in payees {
+            match payee
+                .amount
+                .to_bones(price.as_ref().map(|price| price.price))?
+            {
+                Some(amount) => payments.push((payee.address.clone(), amount)),
+                None => bail!("Sweep and percentage amounts can not be split"),
+            }
+        }
+
+        // Hold the wallet's nonce lock from reading the nonce until the
+        // payments are submitted
+        let _lock = if self.commit && !opts.submit.dry_run {
+            NonceLock::acquire(payer)?
+        } else {
+            None
+        };
+        let account = api.account(payer)?;
+        let fee_config = override_txn_fees(api.txn_fees()?, &opts.submit);
+        let mut txns = Vec::with_capacity(txn_count(payees.len(), max) as usize);
+        let mut dc_balance = account.dc_balance;
+        let mut burned = 0;
+        for (index, group) in split_payees(&payments, max).into_iter().enumerate() {
+            let nonce = account.speculative_nonce + 1 + index as u64;
+            check_logged_nonce(payer, nonce, self.commit, &opts.submit)?;
+            let mut txn = TxnBuilder::payment(group)
+                .nonce(nonce)
+                .fee_config(&fee_config)
+                .build(payer)?;
+            if let Some(requested) = self.fee {
+                let fee = requested.to_dc(price_or_zero(&price))?;
+                match check_fee(fee, txn.fee) {
+                    Err(err) if self.force => eprintln!("Warning: {}", err),
+                    Err(err) => bail!("{}, use --force to send anyway", err),
+                    Ok(()) => (),
+                }
+                txn.fee = fee;
+            }
+            if self.no_hnt_burn {
+                check_dc_fee(dc_balance, txn.fee)?;
+            }
+            let fee_source = FeeSource::for_fee(dc_balance, txn.fee);
+            match fee_source {
+                FeeSource::DcBalance => dc_balance -= txn.fee,
+                FeeSource::HntBurn => {
+                    if price.is_none() {
+                        price = Some(self.oracle_price(api)?);
+                    }
+                    burned += dc_to_hnt_bones(txn.fee, price_or_zero(&price))?;
+                }
+            }
+            if let Some(max) = self.max_per_payee {
+                check_max_per_payee(&txn.payments, max.to_bones())?;
+            }
+            txns.push((txn, fee_source));
+        }
+        if let Some(price) = &price {
+            for (txn, _) in &txns {
+                match check_dust(&txn.payments, txn.fee, price.price, opts.fiat_decimals) {
+                    Err(err) if self.allow_dust => eprintln!("Warning: {}", err),
+                    Err(err) => bail!("{}, use --allow-dust to send anyway", err),
+                    Ok(()) => (),
+                }
+            }
+        }
+        let paid: u64 = payments.iter().map(|(_, amount)| amount).sum();
+        if paid + burned > account.balance {
+            bail!(
+                "Insufficient balance {} for payments of {} and {} burned for fees",
+                Hnt::from_bones(account.balance),
+                Hnt::from_bones(paid),
+                Hnt::from_bones(burned)
+            );
+        }
+        if burned > 0 {
+            eprintln!(
+                "Warning: DC balance {} does not cover the fees, {} HNT will be implicitly burned to pay them",
+                account.dc_balance,
+                Hnt::from_bones(burned)
+            );
+        }
+        if let Some(path) = &self.summary {
+            let mut summary = String::new();
+            for (txn, fee_source) in &txns {
+                summary.push_str(&payment_summary(
+                    txn,
+                    payer.network,
+                    *fee_source,
+                    price_or_zero(&price),
+                )?);
+            }
+            write_atomic(path, summary.as_bytes())?;
+        }
+
+        // The wallet is only decrypted once the payments passed all checks
+        let signer = wallet_signer(wallet, &opts.key_file, &opts.signer_command)?;
+        let mut envelopes = Vec::with_capacity(txns.len());
+        for (txn, _) in txns.iter_mut() {
+            txn.signature = txn.sign(&signer)?;
+            envelopes.push(txn.in_envelope());
+        }
+        if self.commit && self.check_nonce {
+            verify_nonce(
+                account.speculative_nonce,
+                api.account(payer)?.speculative_nonce,
+            )?;
+        }
+        let results = submit_batch(api, &envelopes, self.commit, &opts.submit)?;
+
+        print_split(
+            &txns,
+            &results,
+            payer.network,
+            self.commit && !opts.submit.dry_run,
+            opts.format,
+        )?;
+        check_batch(&results)?;
+        Ok(envelopes)
+    }
+
+    /// Whether the oracle price is needed before the fee is known: for the
+    /// dust check unless --allow-dust is given, and to convert USD and DC
+    /// amounts or an HNT fee
+    fn needs_price(&self, payees: &[Payee]) -> bool {
+        !self.allow_dust
+            || payees.iter().any(|p| p.amount.needs_price())
+            || matches!(self.fee, Some(FeeAmount::Hnt(_)))
+    }
+
+    /// Fetches the oracle price and checks its age against --max-oracle-age
+    fn oracle_price<A: PayApi>(&self, api: &A) -> Result<OraclePrice> {
+        let price = api.oracle_price()?;
+        if let Some(max_age) = self.max_oracle_age {
+            check_oracle_age(&price, api.height()?, max_age)?;
+        }
+        Ok(price)
+    }
+}
+
+/// The given oracle price, or 0 if it was not needed and not fetched.
+/// Conversions with a zero oracle price fail rather than give a wrong
+/// amount.
+fn price_or_zero(price: &Option<OraclePrice>) -> u64 {
+    price.as_ref().map_or(0, |price| price.price)
+}
+
+/// Converts a single payee payment to a version 1 payment transaction
+pub fn to_v1(txn: &BlockchainTxnPaymentV2) -> Result<BlockchainTxnPaymentV1> {
+    match txn.payments.as_slice() {
+        [payment] => Ok(BlockchainTxnPaymentV1 {
+            payer: txn.payer.clone(),
+            payee: payment.payee.clone(),
+            amount: payment.amount,
+            fee: txn.fee,
+            nonce: txn.nonce,
+            signature: txn.signature.clone(),
+        }),
+        payments => bail!(
+            "A version 1 payment requires exactly one payee, got {}",
+            payments.len()
+        ),
+    }
+}
+
+/// Calculates the amount of HNT in bones that remains of a given balance
+/// after paying a fee and keeping a reserve in bones. The fee is paid from
+/// the DC balance if it covers the fee, otherwise the HNT needed to burn
+/// for the fee at the given oracle price is deducted.
+pub fn calculate_remaining_hnt(
+    balance: u64,
+    dc_balance: u64,
+    fee: u64,
+    oracle_price: u64,
+    reserve: u64,
+) -> Result<u64> {
+    let remaining = if FeeSource::for_fee(dc_balance, fee) == FeeSource::DcBalance {
+        balance
+    } else {
+        let fee_bones = dc_to_hnt_bones(fee, oracle_price)?;
+        if fee_bones >= balance {
+            bail!(
+                "Insufficient balance {} to pay fee of {}",
+                Hnt::from_bones(balance),
+                Hnt::from_bones(fee_bones)
+            );
+        }
+        balance - fee_bones
+    };
+    if reserve > remaining {
+        bail!(
+            "Reserve of {} exceeds the available balance of {}",
+            Hnt::from_bones(reserve),
+            Hnt::from_bones(remaining)
+        );
+    }
+    Ok(remaining - reserve)
+}
 
-        print_txn(&txn, &envelope, &status, opts.format)
+/// Checks that the oracle price was set at most the given number of blocks
+/// before the given chain height.
+pub fn check_oracle_age(price: &OraclePrice, height: u64, max_age: u64) -> Result {
+    let age = height.saturating_sub(price.block);
+    if age > max_age {
+        bail!(
+            "Oracle price from block {} is {} blocks old, more than the maximum of {}",
+            price.block,
+            age,
+            max_age
+        );
     }
+    Ok(())
+}
+
+/// The number of transactions needed to pay the given number of payees
+/// with at most `max` payees per transaction
+pub fn txn_count(payees: usize, max: u64) -> u64 {
+    if max == 0 {
+        return 0;
+    }
+    (payees as u64 + max - 1) / max
+}
+
+/// Splits payees into consecutive groups of at most `max` payees, one for
+/// each transaction. Only the last group can have fewer than `max` payees.
+pub fn split_payees<T>(payees: &[T], max: u64) -> Vec<&[T]> {
+    payees.chunks(max.max(1) as usize).collect()
+}
+
+/// Checks the number of payees against the given maximum per transaction,
+/// or the chain maximum if none is given, and returns the maximum that
+/// applies. The given maximum can not be above the chain maximum. More
+/// payees than the maximum are only accepted when they are split.
+pub fn check_max_payees(
+    payees: usize,
+    max: Option<u64>,
+    chain_max: Option<u64>,
+    split: bool,
+) -> Result<Option<u64>> {
+    if let Some(max) = max {
+        if max == 0 {
+            bail!("The maximum number of payees per transaction must be at least 1");
+        }
+        if let Some(chain_max) = chain_max.filter(|chain_max| max > *chain_max) {
+            bail!(
+                "A maximum of {} payees per transaction is above the chain maximum of {}",
+                max,
+                chain_max
+            );
+        }
+    }
+    let max = max.or(chain_max);
+    if let Some(max) = max.filter(|max| payees as u64 > *max && !split) {
+        bail!(
+            "{} payees are more than the maximum of {} per transaction, use --split to pay them in {} transactions",
+            payees,
+            max,
+            txn_count(payees, max)
+        );
+    }
+    Ok(max)
+}
+
+/// Checks that a manually set fee is not below the minimum fee of the
+/// transaction, which the chain would reject.
+pub fn check_fee(fee: u64, minimum_fee: u64) -> Result {
+    if fee < minimum_fee {
+        bail!(
+            "Fee of {} DC is below the minimum fee of {} DC",
+            fee,
+            minimum_fee
+        );
+    }
+    Ok(())
+}
+
+/// Checks that no (non-zero) payment is worth less than the HNT value of
+/// the DC fee at the given oracle price.
+pub fn check_dust(payments: &[Payment], fee: u64, oracle_price: u64, fiat_decimals: u32) -> Result {
+    let fee_bones = dc_to_hnt_bones(fee, oracle_price)?;
+    for payment in payments {
+        if payment.amount > 0 && payment.amount < fee_bones {
+            bail!(
+                "Sending {} HNT to {} costs {} HNT (${}) in fees",
+                Hnt::from_bones(payment.amount),
+                PublicKey::from_bytes(&payment.payee)?,
+                Hnt::from_bones(fee_bones),
+                format_usd(dc_to_usd(fee), fiat_decimals)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Checks that no payment pays its payee more than the given maximum in
+/// bones
+pub fn check_max_per_payee(payments: &[Payment], max: u64) -> Result {
+    for payment in payments {
+        if payment.amount > max {
+            bail!(
+                "Payment of {} HNT to {} exceeds the maximum of {} HNT per payee",
+                Hnt::from_bones(payment.amount),
+                PublicKey::from_bytes(&payment.payee)?,
+                Hnt::from_bones(max)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Where the DC fee of a transaction is paid from. If the DC balance of
+/// the payer does not cover the fee the chain burns HNT from the payer to
+/// cover it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeSource {
+    DcBalance,
+    HntBurn,
+}
+
+impl FeeSource {
+    pub fn for_fee(dc_balance: u64, fee: u64) -> Self {
+        if dc_balance >= fee {
+            Self::DcBalance
+        } else {
+            Self::HntBurn
+        }
+    }
+}
+
+/// Fails if the DC balance does not cover the fee, for payments that must
+/// not burn HNT to pay the fee
+pub fn check_dc_fee(dc_balance: u64, fee: u64) -> Result {
+    if FeeSource::for_fee(dc_balance, fee) == FeeSource::HntBurn {
+        bail!(
+            "Insufficient DC balance {} for a fee of {} DC; HNT burn disabled",
+            dc_balance,
+            fee
+        );
+    }
+    Ok(())
+}
+
+impl fmt::Display for FeeSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DcBalance => f.write_str("dc_balance"),
+            Self::HntBurn => f.write_str("hnt_burn"),
+        }
+    }
+}
+
+/// The HNT and DC balances of a payer before and after a payment. The
+/// balances after the payment are negative if the balances before do not
+/// cover the payments and the fee.
+#[derive(Debug, PartialEq)]
+pub struct Projection {
+    pub balance_before: u64,
+    pub balance_after: i64,
+    pub dc_balance_before: u64,
+    pub dc_balance_after: i64,
+}
+
+impl Projection {
+    /// Projects the balances of the given account after the payment, with
+    /// the fee paid from the given source at the given oracle price
+    pub fn new(
+        account: &Account,
+        txn: &BlockchainTxnPaymentV2,
+        fee_source: FeeSource,
+        oracle_price: u64,
+    ) -> Result<Self> {
+        let paid: u64 = txn.payments.iter().map(|p| p.amount).sum();
+        let (fee_bones, fee_dc) = match fee_source {
+            FeeSource::DcBalance => (0, txn.fee),
+            FeeSource::HntBurn => (dc_to_hnt_bones(txn.fee, oracle_price)?, 0),
+        };
+        Ok(Self {
+            balance_before: account.balance,
+            balance_after: account.balance as i64 - paid as i64 - fee_bones as i64,
+            dc_balance_before: account.dc_balance,
+            dc_balance_after: account.dc_balance as i64 - fee_dc as i64,
+        })
+    }
+
+    /// Fails if the payment leaves a negative balance
+    pub fn check(&self) -> Result {
+        if self.balance_after < 0 {
+            bail!(
+                "Insufficient balance {} for the payment, the balance after it would be {}",
+                Hnt::from_bones(self.balance_before),
+                bones_decimal(self.balance_after)
+            );
+        }
+        if self.dc_balance_after < 0 {
+            bail!(
+                "Insufficient DC balance {} for the fee, the DC balance after it would be {}",
+                self.dc_balance_before,
+                self.dc_balance_after
+            );
+        }
+        Ok(())
+    }
+}
+
+/// An amount of bones as a (possibly negative) HNT decimal
+fn bones_decimal(bones: i64) -> Decimal {
+    Decimal::new(bones, 8)
+}
+
+fn print_projection(
+    txn: &BlockchainTxnPaymentV2,
+    payees: &[Payee],
+    fee_source: FeeSource,
+    projection: &Projection,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Payee", "Amount", "Requested"]);
+            for (payment, payee) in txn.payments.iter().zip(payees) {
+                table.add_row(row![
+                    PublicKey::from_bytes(&payment.payee)?.to_string(),
+                    Hnt::from_bones(payment.amount),
+                    payee.amount
+                ]);
+            }
+            print_table(&table)?;
+
+            let mut table = Table::new();
+            table.add_row(row!["Key", "Before", "After"]);
+            table.add_row(row![
+                "Balance",
+                bones_decimal(projection.balance_before as i64),
+                bones_decimal(projection.balance_after)
+            ]);
+            table.add_row(row![
+                "DC Balance",
+                projection.dc_balance_before,
+                projection.dc_balance_after
+            ]);
+            print_table(&table)?;
+            println!(
+                "
+Fee: {} DC from {}",
+                txn.fee, fee_source
+            );
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let mut payments = Vec::with_capacity(txn.payments.len());
+            for (payment, payee) in txn.payments.iter().zip(payees) {
+                payments.push(json!({
+                    "payee": PublicKey::from_bytes(&payment.payee)?.to_string(),
+                    "amount": Hnt::from_bones(payment.amount),
+                    "requested": payee.amount.to_string(),
+                }))
+            }
+            let table = json!({
+                "payments": payments,
+                "fee": txn.fee,
+                "fee_source": fee_source.to_string(),
+                "balance_before": bones_decimal(projection.balance_before as i64),
+                "balance_after": bones_decimal(projection.balance_after),
+                "dc_balance_before": projection.dc_balance_before,
+                "dc_balance_after": projection.dc_balance_after,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+fn print_validation(
+    count: usize,
+    total: u64,
+    fee: u64,
+    problems: &[(String, String)],
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            if problems.is_empty() {
+                println!(
+                    "All {} payees are valid, paying {} with a fee of {} DC",
+                    count,
+                    Hnt::from_bones(total),
+                    fee
+                );
+                return Ok(());
+            }
+            let mut table = Table::new();
+            table.add_row(row!["Location", "Problem"]);
+            for (location, problem) in problems {
+                table.add_row(row![location, problem]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let problems: Vec<serde_json::Value> = problems
+                .iter()
+                .map(|(location, problem)| json!({"location": location, "problem": problem}))
+                .collect();
+            let table = json!({
+                "payees": count,
+                "total": Hnt::from_bones(total),
+                "fee": fee,
+                "problems": problems,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+fn print_split(
+    txns: &[(BlockchainTxnPaymentV2, FeeSource)],
+    results: &[BatchResult],
+    network: Network,
+    committed: bool,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row![
+                "Network",
+                "Nonce",
+                "Payees",
+                "Amount",
+                "Fee",
+                "Fee Source",
+                "Hash",
+                "Status",
+                "Reason"
+            ]);
+            for ((txn, fee_source), result) in txns.iter().zip(results) {
+                let amount: u64 = txn.payments.iter().map(|p| p.amount).sum();
+                table.add_row(row![
+                    network,
+                    txn.nonce,
+                    txn.payments.len(),
+                    Hnt::from_bones(amount),
+                    txn.fee,
+                    fee_source,
+                    result.hash().unwrap_or("none"),
+                    result.status(),
+                    result.reason().unwrap_or_default()
+                ]);
+            }
+            print_table(&table)?;
+            if !committed {
+                println!("\nPreview mode: use --commit to submit the payments to the network");
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let mut split = Vec::with_capacity(txns.len());
+            for ((txn, fee_source), result) in txns.iter().zip(results) {
+                let mut payments = Vec::with_capacity(txn.payments.len());
+                for payment in &txn.payments {
+                    payments.push(json!({
+                        "payee": PublicKey::from_bytes(&payment.payee)?.to_string(),
+                        "amount": Hnt::from_bones(payment.amount),
+                    }));
+                }
+                split.push(json!({
+                    "network": network.to_string(),
+                    "nonce": txn.nonce,
+                    "payments": payments,
+                    "fee": txn.fee,
+                    "fee_source": fee_source.to_string(),
+                    "hash": result.hash(),
+                    "status": result.status(),
+                    "reason": result.reason(),
+                }));
+            }
+            print_json(&split)
+        }
+    }
+}
+
+/// Describes the payment in plain language, like "Send 10 HNT to <address>,
+/// fee 35000 DC (~0.35 HNT burned), nonce 42, network mainnet"
+fn payment_summary(
+    txn: &BlockchainTxnPaymentV2,
+    network: Network,
+    fee_source: FeeSource,
+    oracle_price: u64,
+) -> Result<String> {
+    let mut payments = Vec::with_capacity(txn.payments.len());
+    for payment in &txn.payments {
+        payments.push(format!(
+            "{} HNT to {}",
+            Hnt::from_bones(payment.amount),
+            PublicKey::from_bytes(&payment.payee)?
+        ));
+    }
+    let fee = match fee_source {
+        FeeSource::DcBalance => format!("fee {} DC from the DC balance", txn.fee),
+        FeeSource::HntBurn => format!(
+            "fee {} DC (~{} HNT burned)",
+            txn.fee,
+            Hnt::from_bones(dc_to_hnt_bones(txn.fee, oracle_price)?)
+        ),
+    };
+    Ok(format!(
+        "Send {}, {}, nonce {}, network {}\n",
+        payments.join(", "),
+        fee,
+        txn.nonce,
+        network
+    ))
 }
 
 fn print_txn(
     txn: &BlockchainTxnPaymentV2,
     envelope: &BlockchainTxn,
+    size: usize,
+    payees: &[Payee],
+    network: Network,
+    fee_source: FeeSource,
+    price: Option<&OraclePrice>,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
+    // The HNT value of the fee is only known when the oracle price was
+    // fetched
+    let fee_hnt = match price {
+        Some(price) => Some(Hnt::from_bones(dc_to_hnt_bones(txn.fee, price.price)?)),
+        None => None,
+    };
     match format {
         OutputFormat::Table => {
             let mut table = Table::new();
-            table.add_row(row!["Payee", "Amount"]);
-            for payment in txn.payments.clone() {
+            table.add_row(row!["Payee", "Amount", "Requested"]);
+            for (payment, payee) in txn.payments.iter().zip(payees) {
                 table.add_row(row![
-                    PublicKey::from_bytes(payment.payee)?.to_string(),
-                    Hnt::from_bones(payment.amount)
+                    PublicKey::from_bytes(&payment.payee)?.to_string(),
+                    Hnt::from_bones(payment.amount),
+                    payee.amount
                 ]);
             }
             print_table(&table)?;
 
-            ptable!(
-                ["Key", "Value"],
-                ["Fee", txn.fee],
-                ["Nonce", txn.nonce],
-                ["Hash", status_str(status)]
-            );
+            let mut table = Table::new();
+            table.add_row(row!["Key", "Value"]);
+            table.add_row(row!["Network", network]);
+            table.add_row(row!["Fee", txn.fee]);
+            if let Some(fee_hnt) = fee_hnt {
+                table.add_row(row!["Fee (HNT)", fee_hnt]);
+            }
+            table.add_row(row!["Size (bytes)", size]);
+            table.add_row(row!["Fee Source", fee_source]);
+            table.add_row(row!["Nonce", txn.nonce]);
+            if let Some(price) = price {
+                table.add_row(row![
+                    "Oracle Price",
+                    format!("${}", Decimal::new(price.price as i64, 8))
+                ]);
+                table.add_row(row!["Oracle Block", price.block]);
+                if let Some(timestamp) = &price.timestamp {
+                    table.add_row(row!["Oracle Timestamp", timestamp]);
+                }
+            }
+            table.add_row(row!["Hash", status_str(status)]);
+            print_table(&table)?;
 
             print_footer(status)
         }
         OutputFormat::Json => {
             let mut payments = Vec::with_capacity(txn.payments.len());
-            for payment in txn.payments.clone() {
+            for (payment, payee) in txn.payments.iter().zip(payees) {
                 payments.push(json!({
-                    "payee": PublicKey::from_bytes(payment.payee)?.to_string(),
+                    "payee": PublicKey::from_bytes(&payment.payee)?.to_string(),
                     "amount": Hnt::from_bones(payment.amount),
+                    "requested": payee.amount.to_string(),
                 }))
             }
             let table = json!({
+                "network": network.to_string(),
                 "payments": payments,
                 "fee": txn.fee,
+                "fee_hnt": fee_hnt,
+                "size": size,
+                "fee_source": fee_source.to_string(),
+                "oracle_price": price,
                 "nonce": txn.nonce,
                 "hash": status_json(status),
-                "txn": envelope.to_b64()?,
+                "txn": encoding.encode(envelope)?,
             });
             print_json(&table)
         }
     }
 }
 
-#[derive(Debug)]
-pub struct Payee {
-    address: PublicKey,
-    amount: Hnt,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cmd::testing::{self, run_pay, MockApi},
+        keypair::Keypair,
+        wallet::Wallet,
+    };
+    use std::{fs, path::Path};
 
-impl FromStr for Payee {
-    type Err = Box<dyn std::error::Error>;
+    #[test]
+    fn fee_from_dc_balance() {
+        assert_eq!(FeeSource::DcBalance, FeeSource::for_fee(35_000, 35_000));
+        assert_eq!(FeeSource::DcBalance, FeeSource::for_fee(100_000, 35_000));
+        assert_eq!(FeeSource::DcBalance, FeeSource::for_fee(0, 0));
+    }
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let pos = s
-            .find('=')
-            .ok_or_else(|| format!("invalid KEY=value: missing `=`  in `{}`", s))?;
-        Ok(Payee {
-            address: s[..pos].parse()?,
-            amount: s[pos + 1..].parse()?,
-        })
+    #[test]
+    fn remaining_hnt() {
+        // $1.00 per HNT, 35_000 DC is $0.35 or 0.35 HNT
+        let price = 100_000_000;
+        assert_eq!(35_000_000, dc_to_hnt_bones(35_000, price).unwrap());
+        assert_eq!(
+            100_000_000,
+            calculate_remaining_hnt(100_000_000, 35_000, 35_000, price, 0).unwrap()
+        );
+        assert_eq!(
+            65_000_000,
+            calculate_remaining_hnt(100_000_000, 0, 35_000, price, 0).unwrap()
+        );
+        assert!(calculate_remaining_hnt(35_000_000, 0, 35_000, price, 0).is_err());
+        assert!(dc_to_hnt_bones(35_000, 0).is_err());
+    }
+
+    #[test]
+    fn remaining_hnt_reserve() {
+        // $1.00 per HNT, 35_000 DC is 0.35 HNT and 0.65 HNT is sweepable
+        let price = 100_000_000;
+        assert_eq!(
+            55_000_000,
+            calculate_remaining_hnt(100_000_000, 0, 35_000, price, 10_000_000).unwrap()
+        );
+        assert_eq!(
+            0,
+            calculate_remaining_hnt(100_000_000, 0, 35_000, price, 65_000_000).unwrap()
+        );
+        assert!(calculate_remaining_hnt(100_000_000, 0, 35_000, price, 65_000_001).is_err());
+        // The fee is paid from the DC balance
+        assert_eq!(
+            90_000_000,
+            calculate_remaining_hnt(100_000_000, 35_000, 35_000, price, 10_000_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn dust_payments() {
+        // $1.00 per HNT, a 35_000 DC fee is worth 0.35 HNT
+        let price = 100_000_000;
+        let payment = |amount| Payment {
+            payee: crate::keypair::Keypair::default().public_key().to_vec(),
+            amount,
+        };
+        assert!(check_dust(&[payment(35_000_000)], 35_000, price, 2).is_ok());
+        assert!(check_dust(&[payment(34_999_999)], 35_000, price, 2).is_err());
+        assert!(check_dust(&[payment(100_000_000), payment(100_000)], 35_000, price, 2).is_err());
+        // Zero amounts are covered by the zero amount check
+        assert!(check_dust(&[payment(0)], 35_000, price, 2).is_ok());
+    }
+
+    #[test]
+    fn oracle_age() {
+        let price = OraclePrice {
+            price: 100_000_000,
+            block: 1000,
+            timestamp: None,
+        };
+        assert!(check_oracle_age(&price, 1000, 0).is_ok());
+        assert!(check_oracle_age(&price, 1060, 60).is_ok());
+        assert!(check_oracle_age(&price, 1061, 60).is_err());
+    }
+
+    #[test]
+    fn max_payees() {
+        // Evenly and unevenly divided payouts
+        assert_eq!(4, txn_count(100, 25));
+        assert_eq!(5, txn_count(101, 25));
+        assert_eq!(1, txn_count(1, 50));
+        assert_eq!(0, txn_count(0, 50));
+        assert_eq!(7, txn_count(7, 1));
+
+        assert_eq!(
+            Some(50),
+            check_max_payees(50, None, Some(50), false).unwrap()
+        );
+        assert!(check_max_payees(51, None, Some(50), false).is_err());
+        assert_eq!(None, check_max_payees(100, None, None, false).unwrap());
+        assert_eq!(
+            Some(10),
+            check_max_payees(10, Some(10), Some(50), false).unwrap()
+        );
+        let err = check_max_payees(101, Some(25), Some(50), false).unwrap_err();
+        assert_eq!(
+            "101 payees are more than the maximum of 25 per transaction, use --split to pay them in 5 transactions",
+            err.to_string()
+        );
+        assert_eq!(
+            Some(25),
+            check_max_payees(101, Some(25), Some(50), true).unwrap()
+        );
+        assert_eq!(
+            Some(50),
+            check_max_payees(101, None, Some(50), true).unwrap()
+        );
+        assert!(check_max_payees(10, Some(51), Some(50), true).is_err());
+        assert!(check_max_payees(10, Some(0), None, true).is_err());
+    }
+
+    #[test]
+    fn split_partitions() {
+        let payees: Vec<u64> = (0..100).collect();
+        let sizes = |max| -> Vec<usize> {
+            split_payees(&payees, max)
+                .iter()
+                .map(|group| group.len())
+                .collect()
+        };
+        // Evenly divided
+        assert_eq!(vec![25, 25, 25, 25], sizes(25));
+        assert_eq!(vec![50, 50], sizes(50));
+        // Unevenly divided, the last group has the rest
+        assert_eq!(vec![30, 30, 30, 10], sizes(30));
+        assert_eq!(vec![99, 1], sizes(99));
+        // Fewer payees than the maximum
+        assert_eq!(vec![100], sizes(150));
+        for max in &[1, 7, 25, 30, 99, 100, 150] {
+            let groups = split_payees(&payees, *max);
+            assert_eq!(txn_count(payees.len(), *max), groups.len() as u64);
+            assert_eq!(payees, groups.concat());
+        }
+    }
+
+    #[test]
+    fn max_per_payee() {
+        let payee = Keypair::default().public_key().clone();
+        let payment = |amount| Payment {
+            payee: payee.to_vec(),
+            amount,
+        };
+        let max = 100_000_000;
+        assert!(check_max_per_payee(&[payment(max)], max).is_ok());
+        assert!(check_max_per_payee(&[payment(max - 1)], max).is_ok());
+        let err = check_max_per_payee(&[payment(1), payment(max + 1)], max).unwrap_err();
+        assert_eq!(
+            format!(
+                "Payment of {} HNT to {} exceeds the maximum of {} HNT per payee",
+                Hnt::from_bones(max + 1),
+                payee,
+                Hnt::from_bones(max)
+            ),
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let payee = Keypair::default().public_key().clone();
+        let txn = BlockchainTxnPaymentV2 {
+            fee: 35_000,
+            payments: vec![Payment {
+                payee: payee.to_vec(),
+                amount: 1_000_000_000,
+            }],
+            payer: Keypair::default().public_key().to_vec(),
+            nonce: 42,
+            signature: Vec::new(),
+        };
+        // $1.00 per HNT
+        let summary = payment_summary(&txn, Network::MainNet, FeeSource::HntBurn, 100_000_000)
+            .expect("summary");
+        assert_eq!(
+            format!(
+                "Send {} HNT to {}, fee 35000 DC (~{} HNT burned), nonce 42, network mainnet\n",
+                Hnt::from_bones(1_000_000_000),
+                payee,
+                Hnt::from_bones(35_000_000)
+            ),
+            summary
+        );
+        let summary = payment_summary(&txn, Network::MainNet, FeeSource::DcBalance, 100_000_000)
+            .expect("summary");
+        assert!(summary.contains("fee 35000 DC from the DC balance"));
+    }
+
+    #[test]
+    fn fee_override() {
+        let err = check_fee(34_999, 35_000).unwrap_err();
+        assert_eq!(
+            "Fee of 34999 DC is below the minimum fee of 35000 DC",
+            err.to_string()
+        );
+        assert!(check_fee(35_000, 35_000).is_ok());
+        assert!(check_fee(40_000, 35_000).is_ok());
+    }
+
+    #[test]
+    fn v1_payment() {
+        let payment = |amount| Payment {
+            payee: crate::keypair::Keypair::default().public_key().to_vec(),
+            amount,
+        };
+        let mut txn = BlockchainTxnPaymentV2 {
+            fee: 0,
+            payments: vec![payment(10_000)],
+            payer: crate::keypair::Keypair::default().public_key().to_vec(),
+            nonce: 1,
+            signature: Vec::new(),
+        };
+        let v1 = to_v1(&txn).expect("v1 payment");
+        assert_eq!(txn.payments[0].payee, v1.payee);
+        assert_eq!(10_000, v1.amount);
+        assert_eq!(
+            30_000,
+            v1.txn_fee(&TxnFeeConfig::with_txn_fees(5000, 24))
+                .expect("v1 fee")
+        );
+        match v1.in_envelope().txn {
+            Some(helium_api::Txn::Payment(t)) => assert_eq!(v1, t),
+            other => panic!("unexpected envelope {:?}", other),
+        }
+
+        txn.payments.push(payment(10_000));
+        assert!(to_v1(&txn).is_err());
+        txn.payments.clear();
+        assert!(to_v1(&txn).is_err());
+    }
+
+    #[test]
+    fn fee_from_hnt_burn() {
+        assert_eq!(FeeSource::HntBurn, FeeSource::for_fee(0, 35_000));
+        assert_eq!(FeeSource::HntBurn, FeeSource::for_fee(34_999, 35_000));
+    }
+
+    #[test]
+    fn run_payment() {
+        let keypair = Keypair::default();
+        testing::use_wallet(Path::new("in-memory.key"), &Wallet::in_memory(&keypair));
+        let account = testing::account(keypair.public_key(), 10 * 100_000_000, 1_000_000);
+
+        let payee = Keypair::default().public_key().to_string();
+        let envelopes = run_pay(
+            &["-p", &format!("{}=1.5", payee), "--no-allowlist"],
+            account,
+        )
+        .expect("payment");
+
+        assert_eq!(1, envelopes.len());
+        let txn = BlockchainTxnPaymentV2::from_envelope(&envelopes[0]).expect("payment v2");
+        assert_eq!(keypair.public_key().to_vec(), txn.payer);
+        assert_eq!(6, txn.nonce);
+        assert_eq!(1, txn.payments.len());
+        assert_eq!(150_000_000, txn.payments[0].amount);
+        assert_eq!(
+            txn.fee,
+            txn.txn_fee(&TxnFeeConfig::with_txn_fees(5000, 24))
+                .expect("fee")
+        );
+        txn.verify(keypair.public_key(), &txn.signature)
+            .expect("signature");
+    }
+
+    #[test]
+    fn run_bones_payment() {
+        let keypair = Keypair::default();
+        testing::use_wallet(Path::new("in-memory.key"), &Wallet::in_memory(&keypair));
+        let account = testing::account(keypair.public_key(), 10 * 100_000_000, 1_000_000);
+
+        let payee = Keypair::default().public_key().to_string();
+        let amounts: Vec<u64> = ["1.5", "150000000b"]
+            .iter()
+            .map(|amount| {
+                let envelopes = run_pay(
+                    &["-p", &format!("{}={}", payee, amount), "--no-allowlist"],
+                    account.clone(),
+                )
+                .expect("payment");
+                let txn = BlockchainTxnPaymentV2::from_envelope(&envelopes[0]).expect("payment v2");
+                txn.payments[0].amount
+            })
+            .collect();
+        assert_eq!(vec![150_000_000, 150_000_000], amounts);
+    }
+
+    #[test]
+    fn run_split_payment() {
+        let keypair = Keypair::default();
+        testing::use_wallet(Path::new("in-memory.key"), &Wallet::in_memory(&keypair));
+        let account = testing::account(keypair.public_key(), 10 * 100_000_000, 1_000_000);
+
+        let payees: Vec<String> = (0..5)
+            .map(|_| format!("{}=1", Keypair::default().public_key()))
+            .collect();
+        let mut args = vec!["--no-allowlist", "--max-payees-per-txn", "2"];
+        for payee in &payees {
+            args.push("-p");
+            args.push(payee);
+        }
+        let err = run_pay(&args, account.clone()).expect_err("too many payees");
+        assert!(err.to_string().contains("use --split"));
+
+        args.push("--split");
+        let envelopes = run_pay(&args, account.clone()).expect("split payment");
+        let txns: Vec<BlockchainTxnPaymentV2> = envelopes
+            .iter()
+            .map(|envelope| BlockchainTxnPaymentV2::from_envelope(envelope).expect("payment v2"))
+            .collect();
+        assert_eq!(
+            vec![2, 2, 1],
+            txns.iter()
+                .map(|txn| txn.payments.len())
+                .collect::<Vec<usize>>()
+        );
+        assert_eq!(
+            vec![6, 7, 8],
+            txns.iter().map(|txn| txn.nonce).collect::<Vec<u64>>()
+        );
+        for txn in &txns {
+            assert!(txn.payments.iter().all(|p| p.amount == 100_000_000));
+            txn.verify(keypair.public_key(), &txn.signature)
+                .expect("signature");
+        }
+
+        let sweep = format!("{}=sweep", Keypair::default().public_key());
+        args.extend(&["-p", &sweep]);
+        let err = run_pay(&args, account).expect_err("split sweep");
+        assert!(err.to_string().contains("can not be split"));
+    }
+
+    /// A mock API without an oracle price or chain variables, to check when
+    /// they are fetched
+    struct NoPriceApi(MockApi);
+
+    impl SubmitTxn for NoPriceApi {
+        fn submit(&self, envelope: &BlockchainTxn) -> Result<PendingTxnStatus> {
+            self.0.submit(envelope)
+        }
+
+        fn account_nonce(&self, address: &PublicKey) -> Result<u64> {
+            self.0.account_nonce(address)
+        }
+    }
+
+    impl PayApi for NoPriceApi {
+        fn account(&self, address: &PublicKey) -> Result<Account> {
+            self.0.account(address)
+        }
+
+        fn oracle_price(&self) -> Result<OraclePrice> {
+            bail!("oracle price fetched")
+        }
+
+        fn max_payments(&self) -> Result<Option<u64>> {
+            bail!("chain variables fetched")
+        }
+
+        fn height(&self) -> Result<u64> {
+            self.0.height()
+        }
+
+        fn txn_fees(&self) -> Result<TxnFeeConfig> {
+            self.0.txn_fees()
+        }
+    }
+
+    #[test]
+    fn price_only_when_needed() {
+        let keypair = Keypair::default();
+        testing::use_wallet(Path::new("in-memory.key"), &Wallet::in_memory(&keypair));
+        let account = testing::account(keypair.public_key(), 10 * 100_000_000, 1_000_000);
+
+        let payee = Keypair::default().public_key().to_string();
+        let pay = |amount: &str, dc_balance: u64| {
+            let cmd = Cmd::from_iter(&[
+                "pay",
+                "-p",
+                &format!("{}={}", payee, amount),
+                "--no-allowlist",
+                "--allow-dust",
+            ]);
+            let opts =
+                Opts::from_iter(&["helium-wallet", "-f", "in-memory.key", "--format", "json"]);
+            let account = Account {
+                dc_balance,
+                ..account.clone()
+            };
+            cmd.run_with(opts, |_| Ok(NoPriceApi(MockApi { account })))
+        };
+        // HNT and bones amounts with the fee paid from DC need no price, and
+        // without --split or --max-payees-per-txn no chain maximum either
+        assert!(pay("1.5", 1_000_000).is_ok());
+        assert!(pay("150000000b", 1_000_000).is_ok());
+        // USD amounts and burning HNT for the fee do
+        assert!(pay("1.5usd", 1_000_000).is_err());
+        assert!(pay("1.5", 0).is_err());
+
+        // So does the dust check without --allow-dust
+        let cmd = Cmd::from_iter(&["pay", "-p", &format!("{}=1.5", payee), "--no-allowlist"]);
+        let opts = Opts::from_iter(&["helium-wallet", "-f", "in-memory.key", "--format", "json"]);
+        assert!(cmd
+            .run_with(opts, |_| Ok(NoPriceApi(MockApi { account })))
+            .is_err());
+    }
+
+    #[test]
+    fn dust_hnt_payment() {
+        let keypair = Keypair::default();
+        testing::use_wallet(Path::new("in-memory.key"), &Wallet::in_memory(&keypair));
+        let account = testing::account(keypair.public_key(), 10 * 100_000_000, 1_000_000);
+
+        // A plain HNT payment with the fee paid from DC is checked too
+        let payee = format!("{}=0.0001", Keypair::default().public_key());
+        let err =
+            run_pay(&["-p", &payee, "--no-allowlist"], account.clone()).expect_err("dust payment");
+        assert!(err.to_string().contains("use --allow-dust"));
+        assert!(run_pay(&["-p", &payee, "--no-allowlist", "--allow-dust"], account).is_ok());
+    }
+
+    #[test]
+    fn fee_in_hnt() {
+        // $1.00 per HNT
+        let price = 100_000_000;
+        let fee = |s: &str| {
+            s.parse::<FeeAmount>()
+                .expect("fee")
+                .to_dc(price)
+                .expect("dc")
+        };
+        assert_eq!(35_000, fee("35000"));
+        assert_eq!(35_000, fee("35000dc"));
+        assert_eq!(35_000, fee("0.35hnt"));
+        assert_eq!(1_000, fee(" 0.01 HNT "));
+        assert!("0.35".parse::<FeeAmount>().is_err());
+        assert!("1.5hnt"
+            .parse::<FeeAmount>()
+            .expect("fee")
+            .to_dc(0)
+            .is_err());
+
+        let keypair = Keypair::default();
+        testing::use_wallet(Path::new("in-memory.key"), &Wallet::in_memory(&keypair));
+        let account = testing::account(keypair.public_key(), 10 * 100_000_000, 1_000_000);
+        let payee = Keypair::default().public_key().to_string();
+        let fees: Vec<u64> = ["100000", "1hnt"]
+            .iter()
+            .map(|fee| {
+                let envelopes = run_pay(
+                    &[
+                        "-p",
+                        &format!("{}=1.5", payee),
+                        "--no-allowlist",
+                        "--fee",
+                        fee,
+                    ],
+                    account.clone(),
+                )
+                .expect("payment");
+                BlockchainTxnPaymentV2::from_envelope(&envelopes[0])
+                    .expect("payment v2")
+                    .fee
+            })
+            .collect();
+        assert_eq!(vec![100_000, 100_000], fees);
+    }
+
+    #[test]
+    fn run_without_hnt_burn() {
+        let keypair = Keypair::default();
+        testing::use_wallet(Path::new("in-memory.key"), &Wallet::in_memory(&keypair));
+        let payee = Keypair::default().public_key().to_string();
+        let run = |dc_balance| {
+            run_pay(
+                &[
+                    "-p",
+                    &format!("{}=1.5", payee),
+                    "--no-allowlist",
+                    "--no-hnt-burn",
+                ],
+                testing::account(keypair.public_key(), 10 * 100_000_000, dc_balance),
+            )
+        };
+        let envelopes = run(1_000_000).expect("payment from DC balance");
+        let txn = BlockchainTxnPaymentV2::from_envelope(&envelopes[0]).expect("payment v2");
+        assert!(check_dc_fee(1_000_000, txn.fee).is_ok());
+
+        let err = run(0).expect_err("no HNT burn");
+        assert!(err.to_string().contains("HNT burn disabled"));
+        assert!(check_dc_fee(txn.fee - 1, txn.fee).is_err());
+    }
+
+    #[test]
+    fn balance_projection() {
+        let account = testing::account(Keypair::default().public_key(), 10 * 100_000_000, 50_000);
+        let payment = |amount| BlockchainTxnPaymentV2 {
+            payments: vec![Payment {
+                payee: Keypair::default().public_key().to_vec(),
+                amount,
+            }],
+            fee: 35_000,
+            ..Default::default()
+        };
+        // $1.00 per HNT, 35_000 DC is 0.35 HNT
+        let price = 100_000_000;
+
+        let projection =
+            Projection::new(&account, &payment(150_000_000), FeeSource::DcBalance, price)
+                .expect("projection");
+        assert_eq!(
+            Projection {
+                balance_before: 1_000_000_000,
+                balance_after: 850_000_000,
+                dc_balance_before: 50_000,
+                dc_balance_after: 15_000,
+            },
+            projection
+        );
+        assert!(projection.check().is_ok());
+
+        let projection =
+            Projection::new(&account, &payment(150_000_000), FeeSource::HntBurn, price)
+                .expect("projection");
+        assert_eq!(815_000_000, projection.balance_after);
+        assert_eq!(50_000, projection.dc_balance_after);
+
+        let projection =
+            Projection::new(&account, &payment(1_000_000_000), FeeSource::HntBurn, price)
+                .expect("projection");
+        assert_eq!(-35_000_000, projection.balance_after);
+        let err = projection.check().expect_err("insufficient");
+        assert!(err.to_string().contains("-0.35"));
+    }
+
+    #[test]
+    fn validate_payees_file() {
+        let keypair = Keypair::default();
+        testing::use_wallet(Path::new("in-memory.key"), &Wallet::in_memory(&keypair));
+        let account = testing::account(keypair.public_key(), 10 * 100_000_000, 0);
+
+        let payee = Keypair::default().public_key().to_string();
+        let path =
+            std::env::temp_dir().join(format!("helium-wallet-validate-{}.csv", std::process::id()));
+        fs::write(
+            &path,
+            format!(
+                "address,amount\n{0},1.5\nnot-an-address,1\n{0},9\n{1},many\n",
+                payee,
+                Keypair::default().public_key()
+            ),
+        )
+        .expect("write payees");
+        let cmd = Cmd::from_iter(&[
+            "pay",
+            "--payees-file",
+            path.to_str().expect("path"),
+            "--validate-only",
+            "--no-allowlist",
+        ]);
+        let opts = Opts::from_iter(&["helium-wallet", "-f", "in-memory.key", "--format", "json"]);
+        let problems = cmd
+            .validate_with(opts, |_| Ok(MockApi { account }))
+            .expect("validation");
+        let _ = fs::remove_file(&path);
+
+        // Every problem is reported, not just the first
+        let locations: Vec<&str> = problems
+            .iter()
+            .map(|(location, _)| location.as_str())
+            .collect();
+        let line = |n| format!("{}:{}", path.display(), n);
+        assert_eq!(
+            vec![
+                line(3).as_str(),
+                line(4).as_str(),
+                line(5).as_str(),
+                "total"
+            ],
+            locations
+        );
+        assert!(problems[1].1.starts_with("Duplicate payee"));
     }
 }
@@ -0,0 +1,703 @@
+use crate::{
+    cmd::config_dir,
+    keypair::PublicKey,
+    result::{anyhow, bail, Error, Result},
+};
+use helium_api::Hnt;
+use rust_decimal::{prelude::*, Decimal};
+use std::{
+    convert::TryFrom,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// A payee and the amount of HNT to send to it, parsed from
+/// <address>=<amount> format.
+#[derive(Debug)]
+pub struct Payee {
+    pub address: PublicKey,
+    pub amount: Amount,
+}
+
+/// The amount to pay a payee. Either a given amount of HNT, a whole number
+/// of bones (e.g. "150000000b"), an amount in
+/// USD (e.g. "10usd") or DC (e.g. "100000dc") that is converted to HNT at
+/// the current oracle price, a percentage (e.g. "50%") of the balance of
+/// the payer after the fee, or "sweep" to send the remaining balance of
+/// the payer after all other payments and the fee.
+#[derive(Debug, Clone, Copy)]
+pub enum Amount {
+    Hnt(Hnt),
+    Bones(u64),
+    Usd(Decimal),
+    Dc(u64),
+    Percent(Decimal),
+    Sweep,
+}
+
+impl FromStr for Amount {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Units are matched case insensitively and may be separated from
+        // the number by whitespace, like "1.5 USD"
+        let s = s.trim().to_lowercase();
+        if s == "sweep" {
+            Ok(Self::Sweep)
+        } else if let Some(usd) = s.strip_suffix("usd") {
+            Ok(Self::Usd(usd.trim_end().parse()?))
+        } else if let Some(dc) = s.strip_suffix("dc") {
+            Ok(Self::Dc(dc.trim_end().parse()?))
+        } else if let Some(bones) = s.strip_suffix('b') {
+            // Bones are read as is, without a decimal conversion
+            let bones = bones.trim_end();
+            if bones.is_empty() || !bones.chars().all(|c| c.is_ascii_digit()) {
+                bail!("Invalid amount {}, bones must be a whole number", s);
+            }
+            Ok(Self::Bones(bones.parse()?))
+        } else if let Some(percent) = s.strip_suffix('%') {
+            let percent: Decimal = percent.trim_end().parse()?;
+            if percent.is_sign_negative() || percent > Decimal::from(100) {
+                bail!("Invalid percentage {}%", percent);
+            }
+            Ok(Self::Percent(percent))
+        } else {
+            Ok(Self::Hnt(s.parse()?))
+        }
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Hnt(hnt) => write!(f, "{}", hnt),
+            Self::Bones(bones) => write!(f, "{}b", bones),
+            Self::Usd(usd) => write!(f, "{}usd", usd),
+            Self::Dc(dc) => write!(f, "{}dc", dc),
+            Self::Percent(percent) => write!(f, "{}%", percent),
+            Self::Sweep => f.write_str("sweep"),
+        }
+    }
+}
+
+impl Amount {
+    pub fn is_sweep(&self) -> bool {
+        matches!(self, Self::Sweep)
+    }
+
+    /// Returns the percentage of the balance for a percent amount
+    pub fn percent(&self) -> Option<Decimal> {
+        match self {
+            Self::Percent(percent) => Some(*percent),
+            _ => None,
+        }
+    }
+
+    /// Whether the amount needs an oracle price to convert it to HNT
+    pub fn needs_price(&self) -> bool {
+        matches!(self, Self::Usd(_) | Self::Dc(_))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Self::Hnt(hnt) => hnt.to_bones() == 0,
+            Self::Bones(bones) => *bones == 0,
+            Self::Usd(usd) => usd.is_zero(),
+            Self::Dc(dc) => *dc == 0,
+            Self::Percent(percent) => percent.is_zero(),
+            Self::Sweep => false,
+        }
+    }
+
+    /// Returns the amount in bones, converting USD and DC amounts at the
+    /// given oracle price. Percent and sweep amounts depend on the balance
+    /// and fee, have no fixed amount and return None.
+    pub fn to_bones(&self, oracle_price: Option<u64>) -> Result<Option<u64>> {
+        let price = || match oracle_price {
+            Some(price) => Ok(price),
+            None => bail!("Oracle price required to convert {}", self),
+        };
+        match self {
+            Self::Hnt(hnt) => Ok(Some(hnt.to_bones())),
+            Self::Bones(bones) => Ok(Some(*bones)),
+            Self::Usd(usd) => Ok(Some(usd_to_hnt_bones(*usd, price()?)?)),
+            Self::Dc(dc) => Ok(Some(dc_to_hnt_bones(*dc, price()?)?)),
+            Self::Percent(_) | Self::Sweep => Ok(None),
+        }
+    }
+}
+
+/// Converts an amount of DC to the amount of HNT in bones that is burned
+/// for it at a given oracle price, rounded up. A DC is worth $0.00001 and
+/// the oracle price is given in 1/100_000_000 USD per HNT.
+pub fn dc_to_hnt_bones(dc: u64, oracle_price: u64) -> Result<u64> {
+    if oracle_price == 0 {
+        bail!("Invalid oracle price 0");
+    }
+    let bones = (dc as u128 * 100_000_000_000 + oracle_price as u128 - 1) / oracle_price as u128;
+    Ok(bones as u64)
+}
+
+/// Converts an amount of HNT in bones to the DC it is worth at a given
+/// oracle price, rounded down
+pub fn hnt_bones_to_dc(bones: u64, oracle_price: u64) -> Result<u64> {
+    if oracle_price == 0 {
+        bail!("Invalid oracle price 0");
+    }
+    let dc = bones as u128 * oracle_price as u128 / 100_000_000_000;
+    u64::try_from(dc).map_err(|_| anyhow!("Invalid HNT amount {}", Hnt::from_bones(bones)))
+}
+
+/// A transaction fee override. A plain integer is a fee in DC, an amount
+/// with an "hnt" suffix (e.g. "0.01hnt") is converted to DC at the current
+/// oracle price.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeAmount {
+    Dc(u64),
+    Hnt(Hnt),
+}
+
+impl FromStr for FeeAmount {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase();
+        match s.strip_suffix("hnt") {
+            Some(hnt) => Ok(Self::Hnt(hnt.trim_end().parse()?)),
+            None => Ok(Self::Dc(
+                s.strip_suffix("dc").unwrap_or(&s).trim_end().parse()?,
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FeeAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Dc(dc) => write!(f, "{} DC", dc),
+            Self::Hnt(hnt) => write!(f, "{} HNT", hnt),
+        }
+    }
+}
+
+impl FeeAmount {
+    /// Returns the fee in DC, converting an HNT fee at the given oracle
+    /// price
+    pub fn to_dc(&self, oracle_price: u64) -> Result<u64> {
+        match self {
+            Self::Dc(dc) => Ok(*dc),
+            Self::Hnt(hnt) => hnt_bones_to_dc(hnt.to_bones(), oracle_price),
+        }
+    }
+}
+
+/// The USD value of an amount of DC, a DC is worth $0.00001
+pub fn dc_to_usd(dc: u64) -> Decimal {
+    Decimal::new(dc as i64, 5)
+}
+
+/// Rounds a USD value to the given number of decimal places, rounding
+/// half up (away from zero). All USD values that are shown or exported
+/// are rounded this way.
+pub fn round_usd(usd: Decimal, decimals: u32) -> Decimal {
+    usd.round_dp_with_strategy(decimals, RoundingStrategy::RoundHalfUp)
+}
+
+/// Formats a USD value rounded to exactly the given number of decimal
+/// places
+pub fn format_usd(usd: Decimal, decimals: u32) -> String {
+    format!("{:.*}", decimals as usize, round_usd(usd, decimals))
+}
+
+/// Calculates the given percentage of a balance in bones, rounded down
+pub fn percent_of_bones(percent: Decimal, balance: u64) -> Result<u64> {
+    (Decimal::from(balance) * percent / Decimal::from(100))
+        .floor()
+        .to_u64()
+        .ok_or_else(|| anyhow!("Invalid percentage {}%", percent))
+}
+
+/// Converts an amount of USD to HNT in bones at a given oracle price,
+/// rounded down.
+pub fn usd_to_hnt_bones(usd: Decimal, oracle_price: u64) -> Result<u64> {
+    if oracle_price == 0 {
+        bail!("Invalid oracle price 0");
+    }
+    (usd * Decimal::from(10_000_000_000_000_000u64) / Decimal::from(oracle_price))
+        .floor()
+        .to_u64()
+        .ok_or_else(|| anyhow!("Invalid USD amount {}", usd))
+}
+
+/// Why a payee in <address>=<amount> format could not be parsed
+#[derive(Debug)]
+pub enum PayeeError {
+    /// The `=` between the address and the amount is missing
+    MissingSeparator(String),
+    /// The address is not a valid address
+    Address(String, Error),
+    /// The amount is not a valid amount
+    Amount(String, Error),
+}
+
+impl fmt::Display for PayeeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingSeparator(s) => {
+                write!(f, "Invalid payee {}, expected <address>=<amount>", s)
+            }
+            Self::Address(address, err) => write!(f, "Invalid payee address {}: {}", address, err),
+            Self::Amount(amount, err) => write!(f, "Invalid amount {}: {}", amount, err),
+        }
+    }
+}
+
+impl std::error::Error for PayeeError {}
+
+impl FromStr for Payee {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s, DecimalSeparator::Point)
+    }
+}
+
+impl Payee {
+    /// Parses a payee in <address>=<amount> format with amounts using the
+    /// given decimal separator. Failures are a [`PayeeError`].
+    pub fn parse(s: &str, separator: DecimalSeparator) -> Result<Self> {
+        let pos = s
+            .find('=')
+            .ok_or_else(|| PayeeError::MissingSeparator(s.trim().to_string()))?;
+        // Stray whitespace from shell quoting or file input is ignored
+        // around the address and the amount
+        let address = s[..pos].trim();
+        let amount = s[pos + 1..].trim();
+        Ok(Payee {
+            address: parse_address(address)
+                .map_err(|err| PayeeError::Address(address.to_string(), err))?,
+            amount: normalize_decimal(amount, separator)
+                .and_then(|amount| amount.parse())
+                .map_err(|err| PayeeError::Amount(amount.to_string(), err))?,
+        })
+    }
+
+    /// Whether the payee is paid an explicit zero amount. A sweep payee
+    /// is never considered zero.
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+}
+
+/// Parses a base58 encoded address, which checks its checksum, length,
+/// key type and network
+pub fn parse_address(address: &str) -> Result<PublicKey> {
+    Ok(address.parse()?)
+}
+
+/// The decimal separator used in amounts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecimalSeparator {
+    Point,
+    Comma,
+}
+
+impl Default for DecimalSeparator {
+    fn default() -> Self {
+        Self::Point
+    }
+}
+
+impl FromStr for DecimalSeparator {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "." | "point" => Ok(Self::Point),
+            "," | "comma" => Ok(Self::Comma),
+            _ => bail!("Invalid decimal separator {}, expected \".\" or \",\"", s),
+        }
+    }
+}
+
+impl fmt::Display for DecimalSeparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Point => f.write_str("."),
+            Self::Comma => f.write_str(","),
+        }
+    }
+}
+
+/// Normalizes an amount to use a decimal point. With a decimal point
+/// separator a single comma is accepted as the decimal point as well, like
+/// "1,5", unless it is followed by exactly three digits, like "1,500",
+/// which could also be a thousands separator. With a decimal comma
+/// separator points are rejected rather than guessing whether they
+/// separate thousands.
+pub fn normalize_decimal(amount: &str, separator: DecimalSeparator) -> Result<String> {
+    let commas = amount.matches(',').count();
+    if commas == 0 {
+        if separator == DecimalSeparator::Comma && amount.contains('.') {
+            bail!(
+                "Invalid amount {}, use a comma as the decimal separator",
+                amount
+            );
+        }
+        return Ok(amount.to_string());
+    }
+    if commas > 1 || amount.contains('.') {
+        bail!(
+            "Invalid amount {}, thousands separators are not supported",
+            amount
+        );
+    }
+    if separator == DecimalSeparator::Point {
+        let decimals = amount
+            .split(',')
+            .nth(1)
+            .map_or(0, |s| s.chars().take_while(char::is_ascii_digit).count());
+        if decimals == 3 {
+            bail!(
+                "Ambiguous amount {}, use --decimal-separator , to read the comma as a decimal separator",
+                amount
+            );
+        }
+    }
+    Ok(amount.replacen(',', ".", 1))
+}
+
+/// Checks that none of the given payees are paid a zero amount. A zero
+/// amount payment still costs a fee and is almost always a mistake.
+pub fn check_zero_amounts(payees: &[Payee]) -> Result {
+    let zero: Vec<String> = payees
+        .iter()
+        .filter(|payee| payee.is_zero())
+        .map(|payee| payee.address.to_string())
+        .collect();
+    if !zero.is_empty() {
+        bail!("Zero amount payment to: {}", zero.join(", "));
+    }
+    Ok(())
+}
+
+/// Reads payees from a CSV file with an address and an amount column.
+/// Returns the location and the <address>=<amount> text of every row, so
+/// problems can be reported by line. Lines starting with '#' and a header
+/// row starting with "address" are skipped.
+pub fn read_payees_file(path: &Path) -> Result<Vec<(String, String)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_path(path)
+        .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        if rows.is_empty()
+            && record
+                .get(0)
+                .map_or(false, |field| field.eq_ignore_ascii_case("address"))
+        {
+            continue;
+        }
+        let line = record.position().map_or(0, |position| position.line());
+        let fields: Vec<&str> = record.iter().collect();
+        rows.push((format!("{}:{}", path.display(), line), fields.join("=")));
+    }
+    Ok(rows)
+}
+
+/// The location of the payee allowlist in the configuration directory
+pub fn allowlist_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("allowed_payees.txt"))
+}
+
+/// Reads the payee allowlist at the given path. Returns None if there is
+/// no allowlist.
+pub fn read_allowlist(path: &Path) -> Result<Option<Vec<PublicKey>>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(parse_allowlist(&contents).map_err(|e| {
+            anyhow!("Invalid payee allowlist {}: {}", path.display(), e)
+        })?)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Parses an allowlist with one address per line. Empty lines and lines
+/// starting with '#' are ignored.
+fn parse_allowlist(contents: &str) -> Result<Vec<PublicKey>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.parse()
+                .map_err(|_| anyhow!("invalid address {}", line))
+        })
+        .collect()
+}
+
+pub fn check_allowlist(payees: &[Payee], allowed: &[PublicKey]) -> Result {
+    for payee in payees {
+        if !allowed.contains(&payee.address) {
+            bail!("Payee {} is not in the payee allowlist", payee.address);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn parse_payee() {
+        let address = Keypair::default().public_key().to_string();
+        let payee: Payee = format!("{}=1.5", address).parse().expect("payee");
+        assert_eq!(address, payee.address.to_string());
+        assert_eq!(Some(150_000_000), payee.amount.to_bones(None).unwrap());
+
+        let payee: Payee = format!("{}=0.00000001", address).parse().expect("payee");
+        assert_eq!(Some(1), payee.amount.to_bones(None).unwrap());
+
+        let payee: Payee = format!("{}=sweep", address).parse().expect("payee");
+        assert!(payee.amount.is_sweep());
+        assert_eq!(None, payee.amount.to_bones(None).unwrap());
+    }
+
+    #[test]
+    fn parse_padded_payee() {
+        let address = Keypair::default().public_key().to_string();
+        let payee: Payee = format!(" {} = 1.5 ", address).parse().expect("payee");
+        assert_eq!(address, payee.address.to_string());
+        assert_eq!(Some(150_000_000), payee.amount.to_bones(None).unwrap());
+
+        let payee: Payee = format!("\t{}=\tSweep\n", address).parse().expect("payee");
+        assert!(payee.amount.is_sweep());
+
+        let usd: Amount = " 10 USD ".parse().expect("usd amount");
+        assert!(matches!(usd, Amount::Usd(usd) if usd == Decimal::from(10)));
+        let dc: Amount = "100 DC".parse().expect("dc amount");
+        assert!(matches!(dc, Amount::Dc(100)));
+        let percent: Amount = "50 %".parse().expect("percent amount");
+        assert_eq!(Some(Decimal::from(50)), percent.percent());
+
+        // The address itself is case sensitive
+        assert!(format!("{}=1", address.to_lowercase())
+            .parse::<Payee>()
+            .is_err());
+    }
+
+    #[test]
+    fn decimal_separators() {
+        let point = DecimalSeparator::Point;
+        let comma = DecimalSeparator::Comma;
+        assert_eq!("1.5", normalize_decimal("1.5", point).unwrap());
+        assert_eq!("1.5", normalize_decimal("1,5", point).unwrap());
+        assert_eq!("10.25usd", normalize_decimal("10,25usd", point).unwrap());
+        // A comma followed by three digits may separate thousands
+        assert!(normalize_decimal("1,500", point).is_err());
+        assert_eq!("1.500", normalize_decimal("1,500", comma).unwrap());
+        assert_eq!("sweep", normalize_decimal("sweep", comma).unwrap());
+        assert!(normalize_decimal("1.5", comma).is_err());
+        assert!(normalize_decimal("1,000,5", comma).is_err());
+        assert!(normalize_decimal("1.000,5", comma).is_err());
+
+        let address = Keypair::default().public_key().to_string();
+        let payee: Payee = format!("{}=1,5", address).parse().expect("payee");
+        assert_eq!(Some(150_000_000), payee.amount.to_bones(None).unwrap());
+        let payee = Payee::parse(&format!("{}=1,500", address), comma).expect("payee");
+        assert_eq!(Some(150_000_000), payee.amount.to_bones(None).unwrap());
+        assert!(format!("{}=1,500", address).parse::<Payee>().is_err());
+        assert_eq!(comma, ",".parse().expect("separator"));
+    }
+
+    #[test]
+    fn zero_amounts() {
+        let address = Keypair::default().public_key().to_string();
+        let zero: Payee = format!("{}=0", address).parse().expect("payee");
+        let one: Payee = format!("{}=1", address).parse().expect("payee");
+        let sweep: Payee = format!("{}=sweep", address).parse().expect("payee");
+        assert!(zero.is_zero());
+        assert!(!one.is_zero());
+        // A sweep payee starts at zero but is not an explicit zero payment
+        assert!(!sweep.is_zero());
+        assert!(check_zero_amounts(&[one, sweep]).is_ok());
+        assert!(check_zero_amounts(&[zero]).is_err());
+    }
+
+    #[test]
+    fn convert_amounts() {
+        // $2.00 per HNT
+        let price = Some(200_000_000);
+        let usd: Amount = "10usd".parse().expect("usd amount");
+        assert!(usd.needs_price());
+        assert_eq!(Some(500_000_000), usd.to_bones(price).unwrap());
+        assert!(usd.to_bones(None).is_err());
+
+        // 100_000 DC is $1.00
+        let dc: Amount = "100000dc".parse().expect("dc amount");
+        assert!(dc.needs_price());
+        assert_eq!(Some(50_000_000), dc.to_bones(price).unwrap());
+
+        assert_eq!("10usd", usd.to_string());
+        assert_eq!("100000dc", dc.to_string());
+        assert!("tenusd".parse::<Amount>().is_err());
+        assert!("-1usd".parse::<Amount>().unwrap().to_bones(price).is_err());
+        assert!("0usd".parse::<Amount>().unwrap().is_zero());
+    }
+
+    #[test]
+    fn payee_errors() {
+        let address = Keypair::default().public_key().to_string();
+        let err = address.parse::<Payee>().expect_err("missing separator");
+        assert!(matches!(
+            err.downcast_ref::<PayeeError>(),
+            Some(PayeeError::MissingSeparator(s)) if s == &address
+        ));
+
+        let err = "not-an-address=1"
+            .parse::<Payee>()
+            .expect_err("bad address");
+        assert!(matches!(
+            err.downcast_ref::<PayeeError>(),
+            Some(PayeeError::Address(a, _)) if a == "not-an-address"
+        ));
+
+        let err = format!("{}= ten ", address)
+            .parse::<Payee>()
+            .expect_err("bad amount");
+        assert!(matches!(
+            err.downcast_ref::<PayeeError>(),
+            Some(PayeeError::Amount(a, _)) if a == "ten"
+        ));
+        // Decimal separator problems are amount errors as well
+        let err = format!("{}=1,500", address)
+            .parse::<Payee>()
+            .expect_err("ambiguous amount");
+        assert!(matches!(
+            err.downcast_ref::<PayeeError>(),
+            Some(PayeeError::Amount(a, _)) if a == "1,500"
+        ));
+        assert!(err.to_string().contains("--decimal-separator"));
+    }
+
+    #[test]
+    fn bone_amounts() {
+        let bones: Amount = "150000000b".parse().expect("bones amount");
+        assert!(matches!(bones, Amount::Bones(150_000_000)));
+        assert!(!bones.needs_price());
+        assert_eq!("150000000b", bones.to_string());
+        let hnt: Amount = "1.5".parse().expect("hnt amount");
+        assert_eq!(hnt.to_bones(None).unwrap(), bones.to_bones(None).unwrap());
+        assert!(matches!(" 1 B ".parse::<Amount>(), Ok(Amount::Bones(1))));
+        assert!("0b".parse::<Amount>().unwrap().is_zero());
+        assert!("1.5b".parse::<Amount>().is_err());
+        assert!("-1b".parse::<Amount>().is_err());
+        assert!("+1b".parse::<Amount>().is_err());
+        assert!("b".parse::<Amount>().is_err());
+        assert!("18446744073709551616b".parse::<Amount>().is_err());
+    }
+
+    #[test]
+    fn payees_file() {
+        let address = Keypair::default().public_key().to_string();
+        let path =
+            std::env::temp_dir().join(format!("helium-wallet-payees-{}.csv", std::process::id()));
+        fs::write(
+            &path,
+            format!(
+                "address,amount\n{0}, 1.5\n{0},\"1,5\"\n{0}\n# rent\n",
+                address
+            ),
+        )
+        .expect("write payees");
+        let rows = read_payees_file(&path).expect("payees");
+        let _ = fs::remove_file(&path);
+        let locations: Vec<String> = (2..=4)
+            .map(|line| format!("{}:{}", path.display(), line))
+            .collect();
+        assert_eq!(
+            vec![
+                (locations[0].clone(), format!("{}=1.5", address)),
+                (locations[1].clone(), format!("{}=1,5", address)),
+                (locations[2].clone(), address.clone()),
+            ],
+            rows
+        );
+        assert!(Payee::parse(&rows[1].1, DecimalSeparator::Comma).is_ok());
+        assert!(Payee::parse(&rows[2].1, DecimalSeparator::Point).is_err());
+    }
+
+    #[test]
+    fn usd_formatting() {
+        let usd = |s| Decimal::from_str(s).expect("decimal");
+        assert_eq!("1.50", format_usd(usd("1.5"), 2));
+        // Half up, not to even
+        assert_eq!("0.13", format_usd(usd("0.125"), 2));
+        assert_eq!("0.3", format_usd(usd("0.25"), 1));
+        assert_eq!("-0.01", format_usd(usd("-0.005"), 2));
+        assert_eq!("2", format_usd(usd("1.5"), 0));
+        assert_eq!("0.35", format_usd(dc_to_usd(35_000), 2));
+    }
+
+    #[test]
+    fn percent_amounts() {
+        let half: Amount = "50%".parse().expect("percent amount");
+        assert_eq!(Some(Decimal::from(50)), half.percent());
+        assert!(!half.needs_price());
+        assert_eq!(None, half.to_bones(None).unwrap());
+        assert_eq!("50%", half.to_string());
+        assert!("0%".parse::<Amount>().unwrap().is_zero());
+        assert!("100.5%".parse::<Amount>().is_err());
+        assert!("-1%".parse::<Amount>().is_err());
+        assert!("%".parse::<Amount>().is_err());
+
+        assert_eq!(
+            50_000_000,
+            percent_of_bones(Decimal::from(50), 100_000_000).unwrap()
+        );
+        // Fractions of a bone are rounded down
+        assert_eq!(
+            33,
+            percent_of_bones(Decimal::from_str("33.3").unwrap(), 101).unwrap()
+        );
+        assert_eq!(0, percent_of_bones(Decimal::from(50), 0).unwrap());
+    }
+
+    #[test]
+    fn parse_invalid_payee() {
+        let address = Keypair::default().public_key().to_string();
+        // Missing separator
+        assert!(address.parse::<Payee>().is_err());
+        // Invalid amount
+        assert!(format!("{}=abc", address).parse::<Payee>().is_err());
+        assert!(format!("{}=", address).parse::<Payee>().is_err());
+        // Invalid address
+        assert!("not_an_address=1".parse::<Payee>().is_err());
+    }
+
+    #[test]
+    fn allowlist() {
+        let allowed = Keypair::default().public_key().clone();
+        let other = Keypair::default().public_key().clone();
+        let contents = format!("# treasury\n\n  {}  \n", allowed);
+        let list = parse_allowlist(&contents).expect("allowlist");
+        assert_eq!(vec![allowed.clone()], list);
+        assert!(parse_allowlist("not an address").is_err());
+
+        let payee = |address: &PublicKey| Payee {
+            address: address.clone(),
+            amount: Amount::Dc(1),
+        };
+        assert!(check_allowlist(&[payee(&allowed)], &list).is_ok());
+        let err = check_allowlist(&[payee(&allowed), payee(&other)], &list).unwrap_err();
+        assert!(err.to_string().contains(&other.to_string()));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    cmd::write_atomic,
+    keypair::Network,
+    result::{anyhow, Result},
+    traits::{TxnPayer, B64},
+};
+use helium_api::{BlockchainTxn, PendingTxnStatus, Txn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A record of a submitted transaction that is written to a file so its
+/// status can be tracked later. The receipt includes the full signed
+/// transaction so it can be submitted again if needed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Receipt {
+    /// The hash the API returned for the transaction
+    pub hash: String,
+    /// The network of the payer of the transaction, if it has one
+    pub network: Option<String>,
+    /// The account nonce the transaction uses, if any
+    pub nonce: Option<u64>,
+    /// Seconds since the unix epoch the transaction was submitted at
+    pub time: u64,
+    /// The base64 encoded signed transaction
+    pub txn: String,
+}
+
+impl Receipt {
+    pub fn new(envelope: &BlockchainTxn, status: &PendingTxnStatus) -> Result<Self> {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let nonce = match &envelope.txn {
+            Some(Txn::Payment(t)) => Some(t.nonce),
+            Some(Txn::PaymentV2(t)) => Some(t.nonce),
+            Some(Txn::TokenBurn(t)) => Some(t.nonce),
+            Some(Txn::CreateHtlc(t)) => Some(t.nonce),
+            _ => None,
+        };
+        Ok(Self {
+            hash: status.hash.clone(),
+            network: envelope.payer()?.map(|payer| payer.network.to_string()),
+            nonce,
+            time,
+            txn: envelope.to_b64()?,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path)
+            .map_err(|e| anyhow!("Failed to open receipt {}: {}", path.display(), e))?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Writes the receipt to the given path. The file is replaced
+    /// atomically so an interrupted write never leaves a partial receipt.
+    pub fn write(&self, path: &Path) -> Result {
+        write_atomic(path, &serde_json::to_vec_pretty(self)?)
+    }
+
+    /// The network of the transaction, if the receipt records one
+    pub fn network(&self) -> Result<Option<Network>> {
+        self.network
+            .as_ref()
+            .map(|network| network.parse())
+            .transpose()
+            .map_err(|_| anyhow!("Invalid network in receipt"))
+    }
+
+    /// The signed transaction
+    pub fn envelope(&self) -> Result<BlockchainTxn> {
+        BlockchainTxn::from_b64(&self.txn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::Keypair, traits::TxnEnvelope};
+    use helium_api::BlockchainTxnPaymentV2;
+
+    #[test]
+    fn receipt_roundtrip() {
+        let keypair = Keypair::default();
+        let envelope = BlockchainTxnPaymentV2 {
+            payer: keypair.public_key().to_vec(),
+            nonce: 7,
+            ..Default::default()
+        }
+        .in_envelope();
+        let receipt = Receipt::new(
+            &envelope,
+            &PendingTxnStatus {
+                hash: "hash".to_string(),
+            },
+        )
+        .expect("receipt");
+        assert_eq!(Some(7), receipt.nonce);
+        assert_eq!(
+            Some(keypair.public_key().network),
+            receipt.network().unwrap()
+        );
+        assert_eq!(envelope, receipt.envelope().expect("envelope"));
+
+        let path =
+            std::env::temp_dir().join(format!("helium-wallet-receipt-{}.json", std::process::id()));
+        receipt.write(&path).expect("write");
+        let read = Receipt::read(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(receipt, read.expect("read"));
+    }
+}
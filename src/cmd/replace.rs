@@ -0,0 +1,253 @@
+use crate::{
+    api::Client,
+    cmd::{
+        api_url, decrypt_wallet, get_txn_fees, load_wallet, print_footer, print_json, print_table,
+        status_json, status_str, submit_txn, Opts, OutputFormat,
+    },
+    keypair::{Network, PublicKey},
+    result::{bail, Result},
+    traits::{TxnEnvelope, TxnSign, B64},
+    txn::TxnBuilder,
+};
+use dialoguer::Confirm;
+use helium_api::{BlockchainTxn, PendingTxnStatus};
+use serde_json::{json, Value};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Replace a stuck pending transaction of the wallet by submitting a
+/// minimal payment of one bone at its nonce with a higher fee. The pending
+/// transaction with the given nonce is looked up first and the new fee has
+/// to outbid it. The original transaction is not rebuilt, the replacement
+/// only uses up its nonce. The chain rejects payments to the payer itself,
+/// so like fill-nonce the payment goes to the given payee. This only helps
+/// if the chain accepts a higher fee transaction for a nonce that is
+/// already pending. If the original clears first the replacement fails,
+/// so committing asks for confirmation.
+pub struct Cmd {
+    /// The nonce of the pending transaction to replace
+    #[structopt(long)]
+    nonce: u64,
+
+    /// The new fee in DC. Must be higher than the fee of the pending
+    /// transaction and at least the minimum fee of the replacement.
+    #[structopt(long)]
+    fee: u64,
+
+    /// Address to send the one bone payment to. Must not be the wallet
+    /// address
+    #[structopt(long)]
+    payee: PublicKey,
+
+    /// Commit the replacement to the API
+    #[structopt(long)]
+    commit: bool,
+}
+
+/// The pending transaction that is replaced
+#[derive(Debug, PartialEq)]
+struct Pending {
+    hash: String,
+    fee: u64,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        if self.payee == wallet.public_key {
+            bail!("The payee can not be the wallet itself");
+        }
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
+        let pending = match find_pending(&client, &wallet.public_key, self.nonce)? {
+            Some(pending) => pending,
+            None => bail!(
+                "No pending transaction with nonce {} for {}",
+                self.nonce,
+                wallet.public_key
+            ),
+        };
+
+        let fee_config = get_txn_fees(&client, &opts.submit)?;
+        let minimum_fee = TxnBuilder::payment(&[(self.payee.clone(), 1)])
+            .nonce(self.nonce)
+            .fee_config(&fee_config)
+            .build(&wallet.public_key)?
+            .fee;
+        check_fee(self.fee, pending.fee, minimum_fee)?;
+
+        eprintln!(
+            "Warning: the replacement only takes effect if the chain accepts it in place of \
+             pending transaction {}. If the pending transaction clears first the replacement \
+             fails and the original transaction stands.",
+            pending.hash
+        );
+        if self.commit
+            && !opts.submit.dry_run
+            && !Confirm::new()
+                .with_prompt(format!(
+                    "Replace the pending transaction with nonce {} at a fee of {} DC?",
+                    self.nonce, self.fee
+                ))
+                .interact()?
+        {
+            bail!("Not replacing transaction");
+        }
+
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
+        let mut txn = TxnBuilder::payment(&[(self.payee.clone(), 1)])
+            .nonce(self.nonce)
+            .fee(self.fee)
+            .build(keypair.public_key())?;
+        txn.signature = txn.sign(&keypair)?;
+        let envelope = txn.in_envelope();
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            self.nonce,
+            self.fee,
+            &pending,
+            &envelope,
+            &status,
+            wallet.public_key.network,
+            opts.format,
+        )
+    }
+}
+
+/// Looks up the pending transaction of the given payer with the given
+/// nonce.
+fn find_pending(client: &Client, payer: &PublicKey, nonce: u64) -> Result<Option<Pending>> {
+    let address = payer.to_string();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = client.get_account_pending_txns(&address, cursor.as_deref())?;
+        if let Some(pending) = match_pending(&page.data, &address, nonce) {
+            return Ok(Some(pending));
+        }
+        match page.cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(None),
+        }
+    }
+}
+
+/// Finds the entry for the given payer and nonce in a page of pending
+/// transactions. Transactions that already failed are skipped.
+fn match_pending(entries: &[Value], payer: &str, nonce: u64) -> Option<Pending> {
+    entries
+        .iter()
+        .find(|pending| {
+            pending["status"].as_str() != Some("failed")
+                && pending["txn"]["payer"].as_str() == Some(payer)
+                && pending["txn"]["nonce"].as_u64() == Some(nonce)
+        })
+        .map(|pending| Pending {
+            hash: pending["hash"].as_str().unwrap_or("unknown").to_string(),
+            fee: pending["txn"]["fee"].as_u64().unwrap_or(0),
+        })
+}
+
+/// Checks that the new fee outbids the pending transaction and covers the
+/// minimum fee of the replacement.
+fn check_fee(fee: u64, pending_fee: u64, minimum_fee: u64) -> Result {
+    if fee <= pending_fee {
+        bail!(
+            "Fee of {} DC must be higher than the pending fee of {} DC",
+            fee,
+            pending_fee
+        );
+    }
+    if fee < minimum_fee {
+        bail!(
+            "Fee of {} DC is below the minimum fee of {} DC",
+            fee,
+            minimum_fee
+        );
+    }
+    Ok(())
+}
+
+fn print_txn(
+    nonce: u64,
+    fee: u64,
+    pending: &Pending,
+    envelope: &BlockchainTxn,
+    status: &Option<PendingTxnStatus>,
+    network: Network,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Network", network],
+                ["Nonce", nonce],
+                ["Replaced", pending.hash],
+                ["Replaced fee (DC)", pending.fee],
+                ["Fee (DC)", fee],
+                ["Hash", status_str(status)]
+            ))?;
+            print_footer(status)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "network": network.to_string(),
+                "nonce": nonce,
+                "replaced": pending.hash,
+                "replaced_fee": pending.fee,
+                "fee": fee,
+                "txn": envelope.to_b64()?,
+                "hash": status_json(status),
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn pending_at_nonce() {
+        let payer = Keypair::default().public_key().to_string();
+        let entries = vec![
+            json!({
+                "hash": "failed",
+                "status": "failed",
+                "txn": {"payer": payer, "nonce": 7, "fee": 30_000}
+            }),
+            json!({
+                "hash": "other",
+                "status": "pending",
+                "txn": {"payer": payer, "nonce": 8, "fee": 30_000}
+            }),
+            json!({
+                "hash": "stuck",
+                "status": "pending",
+                "txn": {"payer": payer, "nonce": 7, "fee": 35_000}
+            }),
+        ];
+        assert_eq!(
+            Some(Pending {
+                hash: "stuck".to_string(),
+                fee: 35_000
+            }),
+            match_pending(&entries, &payer, 7)
+        );
+        assert_eq!(None, match_pending(&entries, &payer, 9));
+        let other = Keypair::default().public_key().to_string();
+        assert_eq!(None, match_pending(&entries, &other, 7));
+    }
+
+    #[test]
+    fn replacement_fee() {
+        assert!(check_fee(40_000, 35_000, 35_000).is_ok());
+        // Must outbid the pending transaction
+        assert!(check_fee(35_000, 35_000, 30_000).is_err());
+        // Must cover the minimum fee
+        assert!(check_fee(30_000, 20_000, 35_000).is_err());
+    }
+}
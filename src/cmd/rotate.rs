@@ -0,0 +1,169 @@
+use crate::{
+    cmd::{
+        api_url, check_logged_nonce, fetch_payer_account, get_txn_fees, get_wallet_password,
+        interrupt::Steps, load_wallet, open_output_file, pay::calculate_remaining_hnt,
+        print_footer, print_json, print_table, status_json, status_str, submit_txn,
+        wait_for_confirmations, Encoding, Opts, OutputFormat,
+    },
+    format::{self, Format},
+    keypair::{Keypair, Network},
+    pwhash::PwHash,
+    result::{bail, Result},
+    traits::{TxnEnvelope, TxnSign},
+    txn::TxnBuilder,
+    wallet::Wallet,
+};
+use helium_api::{BlockchainTxnPaymentV2, Hnt, PendingTxnStatus};
+use serde_json::json;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Rotate a wallet to a new key. A new basic wallet is created with the
+/// password of the current wallet and the entire balance of the current
+/// wallet is swept to the new wallet. The new wallet file is written
+/// after the balance and fee are checked and before any funds are moved.
+pub struct Cmd {
+    /// Output file to store the new wallet in
+    #[structopt(short, long)]
+    output: PathBuf,
+
+    /// Overwrite an existing file
+    #[structopt(long)]
+    force: bool,
+
+    /// Commit the sweep payment to the API
+    #[structopt(long)]
+    commit: bool,
+
+    /// Wait for the committed sweep payment to clear before completing
+    #[structopt(long, requires = "commit")]
+    wait: bool,
+
+    /// Number of blocks the sweep payment has to be confirmed by with
+    /// --wait. The block with the payment is the first confirmation
+    #[structopt(long, requires = "wait", default_value = "1")]
+    confirmations: u64,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        let password = get_wallet_password(&wallet)?;
+        let keypair = wallet.decrypt(password.as_bytes())?;
+
+        let new_keypair = Keypair::generate(keypair.public_key().tag());
+        let format = format::Basic {
+            pwhash: PwHash::argon2id13_default(),
+        };
+        let new_wallet = Wallet::encrypt(&new_keypair, password.as_bytes(), Format::Basic(format))?;
+
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
+        let account = fetch_payer_account(&client, keypair.public_key())?;
+        if account.balance == 0 {
+            bail!("No balance to sweep to the new wallet");
+        }
+        check_logged_nonce(
+            keypair.public_key(),
+            account.speculative_nonce + 1,
+            self.commit,
+            &opts.submit,
+        )?;
+
+        let mut txn = TxnBuilder::payment(&[(new_keypair.public_key().clone(), account.balance)])
+            .nonce(account.speculative_nonce + 1)
+            .fee_config(&get_txn_fees(&client, &opts.submit)?)
+            .build(keypair.public_key())?;
+        txn.payments[0].amount = calculate_remaining_hnt(
+            account.balance,
+            account.dc_balance,
+            txn.fee,
+            client.get_oracle_price()?.price,
+            0,
+        )?;
+        txn.signature = txn.sign(&keypair)?;
+
+        // The new wallet is only written once there is a balance to sweep
+        let mut steps = Steps::new(&[("wallet", "created"), ("sweep", "submitted")]);
+        steps.run("wallet", || {
+            let mut writer = open_output_file(&self.output, !self.force)?;
+            new_wallet.write(&mut writer)
+        })?;
+
+        let envelope = txn.in_envelope();
+        let status = steps.run("sweep", || {
+            submit_txn(&client, &envelope, self.commit, &opts.submit)
+        })?;
+        let confirmations = match &status {
+            Some(status) if self.wait => Some(wait_for_confirmations(
+                &client,
+                &status.hash,
+                self.confirmations,
+                opts.timeouts.wait(),
+            )?),
+            _ => None,
+        };
+        print_rotation(
+            &wallet,
+            &new_wallet,
+            &txn,
+            wallet.public_key.network,
+            &status,
+            confirmations,
+            opts.encoding,
+            opts.format,
+        )
+    }
+}
+
+fn print_rotation(
+    wallet: &Wallet,
+    new_wallet: &Wallet,
+    txn: &BlockchainTxnPaymentV2,
+    network: Network,
+    status: &Option<PendingTxnStatus>,
+    confirmations: Option<u64>,
+    encoding: Encoding,
+    format: OutputFormat,
+) -> Result {
+    let amount = Hnt::from_bones(txn.payments[0].amount);
+    let cleared = confirmations.is_some();
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Network", network],
+                ["Old Address", wallet.address()?],
+                ["New Address", new_wallet.address()?],
+                ["Amount", amount],
+                ["Fee", txn.fee],
+                ["Hash", status_str(status)],
+                ["Cleared", cleared],
+                [
+                    "Confirmations",
+                    confirmations.map_or("none".to_string(), |n| n.to_string())
+                ]
+            ))?;
+            if status.is_some() && !cleared {
+                println!("\nSweep submitted but not confirmed, use --wait to wait for it to clear");
+            }
+            print_footer(status)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "network": network.to_string(),
+                "old_address": wallet.address()?,
+                "new_address": new_wallet.address()?,
+                "amount": amount,
+                "fee": txn.fee,
+                "hash": status_json(status),
+                "cleared": cleared,
+                "confirmations": confirmations,
+                "txn": encoding.encode(&txn.in_envelope())?,
+            });
+            print_json(&table)
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{
+    cmd::{decrypt_wallet, load_wallet, open_output_file, Opts},
+    keypair::{seal_to, PublicKey},
+    result::Result,
+};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Encrypt a payload to the public key of a recipient. Only the holder of
+/// the matching private key can decrypt the result using the
+/// "decrypt-from" command. The encrypted payload is printed base64
+/// encoded. Only ed25519 keys are supported.
+pub struct EncryptTo {
+    /// Address of the recipient
+    recipient: PublicKey,
+
+    /// File with the payload to encrypt. If not given stdin is read
+    #[structopt(long, short = "i")]
+    input: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+/// Decrypt a payload that was encrypted to the public key of this wallet
+/// using the "encrypt-to" command.
+pub struct DecryptFrom {
+    /// File with the base64 encoded encrypted payload. If not given stdin
+    /// is read. Note that the stdin feature only works if the wallet
+    /// password is set in the HELIUM_WALLET_PASSWORD environment variable
+    #[structopt(long, short = "i")]
+    input: Option<PathBuf>,
+
+    /// File to write the decrypted payload to. If not given the payload is
+    /// written to stdout
+    #[structopt(long, short = "o")]
+    output: Option<PathBuf>,
+
+    /// Overwrite an existing output file
+    #[structopt(long)]
+    force: bool,
+}
+
+impl EncryptTo {
+    pub fn run(&self, _opts: Opts) -> Result {
+        let payload = read_input(&self.input)?;
+        let sealed = seal_to(&self.recipient, &payload)?;
+        println!("{}", base64::encode(&sealed));
+        Ok(())
+    }
+}
+
+impl DecryptFrom {
+    pub fn run(&self, opts: Opts) -> Result {
+        let sealed = base64::decode(String::from_utf8(read_input(&self.input)?)?.trim())?;
+        let wallet = load_wallet(opts.files)?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
+        let payload = keypair.open_sealed(&sealed)?;
+        match &self.output {
+            Some(path) => open_output_file(path, !self.force)?.write_all(&payload)?,
+            None => io::stdout().write_all(&payload)?,
+        }
+        Ok(())
+    }
+}
+
+fn read_input(input: &Option<PathBuf>) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    match input {
+        Some(path) => fs::File::open(path)?.read_to_end(&mut buffer)?,
+        None => io::stdin().read_to_end(&mut buffer)?,
+    };
+    Ok(buffer)
+}
@@ -1,13 +1,14 @@
 use crate::{
     cmd::{
-        api_url, get_password, get_txn_fees, load_wallet, print_footer, print_json, status_json,
-        status_str, Opts, OutputFormat,
+        api_url, check_balance, decrypt_wallet, fetch_payer_account, get_txn_fees, load_wallet,
+        print_footer, print_json, print_table, status_json, status_str, submit_txn, Encoding, Opts,
+        OutputFormat,
     },
-    keypair::PublicKey,
+    keypair::{Network, PublicKey},
     result::Result,
-    traits::{TxnEnvelope, TxnFee, TxnSign, B64},
+    traits::{BalanceType, TxnEnvelope, TxnFee, TxnSign},
 };
-use helium_api::{BlockchainTxn, BlockchainTxnSecurityExchangeV1, Client, Hst, PendingTxnStatus};
+use helium_api::{BlockchainTxn, BlockchainTxnSecurityExchangeV1, Hst, PendingTxnStatus};
 use serde_json::json;
 use structopt::StructOpt;
 
@@ -41,13 +42,14 @@ impl Cmd {
 
 impl Transfer {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let account = client.get_account(&keypair.public_key().to_string())?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
+        let account = fetch_payer_account(&client, keypair.public_key())?;
 
         let mut txn = BlockchainTxnSecurityExchangeV1 {
             payer: keypair.public_key().into(),
@@ -57,36 +59,43 @@ impl Transfer {
             fee: 0,
             signature: vec![],
         };
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
+        txn.fee = txn.txn_fee(&get_txn_fees(&client, &opts.submit)?)?;
+        check_balance(&account, &txn.in_envelope(), BalanceType::Hst)?;
         txn.signature = txn.sign(&keypair)?;
         let envelope = txn.in_envelope();
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
 
-        print_txn(&txn, &envelope, &status, opts.format)
+        print_txn(
+            &txn,
+            &envelope,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 fn print_txn(
     txn: &BlockchainTxnSecurityExchangeV1,
     envelope: &BlockchainTxn,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Payee", "Amount"],
                 [PublicKey::from_bytes(&txn.payee)?.to_string(), txn.amount]
-            );
-            ptable!(
+            ))?;
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Nonce", txn.nonce],
                 ["Hash", status_str(status)]
-            );
+            ))?;
 
             print_footer(status)
         }
@@ -96,10 +105,11 @@ fn print_txn(
                     "amount": txn.amount,
             });
             let table = json!({
+                "network": network.to_string(),
                 "transfer": transfer,
                 "nonce": txn.nonce,
                 "hash": status_json(status),
-                "txn": envelope.to_b64()?,
+                "txn": encoding.encode(envelope)?,
             });
             print_json(&table)
         }
@@ -0,0 +1,52 @@
+use crate::{cmd::*, keypair::Network, result::Result};
+use helium_api::PendingTxnStatus;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Submit a signed transaction, for example one that was built with
+/// --unsigned and signed offline. The transaction is not submitted to the
+/// API unless the '--commit' option is given.
+pub struct Cmd {
+    /// Encoded transaction (see --encoding) to submit. If no transaction is
+    /// given stdin is read for the transaction.
+    #[structopt(name = "TRANSACTION")]
+    txn: Option<Transaction>,
+
+    /// The network to submit the transaction to (mainnet/testnet)
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+
+    /// Commit the transaction to the API
+    #[structopt(long)]
+    commit: bool,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let envelope = read_txn(&self.txn, opts.encoding)?;
+        check_logged_txn_nonce(&envelope, self.commit, &opts.submit)?;
+        let client = opts.timeouts.client(api_url(&opts.api_url, self.network))?;
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(self.network, &status, opts.format)
+    }
+}
+
+fn print_txn(network: Network, status: &Option<PendingTxnStatus>, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Network", network],
+                ["Hash", status_str(status)]
+            ))?;
+            print_footer(status)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "network": network.to_string(),
+                "hash": status_json(status),
+            });
+            print_json(&table)
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    cmd::{txn_nonce, write_atomic},
+    keypair::PublicKey,
+    result::{bail, Result},
+};
+use helium_api::{BlockchainTxn, Message, PendingTxnStatus};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The maximum number of submissions kept in the log
+const MAX_ENTRIES: usize = 500;
+
+/// A record of a submitted transaction
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Submitted {
+    /// Hex encoded sha256 digest of the submitted envelope
+    pub digest: String,
+    /// The hash the API returned for the transaction
+    pub hash: String,
+    /// Seconds since the unix epoch the transaction was submitted at
+    pub time: u64,
+    /// The payer of a transaction that uses the account nonce
+    #[serde(default)]
+    pub payer: Option<String>,
+    /// The account nonce the transaction uses
+    #[serde(default)]
+    pub nonce: Option<u64>,
+}
+
+/// A local log of submitted transactions. Signing the same transaction
+/// (same nonce, payees and amounts) twice results in the same envelope, so
+/// a resubmission, for example after a timed out submit, is detected
+/// before the transaction reaches the API again.
+#[derive(Debug, Default)]
+pub struct SubmitLog {
+    path: Option<PathBuf>,
+    entries: Vec<Submitted>,
+}
+
+impl SubmitLog {
+    /// Loads the log from the given file. A missing file is an empty log.
+    pub fn load(path: &Path) -> Result<Self> {
+        let entries = match fs::File::open(path) {
+            Ok(file) => serde_json::from_reader(file)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            entries,
+        })
+    }
+
+    /// Returns the previous submission of the given envelope, if any
+    pub fn find(&self, envelope: &BlockchainTxn) -> Result<Option<&Submitted>> {
+        let digest = envelope_digest(envelope)?;
+        Ok(self.entries.iter().find(|entry| entry.digest == digest))
+    }
+
+    /// Fails if the given envelope was submitted before
+    pub fn check(&self, envelope: &BlockchainTxn) -> Result {
+        if let Some(entry) = self.find(envelope)? {
+            bail!(
+                "An identical transaction was already submitted with hash {}. Check its status before submitting again",
+                entry.hash
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns a previous submission for the same payer at the given nonce
+    pub fn find_nonce(&self, payer: &PublicKey, nonce: u64) -> Option<&Submitted> {
+        let payer = payer.to_string();
+        self.entries
+            .iter()
+            .find(|entry| entry.payer.as_ref() == Some(&payer) && entry.nonce == Some(nonce))
+    }
+
+    /// Fails if a transaction of the given payer at the given account
+    /// nonce was submitted before. Submitting another transaction at that
+    /// nonce can double spend if the first one is still pending.
+    pub fn check_nonce(&self, payer: &PublicKey, nonce: u64) -> Result {
+        if let Some(entry) = self.find_nonce(payer, nonce) {
+            bail!(
+                "A transaction with nonce {} was already submitted with hash {}. Check its status with the track command before paying at this nonce again, or use --force-nonce to use this nonce anyway",
+                nonce,
+                entry.hash
+            );
+        }
+        Ok(())
+    }
+
+    /// Records a submitted envelope and saves the log
+    pub fn record(&mut self, envelope: &BlockchainTxn, status: &PendingTxnStatus) -> Result {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let payer_nonce = txn_nonce(envelope)?;
+        self.entries.push(Submitted {
+            digest: envelope_digest(envelope)?,
+            hash: status.hash.clone(),
+            time,
+            payer: payer_nonce.as_ref().map(|(payer, _)| payer.to_string()),
+            nonce: payer_nonce.map(|(_, nonce)| nonce),
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result {
+        if let Some(path) = &self.path {
+            write_atomic(path, &serde_json::to_vec(&self.entries)?)?;
+        }
+        Ok(())
+    }
+}
+
+fn envelope_digest(envelope: &BlockchainTxn) -> Result<String> {
+    let mut buf = vec![];
+    envelope.encode(&mut buf)?;
+    Ok(hex::encode(Sha256::digest(&buf)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        keypair::Keypair,
+        traits::{TxnEnvelope, TxnSign},
+    };
+    use helium_api::{BlockchainTxnPaymentV2, Payment};
+
+    fn payment(keypair: &Keypair, nonce: u64) -> BlockchainTxn {
+        let mut txn = BlockchainTxnPaymentV2 {
+            payer: keypair.public_key().to_vec(),
+            payments: vec![Payment {
+                payee: Keypair::default().public_key().to_vec(),
+                amount: 100,
+            }],
+            nonce,
+            fee: 35_000,
+            signature: vec![],
+        };
+        txn.signature = txn.sign(keypair).expect("signature");
+        txn.in_envelope()
+    }
+
+    #[test]
+    fn detect_resubmission() {
+        let keypair = Keypair::default();
+        let envelope = payment(&keypair, 1);
+        let mut log = SubmitLog::default();
+        log.check(&envelope).expect("not submitted");
+        log.record(
+            &envelope,
+            &PendingTxnStatus {
+                hash: "hash".to_string(),
+            },
+        )
+        .expect("record");
+        assert!(log.check(&envelope).is_err());
+        assert_eq!("hash", log.find(&envelope).unwrap().unwrap().hash);
+        // A different nonce is a different transaction
+        log.check(&payment(&keypair, 2)).expect("not submitted");
+    }
+
+    #[test]
+    fn detect_nonce_reuse() {
+        let keypair = Keypair::default();
+        let mut log = SubmitLog::default();
+        log.record(
+            &payment(&keypair, 3),
+            &PendingTxnStatus {
+                hash: "hash".to_string(),
+            },
+        )
+        .expect("record");
+        let payer = keypair.public_key();
+        let err = log.check_nonce(payer, 3).unwrap_err();
+        assert!(err.to_string().contains("hash hash"));
+        log.check_nonce(payer, 4).expect("next nonce");
+        log.check_nonce(Keypair::default().public_key(), 3)
+            .expect("other payer");
+
+        // Entries logged before payers and nonces were recorded match nothing
+        let old: Vec<Submitted> =
+            serde_json::from_str(r#"[{"digest": "d", "hash": "h", "time": 0}]"#).expect("old log");
+        let log = SubmitLog {
+            path: None,
+            entries: old,
+        };
+        log.check_nonce(keypair.public_key(), 3).expect("old entry");
+    }
+}
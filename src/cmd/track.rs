@@ -0,0 +1,110 @@
+use crate::{
+    api,
+    cmd::{history::format_time, receipt::Receipt, *},
+    keypair::Network,
+    result::Result,
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Show the status of a submitted transaction from the receipt that was
+/// written for it with --receipt. The receipt includes the signed
+/// transaction, so a transaction that was dropped can be submitted again.
+pub struct Cmd {
+    /// Receipt file of the transaction
+    receipt: PathBuf,
+
+    /// Wait for the transaction to clear or fail
+    #[structopt(long)]
+    wait: bool,
+
+    /// Number of blocks the transaction has to be confirmed by with
+    /// --wait. The block with the transaction is the first confirmation
+    #[structopt(long, requires = "wait", default_value = "1")]
+    confirmations: u64,
+
+    /// The network to use for receipts of transactions without a payer
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let receipt = Receipt::read(&self.receipt)?;
+        let network = receipt.network()?.unwrap_or(self.network);
+        let client = opts.timeouts.client(api_url(&opts.api_url, network))?;
+        let mut confirmations = None;
+        if self.wait {
+            // A failed transaction is shown with its reason below
+            match wait_for_confirmations(
+                &client,
+                &receipt.hash,
+                self.confirmations,
+                opts.timeouts.wait(),
+            ) {
+                Ok(depth) => confirmations = Some(depth),
+                Err(err) => eprintln!("Warning: {}", err),
+            }
+        }
+        let pending = client.get_pending_txn(&receipt.hash)?;
+        print_status(
+            &receipt,
+            network,
+            pending.as_ref(),
+            confirmations,
+            opts.format,
+        )
+    }
+}
+
+fn print_status(
+    receipt: &Receipt,
+    network: Network,
+    pending: Option<&api::PendingTxn>,
+    confirmations: Option<u64>,
+    format: OutputFormat,
+) -> Result {
+    let status = pending.map_or("unknown", |txn| txn.status.as_str());
+    let reason = pending
+        .and_then(|txn| txn.failed_reason.clone())
+        .unwrap_or_default();
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Network", network],
+                ["Hash", receipt.hash],
+                [
+                    "Nonce",
+                    receipt.nonce.map_or("none".to_string(), |n| n.to_string())
+                ],
+                ["Submitted", format_time(receipt.time)],
+                ["Status", status],
+                [
+                    "Confirmations",
+                    confirmations.map_or("unknown".to_string(), |n| n.to_string())
+                ],
+                ["Reason", reason]
+            ))?;
+            if pending.is_none() {
+                println!(
+                    "\nTransaction not found, the signed transaction in the receipt can be submitted again"
+                );
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "network": network.to_string(),
+                "hash": receipt.hash,
+                "nonce": receipt.nonce,
+                "time": receipt.time,
+                "status": status,
+                "confirmations": confirmations,
+                "failed_reason": reason,
+                "txn": receipt.txn,
+            });
+            print_json(&table)
+        }
+    }
+}
@@ -1,9 +1,8 @@
 use crate::{
-    cmd::{get_file_extension, get_password, load_wallet, open_output_file, verify, Opts},
+    cmd::{get_file_extension, get_wallet_password, load_wallet, open_output_file, verify, Opts},
     format::{self, Format},
     pwhash::PwHash,
     result::Result,
-    wallet::Wallet,
 };
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -60,14 +59,13 @@ impl Cmd {
 
 impl Basic {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let password = get_wallet_password(&wallet)?;
 
         let format = format::Basic {
             pwhash: PwHash::argon2id13_default(),
         };
-        let new_wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Basic(format))?;
+        let new_wallet = wallet.encrypt_again(password.as_bytes(), Format::Basic(format))?;
         let mut writer = open_output_file(&self.output, !self.force)?;
         new_wallet.write(&mut writer)?;
         verify::print_result(&new_wallet, true, opts.format)
@@ -76,9 +74,8 @@ impl Basic {
 
 impl Sharded {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let password = get_wallet_password(&wallet)?;
 
         let format = format::Sharded {
             key_share_count: self.key_share_count,
@@ -86,7 +83,7 @@ impl Sharded {
             pwhash: PwHash::argon2id13_default(),
             key_shares: vec![],
         };
-        let new_wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Sharded(format))?;
+        let new_wallet = wallet.encrypt_again(password.as_bytes(), Format::Sharded(format))?;
 
         let extension = get_file_extension(&self.output);
         for (i, shard) in new_wallet.shards()?.iter().enumerate() {
@@ -1,7 +1,8 @@
 use crate::{cmd::*, result::Result};
 use structopt::StructOpt;
 
-mod stake;
+pub mod stake;
+mod status;
 mod transfer;
 mod unstake;
 
@@ -16,6 +17,8 @@ pub enum Cmd {
     Unstake(unstake::Cmd),
     /// Transfer a validator stake to a new validator and owner
     Transfer(Box<transfer::Cmd>),
+    /// Show the stake and heartbeat status of a validator
+    Status(status::Cmd),
 }
 
 impl Cmd {
@@ -25,6 +28,7 @@ impl Cmd {
             Self::Stake(cmd) => cmd.run(opts),
             Self::Unstake(cmd) => cmd.run(opts),
             Self::Transfer(cmd) => cmd.run(opts),
+            Self::Status(cmd) => cmd.run(opts),
         }
     }
 }
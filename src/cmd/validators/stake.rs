@@ -1,9 +1,11 @@
 use crate::{
+    api::Client,
     cmd::*,
-    result::Result,
-    traits::{TxnEnvelope, TxnFee, TxnSign},
+    result::{anyhow, bail, Result},
+    txn::TxnBuilder,
 };
-use helium_api::{BlockchainTxnStakeValidatorV1, Hnt, PendingTxnStatus};
+use helium_api::{Hnt, PendingTxnStatus};
+use std::str::FromStr;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -13,68 +15,154 @@ pub struct Cmd {
     /// Address of the validator to stake
     address: PublicKey,
 
-    /// Amoun to stake
-    stake: Hnt,
+    /// Amount of HNT to stake. Use "auto" to stake the validator stake
+    /// required by the chain. Other amounts are rejected unless they match
+    /// the required stake.
+    stake: Stake,
 
     /// Whether to commit the transaction to the blockchain
     #[structopt(long)]
     commit: bool,
+
+    /// Build the transaction with its fee without signing it. The emitted
+    /// transaction can be signed offline with cosign and then submitted
+    /// with submit.
+    #[structopt(long, conflicts_with = "commit")]
+    unsigned: bool,
 }
 
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = if self.unsigned {
+            None
+        } else {
+            Some(decrypt_wallet(&wallet, &opts.key_file)?)
+        };
+
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let minimum_stake = get_minimum_stake(&client)?;
+        let stake = self.stake.to_bones(minimum_stake)?;
+        eprintln!("Staking {} HNT", Hnt::from_bones(stake));
 
-        let mut txn = BlockchainTxnStakeValidatorV1 {
-            address: self.address.to_vec(),
-            owner: wallet.public_key.to_vec(),
-            stake: self.stake.to_bones(),
-            fee: 0,
-            owner_signature: vec![],
+        let builder = TxnBuilder::stake_validator(&self.address, stake)
+            .fee_config(&get_txn_fees(&client, &opts.submit)?);
+        let (envelope, fee) = match &keypair {
+            Some(keypair) => builder.build_signed(keypair)?,
+            None => builder.build_unsigned(&wallet.public_key)?,
         };
 
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
-        txn.owner_signature = txn.sign(&keypair)?;
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            &envelope,
+            &self.address,
+            stake,
+            fee,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
+    }
+}
+
+/// The stake of a validator, either an amount of HNT or "auto" for the
+/// stake the chain requires
+#[derive(Clone, Copy, Debug)]
+pub enum Stake {
+    Auto,
+    Hnt(Hnt),
+}
 
-        let envelope = txn.in_envelope();
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-        print_txn(&envelope, &txn, &status, opts.format)
+impl FromStr for Stake {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Stake::Auto),
+            _ => Ok(Stake::Hnt(s.parse()?)),
+        }
+    }
+}
+
+impl Stake {
+    /// Returns the stake in bones. A given stake has to match the minimum
+    /// stake of the chain exactly, since the chain rejects any other stake.
+    pub fn to_bones(self, minimum_stake: u64) -> Result<u64> {
+        match self {
+            Stake::Auto => Ok(minimum_stake),
+            Stake::Hnt(hnt) if hnt.to_bones() == minimum_stake => Ok(minimum_stake),
+            Stake::Hnt(hnt) => bail!(
+                "Stake of {} HNT does not match the required validator stake of {} HNT, use \"auto\" to stake the required amount",
+                hnt,
+                Hnt::from_bones(minimum_stake)
+            ),
+        }
     }
 }
 
+/// Fetches the validator stake in bones the chain requires
+pub fn get_minimum_stake(client: &Client) -> Result<u64> {
+    client
+        .get_vars()?
+        .get("validator_minimum_stake")
+        .and_then(|stake| stake.as_u64())
+        .ok_or_else(|| anyhow!("Missing validator_minimum_stake chain variable"))
+}
+
 fn print_txn(
     envelope: &BlockchainTxn,
-    txn: &BlockchainTxnStakeValidatorV1,
+    validator: &PublicKey,
+    stake: u64,
+    fee: u64,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
-    let validator = PublicKey::from_bytes(&txn.address)?.to_string();
+    let validator = validator.to_string();
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Validator", validator],
-                ["Fee", txn.fee],
+                ["Stake", Hnt::from_bones(stake)],
+                ["Fee", fee],
                 ["Hash", status_str(status)]
-            );
+            ))?;
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "validator" : validator,
-                "fee": txn.fee,
-                "txn": envelope.to_b64()?,
+                "stake": Hnt::from_bones(stake),
+                "fee": fee,
+                "txn": encoding.encode(envelope)?,
                 "hash": status_json(status)
             });
             print_json(&table)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stake_amount() {
+        let minimum = 1_000_000_000_000;
+        let auto: Stake = "auto".parse().expect("auto stake");
+        assert_eq!(minimum, auto.to_bones(minimum).unwrap());
+        let exact: Stake = "10000".parse().expect("stake");
+        assert_eq!(minimum, exact.to_bones(minimum).unwrap());
+        let short: Stake = "9999.99999999".parse().expect("stake");
+        assert!(short.to_bones(minimum).is_err());
+        assert!("ten".parse::<Stake>().is_err());
+    }
+}
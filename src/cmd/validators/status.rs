@@ -0,0 +1,99 @@
+use crate::{api::Validator, cmd::*, result::Result};
+use helium_api::Hnt;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Show the stake status, last heartbeat, version and penalty score of a
+/// validator.
+pub struct Cmd {
+    /// Address of the validator
+    address: PublicKey,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, self.address.network))?;
+        let validator = client.get_validator(&self.address.to_string())?;
+        print_status(&self.address, &validator, opts.format)
+    }
+}
+
+fn print_status(
+    address: &PublicKey,
+    validator: &Option<Validator>,
+    format: OutputFormat,
+) -> Result {
+    match (format, validator) {
+        (OutputFormat::Table, Some(validator)) => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Address", address],
+                ["Network", address.network],
+                ["Owner", validator.owner],
+                ["Stake", Hnt::from_bones(validator.stake)],
+                ["Stake Status", validator.stake_status],
+                ["Last Heartbeat", validator.last_heartbeat],
+                ["Version", validator.version_heartbeat],
+                ["Penalty", validator.penalty]
+            ))?;
+            Ok(())
+        }
+        (OutputFormat::Table, None) => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Address", address],
+                ["Network", address.network],
+                ["Stake Status", "not found"]
+            ))?;
+            println!("\nThe validator is not (yet) on chain");
+            Ok(())
+        }
+        (OutputFormat::Json, validator) => {
+            let table = json!({
+                "address": address.to_string(),
+                "network": address.network.to_string(),
+                "found": validator.is_some(),
+                "validator": validator,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_validator() {
+        let validator: Validator = serde_json::from_value(json!({
+            "address": "1ZcMBeKMzA2dGzBZs3iSkEhXTUy2L1ZbRsVUT2mFNdTY3rMHrXj",
+            "owner": "13ESLoXiie3eXoyitxryNQNamGAnJjKt2WkiB4gNq95knxAiGEp",
+            "stake": 1_000_000_000_000,
+            "stake_status": "staked",
+            "last_heartbeat": 1_000_000,
+            "version_heartbeat": 10_001,
+            "penalty": 1.5,
+            "block": 1_000_010,
+            "status": {"online": "online"}
+        }))
+        .expect("validator");
+        assert_eq!("staked", validator.stake_status);
+        assert_eq!(10_001, validator.version_heartbeat);
+
+        // Validators without penalties may omit the score
+        let validator: Validator = serde_json::from_value(json!({
+            "address": "1ZcMBeKMzA2dGzBZs3iSkEhXTUy2L1ZbRsVUT2mFNdTY3rMHrXj",
+            "owner": "13ESLoXiie3eXoyitxryNQNamGAnJjKt2WkiB4gNq95knxAiGEp",
+            "stake": 1_000_000_000_000,
+            "stake_status": "cooldown",
+            "last_heartbeat": 1_000_000,
+            "version_heartbeat": 10_001,
+            "block": 1_000_010
+        }))
+        .expect("validator");
+        assert!(validator.penalty.abs() < f64::EPSILON);
+    }
+}
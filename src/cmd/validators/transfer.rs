@@ -1,7 +1,7 @@
 use crate::{
     cmd::*,
     result::Result,
-    traits::{TxnEnvelope, TxnFee, TxnSign, B64},
+    traits::{TxnEnvelope, TxnFee, TxnSign},
 };
 use helium_api::{BlockchainTxn, BlockchainTxnTransferValidatorStakeV1, Hnt, PendingTxnStatus};
 use structopt::StructOpt;
@@ -46,6 +46,12 @@ pub struct Create {
     /// Whether to commit the transaction to the blockchain
     #[structopt(long)]
     commit: bool,
+
+    /// Build the transaction with its fee without signing it, even if the
+    /// wallet is one of the owners. The emitted transaction can be signed
+    /// offline with accept and then submitted with submit.
+    #[structopt(long, conflicts_with = "commit")]
+    unsigned: bool,
 }
 
 #[derive(Debug, StructOpt)]
@@ -54,7 +60,7 @@ pub struct Create {
 /// owner or the old owner if the owner keys match the public key of the given
 /// wallet.
 pub struct Accept {
-    /// Base64 encoded transaction to sign. If no transaction if given
+    /// Encoded transaction (see --encoding) to sign. If no transaction if given
     /// stdin is read for the transaction. Note that the stdin feature
     /// only works if the wallet password is set in the
     /// HELIUM_WALLET_PASSWORD environment variable
@@ -77,11 +83,16 @@ impl Cmd {
 
 impl Create {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = if self.unsigned {
+            None
+        } else {
+            Some(decrypt_wallet(&wallet, &opts.key_file)?)
+        };
 
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
         let old_owner = self.old_owner.as_ref().unwrap_or(&wallet.public_key);
 
@@ -100,33 +111,40 @@ impl Create {
             new_owner_signature: vec![],
         };
 
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
-        if old_owner == &wallet.public_key {
-            txn.old_owner_signature = txn.sign(&keypair)?;
-        }
-        if let Some(owner) = &self.new_owner {
-            if owner == &wallet.public_key {
-                txn.new_owner_signature = txn.sign(&keypair)?;
+        txn.fee = txn.txn_fee(&get_txn_fees(&client, &opts.submit)?)?;
+        if let Some(keypair) = &keypair {
+            if old_owner == &wallet.public_key {
+                txn.old_owner_signature = txn.sign(keypair)?;
+            }
+            if let Some(owner) = &self.new_owner {
+                if owner == &wallet.public_key {
+                    txn.new_owner_signature = txn.sign(keypair)?;
+                }
             }
         }
 
         let envelope = txn.in_envelope();
-        let status = if self.commit {
-            Some(client.submit_txn(&envelope)?)
-        } else {
-            None
-        };
-        print_txn(Some(&envelope), &txn, &status, opts.format)
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            Some(&envelope),
+            &txn,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 impl Accept {
     pub fn run(&self, opts: Opts) -> Result {
-        let mut txn = BlockchainTxnTransferValidatorStakeV1::from_envelope(&read_txn(&self.txn)?)?;
+        let mut txn = BlockchainTxnTransferValidatorStakeV1::from_envelope(&read_txn(
+            &self.txn,
+            opts.encoding,
+        )?)?;
 
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = decrypt_wallet(&wallet, &opts.key_file)?;
 
         if !txn.old_owner.is_empty() && PublicKey::from_bytes(&txn.old_owner)? == wallet.public_key
         {
@@ -137,21 +155,31 @@ impl Accept {
             txn.new_owner_signature = txn.sign(&keypair)?;
         }
 
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
-        let status = if self.commit {
-            Some(client.submit_txn(&txn.in_envelope())?)
-        } else {
-            None
-        };
-        print_txn(None, &txn, &status, opts.format)
+        // The signed transaction is emitted so it can be submitted from
+        // another (online) machine
+        let envelope = txn.in_envelope();
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            Some(&envelope),
+            &txn,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 fn print_txn(
     envelope: Option<&BlockchainTxn>,
     txn: &BlockchainTxnTransferValidatorStakeV1,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     let old_address = PublicKey::from_bytes(&txn.old_address)?.to_string();
@@ -164,8 +192,9 @@ fn print_txn(
     };
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Old address", old_address],
                 ["New address", new_address],
                 ["Old owner", old_owner],
@@ -173,11 +202,12 @@ fn print_txn(
                 ["Fee", txn.fee],
                 ["Amount", Hnt::from_bones(txn.amount)],
                 ["Hash", status_str(status)]
-            );
+            ))?;
             print_footer(status)
         }
         OutputFormat::Json => {
             let mut table = json!({
+                "network": network.to_string(),
                 "old_address" : old_address,
                 "new_address" : new_address,
                 "old_owner" : old_owner,
@@ -187,7 +217,7 @@ fn print_txn(
                 "hash": status_json(status)
             });
             if let Some(envelope) = envelope {
-                table["txn"] = envelope.to_b64()?.into();
+                table["txn"] = encoding.encode(envelope)?.into();
             };
             print_json(&table)
         }
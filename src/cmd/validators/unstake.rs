@@ -16,15 +16,26 @@ pub struct Cmd {
     /// Whether to commit the transaction to the blockchain
     #[structopt(long)]
     commit: bool,
+
+    /// Build the transaction with its fee without signing it. The emitted
+    /// transaction can be signed offline with cosign and then submitted
+    /// with submit.
+    #[structopt(long, conflicts_with = "commit")]
+    unsigned: bool,
 }
 
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = if self.unsigned {
+            None
+        } else {
+            Some(decrypt_wallet(&wallet, &opts.key_file)?)
+        };
 
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = opts
+            .timeouts
+            .client(api_url(&opts.api_url, wallet.public_key.network))?;
 
         let mut txn = BlockchainTxnUnstakeValidatorV1 {
             address: self.address.to_vec(),
@@ -33,38 +44,50 @@ impl Cmd {
             owner_signature: vec![],
         };
 
-        txn.fee = txn.txn_fee(&get_txn_fees(&client)?)?;
-        txn.owner_signature = txn.sign(&keypair)?;
+        txn.fee = txn.txn_fee(&get_txn_fees(&client, &opts.submit)?)?;
+        if let Some(keypair) = &keypair {
+            txn.owner_signature = txn.sign(keypair)?;
+        }
 
-        let status = if self.commit {
-            Some(client.submit_txn(&txn.in_envelope())?)
-        } else {
-            None
-        };
-        print_txn(&txn, &status, opts.format)
+        let envelope = txn.in_envelope();
+        let status = submit_txn(&client, &envelope, self.commit, &opts.submit)?;
+        print_txn(
+            &envelope,
+            &txn,
+            wallet.public_key.network,
+            &status,
+            opts.encoding,
+            opts.format,
+        )
     }
 }
 
 fn print_txn(
+    envelope: &BlockchainTxn,
     txn: &BlockchainTxnUnstakeValidatorV1,
+    network: Network,
     status: &Option<PendingTxnStatus>,
+    encoding: Encoding,
     format: OutputFormat,
 ) -> Result {
     let validator = PublicKey::from_bytes(&txn.address)?.to_string();
     match format {
         OutputFormat::Table => {
-            ptable!(
+            print_table(&table!(
                 ["Key", "Value"],
+                ["Network", network],
                 ["Validator", validator],
                 ["Fee", txn.fee],
                 ["Hash", status_str(status)]
-            );
+            ))?;
             print_footer(status)
         }
         OutputFormat::Json => {
             let table = json!({
+                "network": network.to_string(),
                 "validator" : validator,
                 "fee": txn.fee,
+                "txn": encoding.encode(envelope)?,
                 "hash": status_json(status)
             });
             print_json(&table)
@@ -4,7 +4,7 @@ use crate::{
     result::Result,
     traits::{ToJson, TxnEnvelope},
 };
-use helium_api::{BlockchainTxnVarsV1, BlockchainVarV1, Client};
+use helium_api::{BlockchainTxnVarsV1, BlockchainVarV1};
 use std::{convert::TryInto, str::FromStr};
 use structopt::StructOpt;
 
@@ -65,16 +65,16 @@ impl Cmd {
 }
 
 impl Current {
-    pub fn run(&self, _opts: Opts) -> Result {
-        let client = Client::new_with_base_url(api_url(self.network));
+    pub fn run(&self, opts: Opts) -> Result {
+        let client = opts.timeouts.client(api_url(&opts.api_url, self.network))?;
         let vars = client.get_vars()?;
         print_json(&vars)
     }
 }
 
 impl Create {
-    pub fn run(&self, _opts: Opts) -> Result {
-        let client = Client::new_with_base_url(api_url(self.network));
+    pub fn run(&self, opts: Opts) -> Result {
+        let client = opts.timeouts.client(api_url(&opts.api_url, self.network))?;
         let vars = client.get_vars()?;
         let mut txn = BlockchainTxnVarsV1 {
             version_predicate: 0,
@@ -1,5 +1,6 @@
 use crate::{
-    cmd::{get_password, load_wallet, print_json, print_table, Opts, OutputFormat},
+    cmd::{get_wallet_password, load_wallet, print_json, print_table, Opts, OutputFormat},
+    pwhash::PwHash,
     result::Result,
     wallet::Wallet,
 };
@@ -13,8 +14,8 @@ pub struct Cmd {}
 
 impl Cmd {
     pub fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
         let wallet = load_wallet(opts.files)?;
+        let password = get_wallet_password(&wallet)?;
         let result = wallet.decrypt(password.as_bytes());
         print_result(&wallet, result.is_ok(), opts.format)
     }
@@ -27,7 +28,12 @@ pub fn print_result(wallet: &Wallet, result: bool, format: OutputFormat) -> Resu
             let mut table = Table::new();
             table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
             table.set_titles(row!["Address", "Sharded", "Verify", "PwHash"]);
-            table.add_row(row![address, wallet.is_sharded(), result, wallet.pwhash()]);
+            table.add_row(row![
+                address,
+                wallet.is_sharded(),
+                result,
+                pwhash_str(wallet.pwhash())
+            ]);
             print_table(&table)
         }
         OutputFormat::Json => {
@@ -35,9 +41,13 @@ pub fn print_result(wallet: &Wallet, result: bool, format: OutputFormat) -> Resu
                 "address": address,
                 "sharded": wallet.is_sharded(),
                 "verify": result,
-                "pwhash": wallet.pwhash().to_string()
+                "pwhash": wallet.pwhash().map(|pwhash| pwhash.to_string())
             });
             print_json(&table)
         }
     }
 }
+
+pub fn pwhash_str(pwhash: Option<&PwHash>) -> String {
+    pwhash.map_or_else(|| "none".to_string(), |pwhash| pwhash.to_string())
+}
@@ -0,0 +1,100 @@
+use crate::{
+    cmd::{payee::parse_address, print_json, print_table, Opts, OutputFormat},
+    keypair::{self, KeyType, Network, PublicKey},
+    result::{anyhow, bail, Result},
+};
+use serde_json::json;
+use structopt::StructOpt;
+
+/// Check that an address is a valid Helium address and show its network
+/// and key type. No wallet or API access is needed. Fails for an invalid
+/// address.
+#[derive(Debug, StructOpt)]
+pub struct Cmd {
+    /// The address to check
+    address: String,
+
+    /// Also fail if the address is not on the given network
+    /// (mainnet/testnet)
+    #[structopt(long)]
+    network: Option<Network>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let (address, key_type) = verify_address(self.address.trim(), self.network)?;
+        print_address(&address, key_type, opts.format)
+    }
+}
+
+/// Parses the given address and returns it with its key type. Fails if
+/// the address is invalid or not on the expected network, if given.
+fn verify_address(address: &str, network: Option<Network>) -> Result<(PublicKey, KeyType)> {
+    let public_key =
+        parse_address(address).map_err(|err| anyhow!("Invalid address {}: {}", address, err))?;
+    if let Some(network) = network {
+        if public_key.network != network {
+            bail!(
+                "Address {} is a {} address, expected {}",
+                address,
+                public_key.network,
+                network
+            );
+        }
+    }
+    let key_type = keypair::key_type(&public_key)?;
+    Ok((public_key, key_type))
+}
+
+fn print_address(address: &PublicKey, key_type: KeyType, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                ["Address", address],
+                ["Network", address.network],
+                ["Key Type", key_type],
+                ["Valid", true]
+            ))?;
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "address": address.to_string(),
+                "network": address.network.to_string(),
+                "key_type": key_type.to_string(),
+                "valid": true,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::{KeyTag, Keypair};
+
+    #[test]
+    fn verify_addresses() {
+        let keypair = Keypair::generate(KeyTag {
+            network: Network::TestNet,
+            key_type: KeyType::EccCompact,
+        });
+        let address = keypair.public_key().to_string();
+        let (public_key, key_type) = verify_address(&address, None).expect("valid address");
+        assert_eq!(keypair.public_key(), &public_key);
+        assert_eq!(KeyType::EccCompact, key_type);
+        assert!(verify_address(&address, Some(Network::TestNet)).is_ok());
+        assert!(verify_address(&address, Some(Network::MainNet)).is_err());
+
+        // A changed character breaks the checksum
+        let mut broken = address.clone();
+        let last = if broken.ends_with('a') { "b" } else { "a" };
+        broken.replace_range(broken.len() - 1.., last);
+        assert!(verify_address(&broken, None).is_err());
+        assert!(verify_address(&address[..address.len() - 2], None).is_err());
+        assert!(verify_address("", None).is_err());
+        assert!(verify_address("not an address", None).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    cmd::{print_json, print_table, verify::pwhash_str, Opts, OutputFormat},
+    format::Format,
+    result::{anyhow, Result},
+    wallet::Wallet,
+};
+use prettytable::{format, Table};
+use serde_json::json;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+/// Check the structure of the given wallet files, like a backup, without
+/// asking for the password. Every shard of a sharded wallet is checked on
+/// its own. Only decrypting the wallet, like with verify, detects an
+/// altered but well formed file.
+#[derive(Debug, StructOpt)]
+pub struct Cmd {}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let mut wallets = Vec::with_capacity(opts.files.len());
+        for path in &opts.files {
+            let wallet = read_wallet(path)?;
+            wallet
+                .check()
+                .map_err(|err| anyhow!("Invalid wallet file {}: {}", path.display(), err))?;
+            wallets.push((path, wallet));
+        }
+        print_wallets(&wallets, opts.format)
+    }
+}
+
+/// Reads a wallet file, reporting a file that ends before the wallet
+/// structure is complete as truncated.
+pub(super) fn read_wallet(path: &Path) -> Result<Wallet> {
+    let mut reader = fs::File::open(path)?;
+    Wallet::read(&mut reader).map_err(|err| match err.downcast_ref::<io::Error>() {
+        Some(io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof => {
+            anyhow!("Wallet file {} is truncated", path.display())
+        }
+        _ => anyhow!("Invalid wallet file {}: {}", path.display(), err),
+    })
+}
+
+fn kind_str(wallet: &Wallet) -> String {
+    match wallet.secret.as_ref().map(|secret| &secret.format) {
+        None => "watch".to_string(),
+        Some(Format::Basic(_)) => "basic".to_string(),
+        Some(Format::Sharded(format)) => format!(
+            "sharded ({} of {})",
+            format.recovery_threshold, format.key_share_count
+        ),
+    }
+}
+
+fn print_wallets(wallets: &[(&PathBuf, Wallet)], format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row![
+                "File",
+                "Address",
+                "Network",
+                "Kind",
+                "Version",
+                "PwHash",
+                "Parameters",
+                "Encrypted (bytes)"
+            ]);
+            for (path, wallet) in wallets {
+                table.add_row(row![
+                    path.display(),
+                    wallet.public_key,
+                    wallet.public_key.network,
+                    kind_str(wallet),
+                    wallet.version,
+                    pwhash_str(wallet.pwhash()),
+                    wallet
+                        .pwhash()
+                        .map_or_else(|| "none".to_string(), |pwhash| pwhash.params()),
+                    wallet
+                        .secret
+                        .as_ref()
+                        .map_or(0, |secret| secret.encrypted.len())
+                ]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let table: Vec<_> = wallets
+                .iter()
+                .map(|(path, wallet)| {
+                    json!({
+                        "file": path.display().to_string(),
+                        "address": wallet.public_key.to_string(),
+                        "network": wallet.public_key.network.to_string(),
+                        "kind": kind_str(wallet),
+                        "version": wallet.version,
+                        "pwhash": wallet.pwhash().map(|pwhash| pwhash.to_string()),
+                        "pwhash_params": wallet.pwhash().map(|pwhash| pwhash.params()),
+                        "encrypted_len": wallet.secret.as_ref().map(|secret| secret.encrypted.len()),
+                    })
+                })
+                .collect();
+            print_json(&table)
+        }
+    }
+}
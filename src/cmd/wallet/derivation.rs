@@ -0,0 +1,72 @@
+use crate::{
+    cmd::{get_wallet_password, load_wallet, print_json, print_table, Opts, OutputFormat},
+    mnemonic::{account_derivation, seed_fingerprint},
+    result::Result,
+};
+use serde_json::json;
+use structopt::StructOpt;
+
+/// Show how the key of a wallet was derived: its account index, how the
+/// key was derived from the seed and a fingerprint of the seed, to tell
+/// which seed and account a wallet file belongs to. The fingerprint is a
+/// short hash that does not reveal the seed. Wallets created for an account
+/// store the fingerprint encrypted with their key. For wallets created
+/// without an account index the key is the seed itself. The wallet is
+/// decrypted to read or compute the fingerprint.
+#[derive(Debug, StructOpt)]
+pub struct Cmd {}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        let password = get_wallet_password(&wallet)?;
+        let fingerprint = match wallet.decrypt_seed_fingerprint(password.as_bytes())? {
+            Some(fingerprint) => Some(fingerprint),
+            None => match wallet.account {
+                None | Some(0) => {
+                    let entropy = wallet.decrypt_entropy(password.as_bytes())?;
+                    Some(seed_fingerprint(&entropy))
+                }
+                // Wallets for other accounts created before the fingerprint
+                // was stored. Their seed can not be recovered from the key.
+                Some(_) => None,
+            },
+        };
+        print_derivation(wallet.account, fingerprint.map(hex::encode), opts.format)
+    }
+}
+
+fn print_derivation(
+    account: Option<u32>,
+    fingerprint: Option<String>,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            print_table(&table!(
+                ["Key", "Value"],
+                [
+                    "Account Index",
+                    account.map_or("none".to_string(), |a| a.to_string())
+                ],
+                [
+                    "Derivation",
+                    account.map_or("none".to_string(), account_derivation)
+                ],
+                [
+                    "Seed Fingerprint",
+                    fingerprint.as_deref().unwrap_or("not stored")
+                ]
+            ))?;
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "account_index": account,
+                "derivation": account.map(account_derivation),
+                "seed_fingerprint": fingerprint,
+            });
+            print_json(&table)
+        }
+    }
+}
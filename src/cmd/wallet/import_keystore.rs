@@ -0,0 +1,57 @@
+use crate::{
+    cmd::{create::open_output_file, get_password, verify, Opts},
+    format::{self, Format},
+    keystore::Keystore,
+    pwhash::PwHash,
+    result::{anyhow, bail, Result},
+    wallet::Wallet,
+};
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Import the key of a JSON keystore file written by other Helium tools
+/// into a new basic wallet. The keystore is decrypted with its own
+/// password and the key is encrypted again under a new wallet password.
+/// See the keystore module documentation for the supported format.
+#[derive(Debug, StructOpt)]
+pub struct Cmd {
+    /// The keystore file to import
+    keystore: PathBuf,
+
+    #[structopt(short, long, default_value = "wallet.key")]
+    /// Output file to store the key in
+    output: PathBuf,
+
+    #[structopt(long)]
+    /// Overwrite an existing file
+    force: bool,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let mut reader = fs::File::open(&self.keystore)
+            .map_err(|e| anyhow!("Failed to open {}: {}", self.keystore.display(), e))?;
+        let keystore = Keystore::read(&mut reader)?;
+        if !self.force && self.output.exists() {
+            bail!(
+                "{} already exists, use --force to overwrite it",
+                self.output.display()
+            );
+        }
+        eprintln!("Importing {}", keystore.address);
+        let keystore_password = dialoguer::Password::new()
+            .with_prompt("Keystore password")
+            .interact()?;
+        let keypair = keystore.decrypt(keystore_password.as_bytes())?;
+
+        eprintln!("Enter a password for the new wallet");
+        let password = get_password(true)?;
+        let format = format::Basic {
+            pwhash: PwHash::argon2id13_default(),
+        };
+        let wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Basic(format))?;
+        let mut writer = open_output_file(&self.output, !self.force)?;
+        wallet.write(&mut writer)?;
+        verify::print_result(&wallet, true, opts.format)
+    }
+}
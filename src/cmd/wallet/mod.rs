@@ -0,0 +1,40 @@
+use crate::{cmd::*, result::Result};
+use structopt::StructOpt;
+
+mod check;
+mod derivation;
+mod import_keystore;
+mod reencrypt;
+mod upgrade;
+mod vanity;
+
+#[derive(Debug, StructOpt)]
+/// Commands for wallet files
+pub enum Cmd {
+    /// Check that wallet files are well formed without decrypting them
+    Check(check::Cmd),
+    /// Show the account index, key derivation and seed fingerprint of a
+    /// wallet
+    Derivation(derivation::Cmd),
+    /// Import a key from a JSON keystore file into a new wallet
+    ImportKeystore(import_keystore::Cmd),
+    /// Encrypt wallet files again with new password hash parameters
+    Reencrypt(reencrypt::Cmd),
+    /// Upgrade wallet files in place to the current file format version
+    Upgrade(upgrade::Cmd),
+    /// Create a wallet with an address that starts with a given prefix
+    Vanity(vanity::Cmd),
+}
+
+impl Cmd {
+    pub fn run(self, opts: Opts) -> Result {
+        match self {
+            Self::Check(cmd) => cmd.run(opts),
+            Self::Derivation(cmd) => cmd.run(opts),
+            Self::ImportKeystore(cmd) => cmd.run(opts),
+            Self::Reencrypt(cmd) => cmd.run(opts),
+            Self::Upgrade(cmd) => cmd.run(opts),
+            Self::Vanity(cmd) => cmd.run(opts),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    cmd::{
+        get_wallet_password, print_json, print_table, read_wallet_file, write_atomic, Opts,
+        OutputFormat,
+    },
+    pwhash::PwHash,
+    result::{bail, Result},
+    wallet::Wallet,
+};
+use prettytable::{format, Table};
+use serde_json::json;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Encrypt the given basic wallet files again under the same password
+/// with new password hash parameters, like a higher cost after moving to
+/// faster hardware. The key and address stay the same. Every file is
+/// replaced atomically, so an interrupted re-encryption leaves the
+/// original file intact.
+#[derive(Debug, StructOpt)]
+pub struct Cmd {
+    /// Use PBKDF2 with the given number of iterations. Without it the
+    /// default Argon2id parameters are used.
+    #[structopt(long)]
+    iterations: Option<u32>,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let mut results = Vec::with_capacity(opts.files.len());
+        for path in &opts.files {
+            let wallet = read_wallet_file(path)?;
+            let password = get_wallet_password(&wallet)?;
+            let reencrypted = wallet.reencrypt(password.as_bytes(), self.pwhash())?;
+            let mut buffer = vec![];
+            reencrypted.write(&mut buffer)?;
+            write_atomic(path, &buffer)?;
+
+            let written = Wallet::read(&mut fs::File::open(path)?)?;
+            if written.public_key != wallet.public_key {
+                bail!("Re-encrypted wallet does not match {}", path.display());
+            }
+            results.push((path.clone(), wallet, reencrypted));
+        }
+        print_results(&results, opts.format)
+    }
+
+    fn pwhash(&self) -> PwHash {
+        match self.iterations {
+            Some(iterations) => PwHash::pbkdf2(iterations),
+            None => PwHash::argon2id13_default(),
+        }
+    }
+}
+
+fn params_str(wallet: &Wallet) -> String {
+    wallet.pwhash().map_or_else(
+        || "none".to_string(),
+        |pwhash| format!("{} ({})", pwhash, pwhash.params()),
+    )
+}
+
+fn print_results(results: &[(PathBuf, Wallet, Wallet)], format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["File", "Address", "From", "To"]);
+            for (path, from, to) in results {
+                table.add_row(row![
+                    path.display(),
+                    from.public_key,
+                    params_str(from),
+                    params_str(to)
+                ]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let table: Vec<_> = results
+                .iter()
+                .map(|(path, from, to)| {
+                    json!({
+                        "file": path.display().to_string(),
+                        "address": from.public_key.to_string(),
+                        "from": params_str(from),
+                        "to": params_str(to),
+                    })
+                })
+                .collect();
+            print_json(&table)
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    cmd::{print_json, print_table, Opts, OutputFormat},
+    result::{bail, Result},
+    wallet::Wallet,
+};
+use prettytable::{format, Table};
+use serde_json::json;
+use std::{
+    ffi::OsString,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+/// Upgrade the given wallet files in place to the current file format
+/// version. The encrypted key is carried over as is so no password is
+/// needed. Every file is written to a temporary file next to it first and
+/// then renamed over the original, so an interrupted upgrade leaves the
+/// original file intact. Files that already use the current version are
+/// left untouched.
+#[derive(Debug, StructOpt)]
+pub struct Cmd {}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let mut upgrades = Vec::with_capacity(opts.files.len());
+        for path in &opts.files {
+            let wallet = super::check::read_wallet(path)?;
+            wallet.check()?;
+            let from_version = wallet.version;
+            if wallet.needs_upgrade() {
+                upgrade_file(path, &wallet)?;
+            }
+            upgrades.push((path, wallet, from_version));
+        }
+        print_upgrades(&upgrades, opts.format)
+    }
+}
+
+/// Atomically replaces the given wallet file with the given wallet in the
+/// current format, after verifying that the written file holds the same key
+fn upgrade_file(path: &Path, wallet: &Wallet) -> Result {
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or_default());
+    tmp_name.push(".upgrade");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut writer = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)?;
+        writer.set_permissions(fs::metadata(path)?.permissions())?;
+        wallet.write(&mut writer)?;
+        writer.flush()?;
+        writer.sync_all()?;
+
+        let written = Wallet::read(&mut fs::File::open(&tmp_path)?)?;
+        let same_key = match (&written.secret, &wallet.secret) {
+            (Some(written), Some(secret)) => {
+                written.encrypted == secret.encrypted
+                    && written.iv == secret.iv
+                    && written.tag == secret.tag
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        if written.public_key != wallet.public_key || !same_key {
+            bail!("Upgraded wallet does not match {}", path.display());
+        }
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+fn print_upgrades(upgrades: &[(&PathBuf, Wallet, u8)], format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["File", "Address", "From", "To", "Upgraded"]);
+            for (path, wallet, from_version) in upgrades {
+                table.add_row(row![
+                    path.display(),
+                    wallet.public_key,
+                    from_version,
+                    wallet.write_version(),
+                    wallet.needs_upgrade()
+                ]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let table: Vec<_> = upgrades
+                .iter()
+                .map(|(path, wallet, from_version)| {
+                    json!({
+                        "file": path.display().to_string(),
+                        "address": wallet.public_key.to_string(),
+                        "from_version": from_version,
+                        "to_version": wallet.write_version(),
+                        "upgraded": wallet.needs_upgrade(),
+                    })
+                })
+                .collect();
+            print_json(&table)
+        }
+    }
+}
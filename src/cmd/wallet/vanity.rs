@@ -0,0 +1,207 @@
+use crate::{
+    cmd::{create::open_output_file, get_password, verify, Opts},
+    format::{self, Format},
+    keypair::{KeyTag, KeyType, Keypair, Network, KEYTYPE_ED25519_STR, NETTYPE_MAIN_STR},
+    pwhash::PwHash,
+    result::{bail, Result},
+    wallet::Wallet,
+};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Instant,
+};
+use structopt::StructOpt;
+
+/// The longest prefix that can be searched for, including the leading
+/// characters every address of a network and key type shares
+const MAX_PREFIX_LEN: usize = 8;
+/// The number of expected attempts above which a long search is warned
+/// about
+const SLOW_SEARCH_ATTEMPTS: f64 = 10_000_000.0;
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Create a new basic wallet with an address that starts with the given
+/// prefix, ignoring case. Keypairs are generated until one matches, which
+/// takes about 58 times longer for every added character.
+#[derive(Debug, StructOpt)]
+pub struct Cmd {
+    /// The prefix the address should start with. Addresses of a network and
+    /// key type share their first characters, like "13" or "14" for
+    /// ed25519 mainnet addresses, which are part of the prefix.
+    #[structopt(long)]
+    prefix: String,
+
+    /// Number of threads to generate keypairs with
+    #[structopt(long, default_value = "4")]
+    threads: usize,
+
+    #[structopt(short, long, default_value = "wallet.key")]
+    /// Output file to store the key in
+    output: PathBuf,
+
+    #[structopt(long)]
+    /// Overwrite an existing file
+    force: bool,
+
+    #[structopt(long, default_value = NETTYPE_MAIN_STR)]
+    /// The network to generate the wallet (testnet/mainnet)
+    network: Network,
+
+    #[structopt(long, default_value = KEYTYPE_ED25519_STR)]
+    /// The type of key to generate (ecc_compact/ed25519)
+    key_type: KeyType,
+}
+
+impl Cmd {
+    pub fn run(&self, opts: Opts) -> Result {
+        let sample = Keypair::generate(self.key_tag()).public_key().to_string();
+        let attempts = expected_attempts(&self.prefix, &sample)?;
+        if attempts > SLOW_SEARCH_ATTEMPTS {
+            eprintln!(
+                "Warning: finding prefix {} takes about {:.0} attempts, which can take hours or longer",
+                self.prefix, attempts
+            );
+        }
+        // Fail before a possibly long search rather than after it
+        if !self.force && self.output.exists() {
+            bail!(
+                "{} already exists, use --force to overwrite it",
+                self.output.display()
+            );
+        }
+        let password = get_password(true)?;
+
+        let start = Instant::now();
+        let (keypair, tries) = search(self.network, self.key_type, &self.prefix, self.threads);
+        let elapsed = start.elapsed().as_secs_f64();
+        eprintln!(
+            "Found {} after {} attempts in {:.1}s ({:.0} keys/s)",
+            keypair.public_key(),
+            tries,
+            elapsed,
+            tries as f64 / elapsed.max(f64::EPSILON)
+        );
+
+        let format = format::Basic {
+            pwhash: PwHash::argon2id13_default(),
+        };
+        let wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Basic(format))?;
+        let mut writer = open_output_file(&self.output, !self.force)?;
+        wallet.write(&mut writer)?;
+        verify::print_result(&wallet, true, opts.format)
+    }
+
+    fn key_tag(&self) -> KeyTag {
+        KeyTag {
+            network: self.network,
+            key_type: self.key_type,
+        }
+    }
+}
+
+/// Whether an address starts with the given prefix, ignoring case
+fn matches_prefix(address: &str, prefix: &str) -> bool {
+    address.len() >= prefix.len()
+        && address.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+/// Checks the prefix against a sample address of the network and key type
+/// and returns the expected number of attempts to find it. Each character
+/// after the first matches one or, ignoring case, two of the 58 base58
+/// characters.
+fn expected_attempts(prefix: &str, sample: &str) -> Result<f64> {
+    if prefix.is_empty() || prefix.len() > MAX_PREFIX_LEN {
+        bail!(
+            "The prefix must be between 1 and {} characters",
+            MAX_PREFIX_LEN
+        );
+    }
+    let mut attempts = 1.0;
+    for (i, c) in prefix.chars().enumerate() {
+        let matching = BASE58_ALPHABET
+            .chars()
+            .filter(|a| a.eq_ignore_ascii_case(&c))
+            .count();
+        if matching == 0 {
+            bail!("The prefix character {} is not used in addresses", c);
+        }
+        if i == 0 {
+            // The first character is the same for all addresses
+            if !matches_prefix(sample, &c.to_string()) {
+                bail!(
+                    "Addresses for this network and key type start with {}",
+                    &sample[..1]
+                );
+            }
+            continue;
+        }
+        attempts *= 58.0 / matching as f64;
+    }
+    Ok(attempts)
+}
+
+/// Generates keypairs on the given number of threads until the address of
+/// one starts with the prefix. Returns the keypair and the number of
+/// keypairs generated.
+fn search(network: Network, key_type: KeyType, prefix: &str, threads: usize) -> (Keypair, u64) {
+    let found = Arc::new(AtomicBool::new(false));
+    let attempts = Arc::new(AtomicU64::new(0));
+    let (tx, rx) = mpsc::channel();
+    let workers: Vec<_> = (0..threads.max(1))
+        .map(|_| {
+            let found = found.clone();
+            let attempts = attempts.clone();
+            let tx = tx.clone();
+            let prefix = prefix.to_string();
+            thread::spawn(move || {
+                while !found.load(Ordering::Relaxed) {
+                    let keypair = Keypair::generate(KeyTag { network, key_type });
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    if matches_prefix(&keypair.public_key().to_string(), &prefix)
+                        && !found.swap(true, Ordering::SeqCst)
+                    {
+                        let _ = tx.send(keypair);
+                    }
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+    let keypair = rx.recv().expect("vanity search result");
+    for worker in workers {
+        let _ = worker.join();
+    }
+    (keypair, attempts.load(Ordering::SeqCst))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_attempts() {
+        let sample = "13buBykFQf5VaQtv7mWj2PBY9Lq4i1DeXhg7C4Vbu3ppzqqNkTH";
+        assert!(matches_prefix(sample, "13BUB"));
+        assert!(!matches_prefix(sample, "14bub"));
+        assert_eq!(1.0, expected_attempts("1", sample).unwrap());
+        // "3" is a single base58 character, "b" matches "b" and "B"
+        assert_eq!(58.0 * 29.0, expected_attempts("13b", sample).unwrap());
+        assert!(expected_attempts("", sample).is_err());
+        assert!(expected_attempts("13bubbbbb", sample).is_err());
+        assert!(expected_attempts("130", sample).is_err());
+        assert!(expected_attempts("2", sample).is_err());
+    }
+
+    #[test]
+    fn search_prefix() {
+        let sample = Keypair::default().public_key().to_string();
+        let (keypair, attempts) = search(Network::MainNet, KeyType::Ed25519, &sample[..1], 2);
+        assert!(keypair.public_key().to_string().starts_with(&sample[..1]));
+        assert!(attempts >= 1);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::result::{bail, Result};
+
+/// Highest resolution supported by H3
+pub const MAX_RESOLUTION: u8 = 15;
+
+const CELL_MODE: u64 = 1;
+const NUM_BASE_CELLS: u64 = 122;
+const PENTAGON_BASE_CELLS: [u64; 12] = [4, 14, 24, 38, 49, 58, 63, 72, 83, 97, 107, 117];
+// The digit value used for resolutions finer than the resolution of a cell
+const UNUSED_DIGIT: u64 = 7;
+// The "K" axes digit which does not exist for the children of a pentagon
+const K_AXES_DIGIT: u64 = 1;
+
+/// Parses and validates a hex encoded H3 cell index as used for hotspot
+/// locations. The mode, resolution, base cell and all index digits are
+/// checked to be well-formed.
+pub fn parse_cell(location: &str) -> Result<u64> {
+    if location.len() != 15 {
+        bail!(
+            "Invalid h3 index {}: expected 15 hex characters, got {}",
+            location,
+            location.len()
+        );
+    }
+    let index = match u64::from_str_radix(location, 16) {
+        Ok(index) => index,
+        Err(_) => bail!("Invalid h3 index {}: not a hex value", location),
+    };
+    if (index >> 63) != 0 {
+        bail!("Invalid h3 index {}: high bit set", location);
+    }
+    if (index >> 59) & 0xf != CELL_MODE {
+        bail!("Invalid h3 index {}: not a cell index", location);
+    }
+    if (index >> 56) & 0x7 != 0 {
+        bail!("Invalid h3 index {}: reserved bits set", location);
+    }
+    let base_cell = (index >> 45) & 0x7f;
+    if base_cell >= NUM_BASE_CELLS {
+        bail!("Invalid h3 index {}: invalid base cell", location);
+    }
+    let resolution = resolution(index);
+    let mut leading_digit = None;
+    for res in 1..=MAX_RESOLUTION {
+        let digit = digit(index, res);
+        if res <= resolution {
+            if digit == UNUSED_DIGIT {
+                bail!("Invalid h3 index {}: invalid digit at {}", location, res);
+            }
+            if leading_digit.is_none() && digit != 0 {
+                leading_digit = Some(digit);
+            }
+        } else if digit != UNUSED_DIGIT {
+            bail!("Invalid h3 index {}: unused digit at {} set", location, res);
+        }
+    }
+    if PENTAGON_BASE_CELLS.contains(&base_cell) && leading_digit == Some(K_AXES_DIGIT) {
+        bail!(
+            "Invalid h3 index {}: deleted pentagon subsequence",
+            location
+        );
+    }
+    Ok(index)
+}
+
+/// Returns the resolution of a given H3 index
+pub fn resolution(index: u64) -> u8 {
+    ((index >> 52) & 0xf) as u8
+}
+
+fn digit(index: u64, res: u8) -> u64 {
+    (index >> (3 * (MAX_RESOLUTION - res) as u64)) & 0x7
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_cells() {
+        let index = parse_cell("8928308280fffff").expect("res 9 cell");
+        assert_eq!(9, resolution(index));
+        let index = parse_cell("8c28308280c15ff").expect("res 12 cell");
+        assert_eq!(12, resolution(index));
+    }
+
+    #[test]
+    fn malformed_cells() {
+        // Wrong length
+        assert!(parse_cell("8c28308280c15f").is_err());
+        assert!(parse_cell("08c28308280c15ff").is_err());
+        // Not hex
+        assert!(parse_cell("8c28308280c15fg").is_err());
+        // Not a cell mode index
+        assert!(parse_cell("0c28308280c15ff").is_err());
+        // Invalid digit within the resolution
+        assert!(parse_cell("8c28308280c1fff").is_err());
+        // Digits past the resolution not set to unused
+        assert!(parse_cell("8c28308280c15f0").is_err());
+        // Base cell out of range
+        assert!(parse_cell("89fe308280fffff").is_err());
+    }
+}
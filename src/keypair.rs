@@ -1,4 +1,7 @@
-use crate::{result::Result, traits::ReadWrite};
+use crate::{
+    result::{anyhow, bail, Result},
+    traits::ReadWrite,
+};
 use byteorder::ReadBytesExt;
 use std::{convert::TryFrom, io};
 
@@ -7,6 +10,7 @@ pub use helium_crypto::{
     KeyTag, KeyType, Network, PublicKey, Sign, Verify, KEYTYPE_ED25519_STR, NETTYPE_MAIN_STR,
     PUBLIC_KEY_LENGTH,
 };
+use sodiumoxide::crypto::{box_, sealedbox, sign};
 
 #[derive(Debug, PartialEq)]
 pub enum Keypair {
@@ -64,6 +68,89 @@ impl Keypair {
             Self::EccCompact(key) => Ok(key.sign(msg)?),
         }
     }
+
+    /// Decrypts a message that was sealed to the public key of this
+    /// keypair using `seal_to`.
+    pub fn open_sealed(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        init();
+        let key = match self {
+            Self::Ed25519(key) => key,
+            Self::EccCompact(_) => bail!("Sealed messages are only supported for ed25519 keys"),
+        };
+        let sk = sign::SecretKey::from_slice(&key.to_bytes()[1..])
+            .ok_or_else(|| anyhow!("Invalid ed25519 secret key"))?;
+        let sk = sign::to_curve25519_sk(&sk).map_err(|_| anyhow!("Invalid ed25519 secret key"))?;
+        let pk = curve25519_pk(&key.public_key)?;
+        sealedbox::open(sealed, &pk, &sk)
+            .map_err(|_| anyhow!("Failed to decrypt message, it is not sealed to this wallet"))
+    }
+}
+
+/// The key type of the given public key
+pub fn key_type(public_key: &PublicKey) -> Result<KeyType> {
+    Ok(KeyType::try_from(public_key.to_vec()[0])?)
+}
+
+/// The length in bytes of a keypair as written by `ReadWrite::write` for
+/// the key type of the given public key
+pub fn keypair_len(public_key: &PublicKey) -> Result<usize> {
+    let bytes = public_key.to_vec();
+    let keypair_len = match key_type(public_key)? {
+        KeyType::Ed25519 => ed25519::KEYPAIR_LENGTH,
+        KeyType::EccCompact => ecc_compact::KEYPAIR_LENGTH,
+    };
+    Ok(keypair_len + bytes.len())
+}
+
+/// Encrypts a message so only the holder of the private key for the given
+/// ed25519 public key can decrypt it. The ed25519 key is converted to its
+/// X25519 equivalent and the message is encrypted with a key derived from
+/// an ephemeral Diffie-Hellman exchange (a libsodium sealed box).
+pub fn seal_to(public_key: &PublicKey, msg: &[u8]) -> Result<Vec<u8>> {
+    init();
+    Ok(sealedbox::seal(msg, &curve25519_pk(public_key)?))
+}
+
+fn curve25519_pk(public_key: &PublicKey) -> Result<box_::PublicKey> {
+    let bytes = public_key.to_vec();
+    if key_type(public_key)? != KeyType::Ed25519 {
+        bail!("Sealed messages are only supported for ed25519 keys");
+    }
+    let pk = sign::PublicKey::from_slice(&bytes[1..])
+        .ok_or_else(|| anyhow!("Invalid ed25519 public key"))?;
+    sign::to_curve25519_pk(&pk).map_err(|_| anyhow!("Invalid ed25519 public key"))
+}
+
+/// The DER encoded SubjectPublicKeyInfo prefix of an ed25519 public key
+/// (RFC 8410). The 32 bytes of the key follow it.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Encodes the given public key as a DER SubjectPublicKeyInfo structure,
+/// the form most generic crypto tooling expects. Only ed25519 keys are
+/// supported.
+pub fn public_key_der(public_key: &PublicKey) -> Result<Vec<u8>> {
+    let bytes = public_key.to_vec();
+    if key_type(public_key)? != KeyType::Ed25519 {
+        bail!("DER encoding is only supported for ed25519 keys");
+    }
+    let mut der = ED25519_SPKI_PREFIX.to_vec();
+    der.extend_from_slice(&bytes[1..]);
+    Ok(der)
+}
+
+/// Encodes the given public key as a PEM "PUBLIC KEY" block of its DER
+/// SubjectPublicKeyInfo encoding
+pub fn public_key_pem(public_key: &PublicKey) -> Result<String> {
+    let der = base64::encode(public_key_der(public_key)?);
+    let mut pem = String::from("-----BEGIN PUBLIC KEY-----\n");
+    for line in der.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line)?);
+        pem.push('\n');
+    }
+    pem.push_str("-----END PUBLIC KEY-----\n");
+    Ok(pem)
 }
 
 impl ReadWrite for Keypair {
@@ -120,6 +207,22 @@ mod tests {
         assert_eq!(keypair, decoded);
     }
 
+    #[test]
+    fn encoded_keypair_len() {
+        for key_type in &[KeyType::Ed25519, KeyType::EccCompact] {
+            let keypair = Keypair::generate(KeyTag {
+                network: Network::MainNet,
+                key_type: *key_type,
+            });
+            let mut buffer = Vec::new();
+            keypair.write(&mut buffer).expect("keypair write");
+            assert_eq!(
+                buffer.len(),
+                keypair_len(keypair.public_key()).expect("keypair length")
+            );
+        }
+    }
+
     #[test]
     fn roundtrip_public_key() {
         let pk = Keypair::default();
@@ -133,6 +236,46 @@ mod tests {
         assert_eq!(pk.public_key(), &decoded);
     }
 
+    #[test]
+    fn roundtrip_sealed() {
+        let keypair = Keypair::default();
+        let sealed = seal_to(keypair.public_key(), b"secret seed").expect("seal");
+        assert_eq!(
+            b"secret seed".to_vec(),
+            keypair.open_sealed(&sealed).expect("open")
+        );
+        assert!(Keypair::default().open_sealed(&sealed).is_err());
+
+        let ecc = Keypair::generate(KeyTag {
+            network: Network::MainNet,
+            key_type: KeyType::EccCompact,
+        });
+        assert!(seal_to(ecc.public_key(), b"secret seed").is_err());
+    }
+
+    #[test]
+    fn roundtrip_pem_public_key() {
+        let keypair = Keypair::default();
+        let pem = public_key_pem(keypair.public_key()).expect("pem");
+        let body: String = pem
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .collect();
+        assert!(pem.starts_with("-----BEGIN PUBLIC KEY-----\n"));
+        assert!(pem.ends_with("-----END PUBLIC KEY-----\n"));
+
+        let der = base64::decode(&body).expect("base64");
+        assert_eq!(public_key_der(keypair.public_key()).expect("der"), der);
+        assert_eq!(ED25519_SPKI_PREFIX[..], der[..12]);
+        assert_eq!(keypair.public_key().to_vec()[1..], der[12..]);
+
+        let ecc = Keypair::generate(KeyTag {
+            network: Network::MainNet,
+            key_type: KeyType::EccCompact,
+        });
+        assert!(public_key_der(ecc.public_key()).is_err());
+    }
+
     #[test]
     fn roundtrip_b58_public_key() {
         let pk = Keypair::default();
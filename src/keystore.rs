@@ -0,0 +1,253 @@
+//! Reading keys from JSON keystore files written by other Helium tools.
+//!
+//! A keystore holds a single private key encrypted with a key derived from
+//! a password:
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "address": "<base58 address of the key>",
+//!     "crypto": {
+//!         "cipher": "aes-256-gcm",
+//!         "cipherparams": { "iv": "<hex, 12 bytes>", "tag": "<hex, 16 bytes>" },
+//!         "ciphertext": "<hex>",
+//!         "kdf": "pbkdf2",
+//!         "kdfparams": { "prf": "hmac-sha256", "c": 100000, "salt": "<hex>", "dklen": 32 }
+//!     }
+//! }
+//! ```
+//!
+//! The supported key derivation functions are `pbkdf2` with
+//! `hmac-sha256`, and `argon2id` with the parameters
+//! `{ "opslimit": <n>, "memlimit": <bytes>, "salt": "<hex, 16 bytes>" }`.
+//! The derived key must be 32 bytes and is used as the AES-256-GCM key
+//! without associated data. The plaintext is either the 32 byte private
+//! key, like an ed25519 seed, or a keypair in the binary encoding of this
+//! crate. The network and key type of the key are taken from the address,
+//! and the decrypted key has to match it.
+
+use crate::{
+    keypair::{self, KeyTag, Keypair, PublicKey},
+    result::{anyhow, bail, Result},
+    traits::ReadWrite,
+    wallet::AesKey,
+};
+use aes_gcm::{
+    aead::{generic_array::GenericArray, NewAead},
+    AeadInPlace, Aes256Gcm,
+};
+use hmac::Hmac;
+use serde_derive::Deserialize;
+use sha2::Sha256;
+use sodiumoxide::crypto::pwhash::argon2id13;
+use std::io::{self, Cursor};
+use zeroize::Zeroizing;
+
+/// The keystore version this module reads
+pub const KEYSTORE_VERSION: u32 = 1;
+
+#[derive(Debug, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub address: String,
+    pub crypto: Crypto,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Crypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    #[serde(flatten)]
+    pub kdf: Kdf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+    pub tag: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kdf", content = "kdfparams", rename_all = "lowercase")]
+pub enum Kdf {
+    Pbkdf2 {
+        prf: String,
+        c: u32,
+        salt: String,
+        dklen: usize,
+    },
+    Argon2id {
+        opslimit: usize,
+        memlimit: usize,
+        salt: String,
+    },
+}
+
+impl Kdf {
+    /// Derives the encryption key from the password. Fails for parameters
+    /// that are not supported.
+    fn derive_key(&self, password: &[u8]) -> Result<Zeroizing<AesKey>> {
+        let mut key = Zeroizing::new(AesKey::default());
+        match self {
+            Self::Pbkdf2 {
+                prf,
+                c,
+                salt,
+                dklen,
+            } => {
+                if prf != "hmac-sha256" {
+                    bail!("Unsupported keystore pbkdf2 prf {}", prf);
+                }
+                if *dklen != key.len() {
+                    bail!("Unsupported keystore key length {}", dklen);
+                }
+                if *c == 0 {
+                    bail!("Invalid keystore pbkdf2 iteration count");
+                }
+                let salt = decode_hex("salt", salt)?;
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(password, &salt, *c, &mut *key);
+            }
+            Self::Argon2id {
+                opslimit,
+                memlimit,
+                salt,
+            } => {
+                let salt = argon2id13::Salt::from_slice(&decode_hex("salt", salt)?)
+                    .ok_or_else(|| anyhow!("Invalid keystore argon2id salt length"))?;
+                argon2id13::derive_key(
+                    &mut *key,
+                    password,
+                    &salt,
+                    argon2id13::OpsLimit(*opslimit),
+                    argon2id13::MemLimit(*memlimit),
+                )
+                .map_err(|_| anyhow!("Failed to hash keystore password"))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+impl Keystore {
+    pub fn read(reader: &mut dyn io::Read) -> Result<Self> {
+        let keystore: Self =
+            serde_json::from_reader(reader).map_err(|e| anyhow!("Invalid keystore: {}", e))?;
+        if keystore.version != KEYSTORE_VERSION {
+            bail!("Unsupported keystore version {}", keystore.version);
+        }
+        if keystore.crypto.cipher != "aes-256-gcm" {
+            bail!("Unsupported keystore cipher {}", keystore.crypto.cipher);
+        }
+        Ok(keystore)
+    }
+
+    /// The public key of the address the keystore is for
+    pub fn public_key(&self) -> Result<PublicKey> {
+        self.address
+            .parse()
+            .map_err(|_| anyhow!("Invalid keystore address {}", self.address))
+    }
+
+    /// Decrypts the key in the keystore with the given password and checks
+    /// that it matches the address of the keystore
+    pub fn decrypt(&self, password: &[u8]) -> Result<Keypair> {
+        let public_key = self.public_key()?;
+        let crypto = &self.crypto;
+        let key = crypto.kdf.derive_key(password)?;
+        let iv = decode_hex("iv", &crypto.cipherparams.iv)?;
+        let tag = decode_hex("tag", &crypto.cipherparams.tag)?;
+        if iv.len() != 12 || tag.len() != 16 {
+            bail!("Invalid keystore cipher parameters");
+        }
+        let mut buffer = Zeroizing::new(decode_hex("ciphertext", &crypto.ciphertext)?);
+
+        let aead = Aes256Gcm::new(GenericArray::from_slice(&key[..]));
+        aead.decrypt_in_place_detached(
+            GenericArray::from_slice(&iv),
+            &[],
+            &mut *buffer,
+            GenericArray::from_slice(&tag),
+        )
+        .map_err(|_| anyhow!("Failed to decrypt keystore, check the password"))?;
+
+        let keypair = if buffer.len() == 32 {
+            let key_tag = KeyTag {
+                network: public_key.network,
+                key_type: keypair::key_type(&public_key)?,
+            };
+            Keypair::generate_from_entropy(key_tag, &buffer)?
+        } else {
+            Keypair::read(&mut Cursor::new(&buffer[..]))?
+        };
+        if keypair.public_key() != &public_key {
+            bail!("Keystore key does not match its address {}", self.address);
+        }
+        Ok(keypair)
+    }
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>> {
+    hex::decode(value).map_err(|_| anyhow!("Invalid hex in keystore {}", field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Encrypts the ed25519 seed 0x00..0x1f with the password "keystore
+    // password"
+    const SAMPLE_KEYSTORE: &str = r#"{
+        "version": 1,
+        "address": "12yLskQXDDVs9PuVj3zzLkyofNrutRe4Mk6X893bQGqjF5pioAY",
+        "crypto": {
+            "cipher": "aes-256-gcm",
+            "cipherparams": {
+                "iv": "0102030405060708090a0b0c",
+                "tag": "ab361ec30f2bbfda64b8716611710ddf"
+            },
+            "ciphertext": "66ae8e543b3ebeefcd94ca84bd19bbea9b2f3fa3a4e273b24f5d0b223f8509bd",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "prf": "hmac-sha256",
+                "c": 1000,
+                "salt": "a1b2c3d4e5f60718293a4b5c6d7e8f90",
+                "dklen": 32
+            }
+        }
+    }"#;
+
+    #[test]
+    fn decrypt_keystore() {
+        let keystore = Keystore::read(&mut SAMPLE_KEYSTORE.as_bytes()).expect("keystore");
+        let keypair = keystore.decrypt(b"keystore password").expect("decrypt");
+        assert_eq!(
+            "12yLskQXDDVs9PuVj3zzLkyofNrutRe4Mk6X893bQGqjF5pioAY",
+            keypair.public_key().to_string()
+        );
+        let seed: Vec<u8> = (0..32).collect();
+        let expected = Keypair::generate_from_entropy(KeyTag::default(), &seed).unwrap();
+        assert_eq!(expected, keypair);
+
+        assert!(keystore.decrypt(b"wrong password").is_err());
+
+        // A keystore for a different address fails even with the password
+        let other = Keypair::default().public_key().to_string();
+        let keystore = Keystore::read(
+            &mut SAMPLE_KEYSTORE
+                .replace(
+                    "12yLskQXDDVs9PuVj3zzLkyofNrutRe4Mk6X893bQGqjF5pioAY",
+                    &other,
+                )
+                .as_bytes(),
+        )
+        .expect("keystore");
+        assert!(keystore.decrypt(b"keystore password").is_err());
+
+        let unsupported = SAMPLE_KEYSTORE.replace("aes-256-gcm", "aes-128-ctr");
+        assert!(Keystore::read(&mut unsupported.as_bytes()).is_err());
+        let unsupported = SAMPLE_KEYSTORE.replace("hmac-sha256", "hmac-sha512");
+        let keystore = Keystore::read(&mut unsupported.as_bytes()).expect("keystore");
+        assert!(keystore.decrypt(b"keystore password").is_err());
+    }
+}
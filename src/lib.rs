@@ -7,12 +7,17 @@ extern crate lazy_static;
 #[macro_use]
 extern crate serde_json;
 
+pub mod api;
 pub mod cmd;
 pub mod format;
+pub mod h3;
 pub mod keypair;
+pub mod keystore;
 pub mod mnemonic;
 pub mod pwhash;
 pub mod result;
+pub mod signer;
 pub mod staking;
 pub mod traits;
+pub mod txn;
 pub mod wallet;
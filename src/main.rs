@@ -1,9 +1,12 @@
 use helium_wallet::{
+    api,
     cmd::{
-        balance, burn, create, hotspots, htlc, info, multisig, oracle, oui, pay, request,
-        securities, upgrade, validators, vars, verify, Opts,
+        accounts, balance, build, burn, consolidate, convert, cosign, create, dc, dc_cost,
+        defaults, export, fee, fee_config, fill_nonce, history, hotspots, htlc, info, interrupt,
+        multisig, oracle, oui, pay, replace, request, rotate, sealed, securities, set_color,
+        submit, track, upgrade, validators, vars, verify, verify_address, wallet, Opts,
     },
-    result::Result,
+    result::{bail, error_json, Result},
 };
 use std::process;
 use structopt::StructOpt;
@@ -20,48 +23,106 @@ pub struct Cli {
 #[derive(Debug, StructOpt)]
 pub enum Cmd {
     Info(info::Cmd),
+    Export(export::Cmd),
     Verify(verify::Cmd),
+    VerifyAddress(verify_address::Cmd),
+    Wallet(wallet::Cmd),
     Balance(balance::Cmd),
+    Accounts(accounts::Cmd),
+    Dc(dc::Cmd),
+    History(history::Cmd),
     Hotspots(Box<hotspots::Cmd>),
     Create(create::Cmd),
     Upgrade(upgrade::Cmd),
     Pay(pay::Cmd),
+    Build(build::Cmd),
     Htlc(htlc::Cmd),
     Oui(oui::Cmd),
     Oracle(oracle::Cmd),
     Securities(securities::Cmd),
     Burn(burn::Cmd),
     Multisig(multisig::Cmd),
+    Cosign(cosign::Cmd),
+    Submit(submit::Cmd),
+    Track(track::Cmd),
+    Rotate(rotate::Cmd),
+    Consolidate(consolidate::Cmd),
     Request(request::Cmd),
     Vars(vars::Cmd),
     Validators(validators::Cmd),
+    FeeConfig(fee_config::Cmd),
+    DcCost(dc_cost::Cmd),
+    Convert(convert::Cmd),
+    Fee(fee::Cmd),
+    FillNonce(fill_nonce::Cmd),
+    Replace(replace::Cmd),
+    EncryptTo(sealed::EncryptTo),
+    DecryptFrom(sealed::DecryptFrom),
 }
 
 fn main() {
-    let cli = Cli::from_args();
+    let args = match defaults::args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+    let cli = Cli::from_iter(args);
+    let is_json = cli.opts.is_json();
     if let Err(e) = run(cli) {
-        eprintln!("error: {}", e);
+        if is_json {
+            // Json consumers get a parseable error on stdout
+            println!("{}", error_json(&e));
+        } else {
+            eprintln!("error: {}", e);
+        }
         process::exit(1);
     }
 }
 
 fn run(cli: Cli) -> Result {
+    interrupt::install()?;
+    set_color(cli.opts.color());
+    api::set_tls(cli.opts.tls()?);
+    if cli.opts.has_signer_command() && !matches!(cli.cmd, Cmd::Pay(_) | Cmd::Build(_)) {
+        bail!("--signer-command is only supported by pay and build");
+    }
     match cli.cmd {
         Cmd::Info(cmd) => cmd.run(cli.opts),
+        Cmd::Export(cmd) => cmd.run(cli.opts),
         Cmd::Verify(cmd) => cmd.run(cli.opts),
+        Cmd::VerifyAddress(cmd) => cmd.run(cli.opts),
+        Cmd::Wallet(cmd) => cmd.run(cli.opts),
         Cmd::Balance(cmd) => cmd.run(cli.opts),
+        Cmd::Dc(cmd) => cmd.run(cli.opts),
+        Cmd::History(cmd) => cmd.run(cli.opts),
         Cmd::Hotspots(cmd) => cmd.run(cli.opts),
         Cmd::Create(cmd) => cmd.run(cli.opts),
         Cmd::Upgrade(cmd) => cmd.run(cli.opts),
         Cmd::Pay(cmd) => cmd.run(cli.opts),
+        Cmd::Build(cmd) => cmd.run(cli.opts),
         Cmd::Htlc(cmd) => cmd.run(cli.opts),
         Cmd::Oui(cmd) => cmd.run(cli.opts),
         Cmd::Oracle(cmd) => cmd.run(cli.opts),
         Cmd::Securities(cmd) => cmd.run(cli.opts),
         Cmd::Burn(cmd) => cmd.run(cli.opts),
         Cmd::Multisig(cmd) => cmd.run(cli.opts),
+        Cmd::Cosign(cmd) => cmd.run(cli.opts),
+        Cmd::Submit(cmd) => cmd.run(cli.opts),
+        Cmd::Track(cmd) => cmd.run(cli.opts),
+        Cmd::Rotate(cmd) => cmd.run(cli.opts),
+        Cmd::Consolidate(cmd) => cmd.run(cli.opts),
         Cmd::Request(cmd) => cmd.run(cli.opts),
         Cmd::Vars(cmd) => cmd.run(cli.opts),
         Cmd::Validators(cmd) => cmd.run(cli.opts),
+        Cmd::FeeConfig(cmd) => cmd.run(cli.opts),
+        Cmd::DcCost(cmd) => cmd.run(cli.opts),
+        Cmd::Convert(cmd) => cmd.run(cli.opts),
+        Cmd::Fee(cmd) => cmd.run(cli.opts),
+        Cmd::FillNonce(cmd) => cmd.run(cli.opts),
+        Cmd::Replace(cmd) => cmd.run(cli.opts),
+        Cmd::EncryptTo(cmd) => cmd.run(cli.opts),
+        Cmd::DecryptFrom(cmd) => cmd.run(cli.opts),
     }
 }
@@ -1,45 +1,382 @@
 use crate::result::{bail, Result};
+use hmac::{Hmac, Mac, NewMac};
 use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashSet,
+    error, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "slip39")]
+pub mod slip39;
 
 include!(concat!(env!("OUT_DIR"), "/english.rs"));
 
 type WordList = &'static [&'static str];
 
+/// The number of words in a wordlist, each word encodes 11 bits
+const WORDLIST_LEN: usize = 2048;
+/// The shortest abbreviation of a seed word that is accepted. Words in the
+/// BIP39 wordlists are unique in their first four letters.
+const MIN_PREFIX_LEN: usize = 4;
+
+/// The wordlist to look up seed words in. The embedded English wordlist is
+/// the default, a custom wordlist with one word per line can be loaded from
+/// a file, for example for tests or a modified dictionary.
 pub enum Language {
     English,
+    Custom(PathBuf),
 }
 
-fn get_wordlist(language: Language) -> WordList {
-    match language {
-        Language::English => WORDS_ENGLISH,
+/// How the checksum bits of a mnemonic are verified
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Checksum {
+    /// All zero checksum bits, as written by the Helium mobile wallet
+    Zero,
+    /// The BIP39 checksum, the leading bits of the SHA256 hash of the
+    /// entropy. A mismatch reports any swap of two adjacent words that
+    /// would make the checksum valid.
+    Bip39,
+}
+
+/// How seed words are matched against the wordlist
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WordMatch {
+    /// Every seed word has to be a word in the wordlist
+    Exact,
+    /// A seed word can also be abbreviated to four or more letters that
+    /// start exactly one word in the wordlist. Abbreviations make a typo
+    /// more likely to silently resolve to a different word, so they have
+    /// to be asked for explicitly.
+    Prefix,
+}
+
+/// The number of words in a mnemonic
+const MNEMONIC_LEN: usize = 12;
+/// The most words suggested for an unknown seed word
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Why seed words could not be converted to entropy. Returned by the
+/// mnemonic functions inside their error, from which it can be recovered
+/// with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MnemonicError {
+    /// The mnemonic does not have the expected number of words
+    WrongWordCount { got: usize, expected: usize },
+    /// The word at the given position, starting at 0, is not in the
+    /// wordlist, or with `WordMatch::Prefix` not the abbreviation of
+    /// exactly one word in it. The suggestions are wordlist words it may
+    /// have been meant as.
+    UnknownWord {
+        index: usize,
+        word: String,
+        suggestions: Vec<String>,
+    },
+    /// All words are known but their checksum bits are wrong. The swaps
+    /// are the positions of the first of two adjacent words that give a
+    /// valid checksum when swapped.
+    ChecksumMismatch { swaps: Vec<usize> },
+}
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongWordCount { got, expected } => write!(
+                f,
+                "Invalid number of seed words {}, expected {}",
+                got, expected
+            ),
+            Self::UnknownWord {
+                index,
+                word,
+                suggestions,
+            } => {
+                write!(
+                    f,
+                    "Seed word {} ({}) not found in wordlist",
+                    index + 1,
+                    word
+                )?;
+                if !suggestions.is_empty() {
+                    write!(f, ", did you mean {}?", suggestions.join(" or "))?;
+                }
+                Ok(())
+            }
+            Self::ChecksumMismatch { swaps } if swaps.is_empty() => f.write_str("invalid checksum"),
+            Self::ChecksumMismatch { swaps } => {
+                let swaps: Vec<String> = swaps
+                    .iter()
+                    .map(|i| format!("words {} and {}", i + 1, i + 2))
+                    .collect();
+                write!(
+                    f,
+                    "invalid checksum, did you mean to swap {}?",
+                    swaps.join(" or ")
+                )
+            }
+        }
     }
 }
 
+impl error::Error for MnemonicError {}
+
 /// Converts a 12 word mnemonic to a entropy that can be used to
-/// generate a keypair
+/// generate a keypair. Invalid seed words fail with a `MnemonicError`.
 pub fn mnemonic_to_entropy(words: Vec<String>) -> Result<[u8; 32]> {
-    if words.len() != 12 {
-        bail!("Invalid number of seed words");
+    mnemonic_to_entropy_in(words, &Language::English)
+}
+
+/// Converts a 12 word mnemonic in the English wordlist to entropy,
+/// matching the words and verifying the checksum bits as given
+pub fn mnemonic_to_entropy_checked(
+    words: Vec<String>,
+    checksum: Checksum,
+    matching: WordMatch,
+) -> Result<[u8; 32]> {
+    words_to_entropy_with(&words, WORDS_ENGLISH, checksum, matching)
+}
+
+/// Converts a 12 word mnemonic to entropy, looking up the words in the
+/// wordlist of the given language
+pub fn mnemonic_to_entropy_in(words: Vec<String>, language: &Language) -> Result<[u8; 32]> {
+    match language {
+        Language::English => words_to_entropy(&words, WORDS_ENGLISH),
+        Language::Custom(path) => words_to_entropy(&words, &read_wordlist(path)?[..]),
     }
-    let wordlist = get_wordlist(Language::English);
+}
 
-    let mut bit_vec = Vec::with_capacity(words.len());
-    for word in words.iter() {
-        let idx_bits = match wordlist.iter().position(|s| *s == word.to_lowercase()) {
-            Some(idx) => format!("{:011b}", idx),
-            _ => bail!("Seed word {} not found in wordlist", word),
-        };
-        bit_vec.push(idx_bits);
+/// Reads a wordlist with one word per line from a file. Duplicate words
+/// would make a mnemonic ambiguous and are rejected, as are lists with
+/// more words than 11 bits can index.
+pub fn read_wordlist(path: &Path) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path)?;
+    let words: Vec<String> = contents
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    check_wordlist(&words)?;
+    Ok(words)
+}
+
+fn check_wordlist(words: &[String]) -> Result {
+    if words.is_empty() || words.len() > WORDLIST_LEN {
+        bail!(
+            "Invalid wordlist with {} words, expected at most {}",
+            words.len(),
+            WORDLIST_LEN
+        );
+    }
+    let mut seen = HashSet::with_capacity(words.len());
+    for word in words {
+        if !seen.insert(word) {
+            bail!("Duplicate word {} in wordlist", word);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the index of a word in the given wordlist, ignoring case
+pub fn find_word_in<S: AsRef<str>>(wordlist: &[S], word: &str) -> Option<usize> {
+    let word = word.to_lowercase();
+    wordlist.iter().position(|s| s.as_ref() == word)
+}
+
+/// How a given seed word was resolved to a word in the wordlist
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    /// The seed word as given
+    pub input: String,
+    /// The wordlist word it was resolved to
+    pub word: String,
+    /// The index of the word in the wordlist
+    pub index: usize,
+    /// Whether the given word was an abbreviation of the resolved word
+    pub prefix: bool,
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.prefix {
+            write!(f, "{} -> {} (prefix)", self.input, self.word)
+        } else {
+            f.write_str(&self.word)
+        }
+    }
+}
+
+/// Resolves a seed word in the given wordlist. An exact match is preferred,
+/// otherwise with `WordMatch::Prefix` a word of at least four letters is
+/// resolved to the one word it is a prefix of.
+pub fn resolve_word_in<S: AsRef<str>>(
+    wordlist: &[S],
+    word: &str,
+    matching: WordMatch,
+) -> Result<Resolution> {
+    match lookup_word(wordlist, word, matching) {
+        Ok(resolution) => Ok(resolution),
+        Err(suggestions) if suggestions.is_empty() => {
+            bail!("Seed word {} not found in wordlist", word)
+        }
+        Err(suggestions) => bail!(
+            "Seed word {} not found in wordlist, did you mean {}?",
+            word,
+            suggestions.join(" or ")
+        ),
+    }
+}
+
+/// Resolves the word at the given position of a mnemonic, failing with an
+/// `UnknownWord` error for a word that can not be resolved
+fn resolve_word_at<S: AsRef<str>>(
+    wordlist: &[S],
+    index: usize,
+    word: &str,
+    matching: WordMatch,
+) -> std::result::Result<Resolution, MnemonicError> {
+    lookup_word(wordlist, word, matching).map_err(|suggestions| MnemonicError::UnknownWord {
+        index,
+        word: word.to_string(),
+        suggestions,
+    })
+}
+
+/// Looks up a seed word like `resolve_word_in`. If the word can not be
+/// resolved the words it may have been meant as are returned: the words
+/// it is a prefix of, or else the words that are one typo away.
+fn lookup_word<S: AsRef<str>>(
+    wordlist: &[S],
+    word: &str,
+    matching: WordMatch,
+) -> std::result::Result<Resolution, Vec<String>> {
+    let resolution = |index: usize, prefix| Resolution {
+        input: word.to_string(),
+        word: wordlist[index].as_ref().to_string(),
+        index,
+        prefix,
+    };
+    if let Some(index) = find_word_in(wordlist, word) {
+        return Ok(resolution(index, false));
+    }
+    let lower = word.to_lowercase();
+    let matches: Vec<usize> = if lower.is_empty() {
+        vec![]
+    } else {
+        wordlist
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.as_ref().starts_with(&lower))
+            .map(|(index, _)| index)
+            .collect()
+    };
+    if matching == WordMatch::Prefix
+        && matches.len() == 1
+        && lower.chars().count() >= MIN_PREFIX_LEN
+    {
+        return Ok(resolution(matches[0], true));
     }
-    let bits = bit_vec.join("");
+    let suggestions: Vec<String> = if matches.is_empty() {
+        wordlist
+            .iter()
+            .map(|s| s.as_ref())
+            .filter(|s| edit_distance(s, &lower) == 1)
+            .map(str::to_string)
+            .collect()
+    } else {
+        matches
+            .iter()
+            .map(|index| wordlist[*index].as_ref().to_string())
+            .collect()
+    };
+    Err(suggestions.into_iter().take(MAX_SUGGESTIONS).collect())
+}
+
+/// The number of single character insertions, deletions and substitutions
+/// that turn one word into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Resolves the given seed words in the English wordlist, allowing
+/// abbreviations, to report how abbreviated words were interpreted
+pub fn resolve_words(words: &[String]) -> Result<Vec<Resolution>> {
+    Ok(resolve_words_in(words, WORDS_ENGLISH, WordMatch::Prefix)?)
+}
+
+fn resolve_words_in<S: AsRef<str>>(
+    words: &[String],
+    wordlist: &[S],
+    matching: WordMatch,
+) -> std::result::Result<Vec<Resolution>, MnemonicError> {
+    words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| resolve_word_at(wordlist, index, word, matching))
+        .collect()
+}
+
+fn words_to_entropy<S: AsRef<str>>(words: &[String], wordlist: &[S]) -> Result<[u8; 32]> {
+    words_to_entropy_with(words, wordlist, Checksum::Zero, WordMatch::Exact)
+}
+
+fn words_to_entropy_with<S: AsRef<str>>(
+    words: &[String],
+    wordlist: &[S],
+    checksum: Checksum,
+    matching: WordMatch,
+) -> Result<[u8; 32]> {
+    if words.len() != MNEMONIC_LEN {
+        return Err(MnemonicError::WrongWordCount {
+            got: words.len(),
+            expected: MNEMONIC_LEN,
+        }
+        .into());
+    }
+
+    let indices: Vec<usize> = resolve_words_in(words, wordlist, matching)?
+        .iter()
+        .map(|r| r.index)
+        .collect();
+    let (entropy_base, checksum_bits) = split_entropy(&indices);
+    match checksum {
+        // The mobile wallet does not calculate the checksum bits right so
+        // they always and up being all 0
+        Checksum::Zero if checksum_bits != "0000" => {
+            return Err(MnemonicError::ChecksumMismatch { swaps: vec![] }.into())
+        }
+        Checksum::Bip39 if checksum_bits != bip39_checksum(&entropy_base) => {
+            return Err(MnemonicError::ChecksumMismatch {
+                swaps: adjacent_swaps(&indices),
+            }
+            .into())
+        }
+        _ => (),
+    }
+
+    let mut entropy_bytes = [0u8; 32];
+    entropy_bytes[..16].copy_from_slice(&entropy_base);
+    entropy_bytes[16..].copy_from_slice(&entropy_base);
+
+    Ok(entropy_bytes)
+}
+
+/// Splits the bits of the given word indices into the entropy and the
+/// checksum bits
+fn split_entropy(indices: &[usize]) -> ([u8; 16], String) {
+    let bits: String = indices.iter().map(|i| format!("{:011b}", i)).collect();
 
     let divider_index: usize = ((bits.len() as f64 / 33.0) * 32.0).floor() as usize;
     let (entropy_bits, checksum_bits) = bits.split_at(divider_index);
-    // The mobile wallet does not calculate the checksum bits right so
-    // they always and up being all 0
-    if checksum_bits != "0000" {
-        bail!("invalid checksum");
-    }
 
     lazy_static! {
         static ref RE_BYTES: Regex = Regex::new("(.{1,8})").unwrap();
@@ -49,12 +386,79 @@ pub fn mnemonic_to_entropy(words: Vec<String>) -> Result<[u8; 32]> {
     for (idx, matched) in RE_BYTES.find_iter(&entropy_bits).enumerate() {
         entropy_base[idx] = binary_to_bytes(matched.as_str()) as u8;
     }
+    (entropy_base, checksum_bits.to_string())
+}
 
-    let mut entropy_bytes = [0u8; 32];
-    entropy_bytes[..16].copy_from_slice(&entropy_base);
-    entropy_bytes[16..].copy_from_slice(&entropy_base);
+/// The BIP39 checksum bits of the given entropy, one bit for every 32
+/// bits of entropy
+fn bip39_checksum(entropy: &[u8]) -> String {
+    let hash = Sha256::digest(entropy);
+    format!("{:08b}", hash[0])[..entropy.len() / 4].to_string()
+}
 
-    Ok(entropy_bytes)
+/// Returns the positions of the adjacent word pairs that give a valid
+/// BIP39 checksum when swapped. Only suggestions are returned, the words
+/// themselves are never changed.
+fn adjacent_swaps(indices: &[usize]) -> Vec<usize> {
+    (0..indices.len() - 1)
+        .filter(|&i| indices[i] != indices[i + 1])
+        .filter(|&i| {
+            let mut swapped = indices.to_vec();
+            swapped.swap(i, i + 1);
+            let (entropy, checksum_bits) = split_entropy(&swapped);
+            checksum_bits == bip39_checksum(&entropy)
+        })
+        .collect()
+}
+
+/// Returns the message that is keyed with the seed entropy to derive the
+/// entropy of an account. It looks like a BIP44 path with Helium coin type
+/// 904 but is only hashed as a string, keys are not derived with BIP32 or
+/// SLIP-10 and the keys of other wallets for that path differ.
+fn account_message(account: u32) -> String {
+    format!("m/44'/904'/{}'/0'/0'", account)
+}
+
+/// Describes how the key of a given account is derived from the seed.
+/// Account 0 uses the seed entropy as is.
+pub fn account_derivation(account: u32) -> String {
+    if account == 0 {
+        "account 0 (seed key, not derived)".to_string()
+    } else {
+        format!("account {} (helium-wallet HMAC derivation)", account)
+    }
+}
+
+/// Derives the entropy for a given account index from the entropy of a
+/// mnemonic. Account 0 is the entropy itself, which keeps the keys of
+/// existing (non multi-account) wallets unchanged. Other accounts use an
+/// HMAC-SHA256 of the account message keyed with the mnemonic entropy. Note
+/// that this is specific to this wallet and is not BIP32 compatible.
+pub fn derive_account_entropy(entropy: &[u8; 32], account: u32) -> Result<[u8; 32]> {
+    if account == 0 {
+        return Ok(*entropy);
+    }
+    let mut hmac = match Hmac::<Sha256>::new_varkey(entropy) {
+        Err(_) => bail!("Failed to initialize hmac"),
+        Ok(m) => m,
+    };
+    hmac.update(account_message(account).as_bytes());
+    let mut derived = [0u8; 32];
+    derived.copy_from_slice(&hmac.finalize().into_bytes());
+    Ok(derived)
+}
+
+/// Returns a fingerprint that identifies the entropy of a mnemonic without
+/// revealing it: the first 4 bytes of a labeled SHA256 hash of the entropy.
+/// Like a BIP32 fingerprint it is meant to tell seeds apart, not to secure
+/// anything, and is shown hex encoded.
+pub fn seed_fingerprint(entropy: &[u8; 32]) -> [u8; 4] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"helium-wallet seed fingerprint");
+    hasher.update(entropy);
+    let mut fingerprint = [0u8; 4];
+    fingerprint.copy_from_slice(&hasher.finalize()[..4]);
+    fingerprint
 }
 
 /// Converts a binary string into an integer
@@ -78,4 +482,288 @@ mod tests {
         let entropy = mnemonic_to_entropy(word_list).expect("entropy");
         assert_eq!(expected_entropy, entropy);
     }
+
+    #[test]
+    fn bip39_swapped_words() {
+        let words = |phrase: &str| -> Vec<String> {
+            phrase.split_whitespace().map(|w| w.to_string()).collect()
+        };
+        // A BIP39 test vector, which does not have an all zero checksum
+        let valid = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        assert!(
+            mnemonic_to_entropy_checked(words(valid), Checksum::Bip39, WordMatch::Exact).is_ok()
+        );
+        assert!(mnemonic_to_entropy(words(valid)).is_err());
+
+        let swapped = "legal winner year thank wave sausage worth useful legal winner thank yellow";
+        let err = mnemonic_to_entropy_checked(words(swapped), Checksum::Bip39, WordMatch::Exact)
+            .unwrap_err();
+        assert_eq!(
+            "invalid checksum, did you mean to swap words 3 and 4?",
+            err.to_string()
+        );
+        // More than one swap can give a valid checksum
+        let swapped = "legal winner thank wave year sausage worth useful legal winner thank yellow";
+        let err = mnemonic_to_entropy_checked(words(swapped), Checksum::Bip39, WordMatch::Exact)
+            .unwrap_err();
+        assert_eq!(
+            "invalid checksum, did you mean to swap words 4 and 5 or words 6 and 7?",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn decode_partial_words() {
+        let words = "catc poet clog inta scare jack throw palm ille buyer allow figu";
+        let word_list: Vec<String> = words.split_whitespace().map(|w| w.to_string()).collect();
+        let full = "catch poet clog intact scare jacket throw palm illegal buyer allow figure";
+        let full_list = full.split_whitespace().map(|w| w.to_string()).collect();
+        assert_eq!(
+            mnemonic_to_entropy(full_list).expect("entropy"),
+            mnemonic_to_entropy_checked(word_list.clone(), Checksum::Zero, WordMatch::Prefix)
+                .expect("entropy")
+        );
+        // Abbreviations are only accepted when asked for
+        let err = mnemonic_to_entropy(word_list.clone()).unwrap_err();
+        assert_eq!(
+            Some(&MnemonicError::UnknownWord {
+                index: 0,
+                word: "catc".to_string(),
+                suggestions: vec!["catch".to_string()],
+            }),
+            err.downcast_ref::<MnemonicError>()
+        );
+
+        let resolved = resolve_words(&word_list).expect("resolved words");
+        let prefixed: Vec<String> = resolved
+            .iter()
+            .filter(|r| r.prefix)
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                "catc -> catch (prefix)",
+                "inta -> intact (prefix)",
+                "jack -> jacket (prefix)",
+                "ille -> illegal (prefix)",
+                "figu -> figure (prefix)",
+            ],
+            prefixed
+        );
+        assert_eq!("poet", resolved[1].to_string());
+    }
+
+    #[test]
+    fn mnemonic_errors() {
+        let words = |phrase: &str| -> Vec<String> {
+            phrase.split_whitespace().map(|w| w.to_string()).collect()
+        };
+        let error = |result: Result<[u8; 32]>| {
+            result
+                .unwrap_err()
+                .downcast_ref::<MnemonicError>()
+                .expect("mnemonic error")
+                .clone()
+        };
+
+        let short = "catch poet clog intact scare jacket throw palm illegal buyer allow";
+        assert_eq!(
+            MnemonicError::WrongWordCount {
+                got: 11,
+                expected: 12
+            },
+            error(mnemonic_to_entropy(words(short)))
+        );
+        // The word count is checked before the words, even unknown ones
+        assert_eq!(
+            MnemonicError::WrongWordCount {
+                got: 13,
+                expected: 12
+            },
+            error(mnemonic_to_entropy(words(&format!(
+                "{} xyzzy qwerty",
+                short
+            ))))
+        );
+
+        let typo = "catch poet clog intact scare jackte throw palm illegal buyer allow figure";
+        let err = error(mnemonic_to_entropy(words(typo)));
+        assert!(matches!(
+            &err,
+            MnemonicError::UnknownWord { index: 5, word, .. } if word == "jackte"
+        ));
+        let unknown = "catch poet clog intact scare xyzzy throw palm illegal buyer allow figure";
+        assert_eq!(
+            MnemonicError::UnknownWord {
+                index: 5,
+                word: "xyzzy".to_string(),
+                suggestions: vec![],
+            },
+            error(mnemonic_to_entropy(words(unknown)))
+        );
+        let typo = "catch poet clog intact scare jackez throw palm illegal buyer allow figure";
+        assert_eq!(
+            MnemonicError::UnknownWord {
+                index: 5,
+                word: "jackez".to_string(),
+                suggestions: vec!["jacket".to_string()],
+            },
+            error(mnemonic_to_entropy(words(typo)))
+        );
+
+        // A valid BIP39 mnemonic does not have the all zero checksum
+        let valid = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        assert_eq!(
+            MnemonicError::ChecksumMismatch { swaps: vec![] },
+            error(mnemonic_to_entropy(words(valid)))
+        );
+        let swapped = "legal winner year thank wave sausage worth useful legal winner thank yellow";
+        assert_eq!(
+            MnemonicError::ChecksumMismatch { swaps: vec![2] },
+            error(mnemonic_to_entropy_checked(
+                words(swapped),
+                Checksum::Bip39,
+                WordMatch::Exact
+            ))
+        );
+    }
+
+    #[test]
+    fn word_suggestions() {
+        let wordlist = ["abandon", "ability", "able", "about"];
+        // Ambiguous and too short prefixes suggest the words they start
+        assert_eq!(
+            Err(vec!["custom".to_string(), "customer".to_string()]),
+            lookup_word(&["custom", "customer"], "cust", WordMatch::Prefix)
+        );
+        assert_eq!(
+            Err(vec!["about".to_string()]),
+            lookup_word(&wordlist, "abo", WordMatch::Prefix)
+        );
+        // Otherwise words one typo away are suggested
+        assert_eq!(
+            Err(vec!["able".to_string()]),
+            lookup_word(&wordlist, "ablr", WordMatch::Prefix)
+        );
+        assert_eq!(
+            Err(vec!["about".to_string()]),
+            lookup_word(&wordlist, "abot", WordMatch::Prefix)
+        );
+        assert_eq!(
+            Err(vec![]),
+            lookup_word(&wordlist, "zebra", WordMatch::Prefix)
+        );
+        // Without abbreviations a unique prefix is suggested
+        assert_eq!(
+            Err(vec!["ability".to_string()]),
+            lookup_word(&wordlist, "abil", WordMatch::Exact)
+        );
+        assert_eq!(1, edit_distance("abot", "about"));
+        assert_eq!(0, edit_distance("able", "able"));
+        assert_eq!(3, edit_distance("", "abc"));
+    }
+
+    #[test]
+    fn ambiguous_prefix() {
+        let prefix = WordMatch::Prefix;
+        let wordlist = ["abandon", "ability", "able", "about"];
+        assert_eq!(2, resolve_word_in(&wordlist, "able", prefix).unwrap().index);
+        assert!(!resolve_word_in(&wordlist, "able", prefix).unwrap().prefix);
+        assert_eq!(1, resolve_word_in(&wordlist, "abil", prefix).unwrap().index);
+        assert!(resolve_word_in(&wordlist, "abil", WordMatch::Exact).is_err());
+        // Too short to resolve as a prefix
+        assert!(resolve_word_in(&wordlist, "abo", prefix).is_err());
+        let wordlist = ["custom", "customer"];
+        assert!(resolve_word_in(&wordlist, "cust", prefix).is_err());
+        assert_eq!(
+            0,
+            resolve_word_in(&wordlist, "custom", prefix).unwrap().index
+        );
+    }
+
+    #[test]
+    fn custom_wordlist() {
+        let wordlist = ["alpha", "bravo", "charlie"];
+        assert_eq!(Some(1), find_word_in(&wordlist, "bravo"));
+        assert_eq!(Some(2), find_word_in(&wordlist, "Charlie"));
+        assert_eq!(None, find_word_in(&wordlist, "char"));
+        assert_eq!(None, find_word_in(&wordlist, "delta"));
+
+        // Words at index 0 encode all zero bits and a zero checksum
+        let words = vec!["alpha".to_string(); 12];
+        assert_eq!([0u8; 32], words_to_entropy(&words, &wordlist).unwrap());
+        // Index 1 in the last word sets a checksum bit
+        let mut words = words;
+        words[11] = "bravo".to_string();
+        assert!(words_to_entropy(&words, &wordlist).is_err());
+        words[11] = "delta".to_string();
+        assert!(words_to_entropy(&words, &wordlist).is_err());
+
+        let path =
+            std::env::temp_dir().join(format!("helium-wallet-wordlist-{}.txt", std::process::id()));
+        fs::write(&path, "Alpha\nbravo\ncharlie\n").expect("write wordlist");
+        let words = vec!["alpha".to_string(); 12];
+        assert_eq!(
+            [0u8; 32],
+            mnemonic_to_entropy_in(words, &Language::Custom(path.clone())).unwrap()
+        );
+        fs::write(&path, "alpha\nbravo\nalpha\n").expect("write wordlist");
+        assert!(read_wordlist(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn derive_accounts() {
+        let words = "catch poet clog intact scare jacket throw palm illegal buyer allow figure";
+        let word_list = words.split_whitespace().map(|w| w.to_string()).collect();
+        let entropy = mnemonic_to_entropy(word_list).expect("entropy");
+
+        // Account 0 is the same as the non multi-account entropy
+        assert_eq!(
+            entropy,
+            derive_account_entropy(&entropy, 0).expect("account 0")
+        );
+        // Other accounts are distinct and deterministic
+        let account_1 = derive_account_entropy(&entropy, 1).expect("account 1");
+        let account_2 = derive_account_entropy(&entropy, 2).expect("account 2");
+        assert_ne!(entropy, account_1);
+        assert_ne!(account_1, account_2);
+        assert_eq!(
+            account_1,
+            derive_account_entropy(&entropy, 1).expect("account 1")
+        );
+        // Known answers for the helium-wallet HMAC derivation
+        assert_eq!(
+            "dca8601e781e701a340763929c6732af15a634737acfd251c115be99a4cd9bf0",
+            hex::encode(account_1)
+        );
+        assert_eq!(
+            "77e692a1c3f3ab2163a4447058cced82ae37a95245b26c4ab572f62a906321b0",
+            hex::encode(account_2)
+        );
+        assert_eq!(
+            "account 1 (helium-wallet HMAC derivation)",
+            account_derivation(1)
+        );
+        assert_eq!("account 0 (seed key, not derived)", account_derivation(0));
+    }
+
+    #[test]
+    fn fingerprint_seed() {
+        let mut entropy = [0u8; 32];
+        entropy
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, b)| *b = i as u8);
+        // The fingerprint is stable across versions
+        assert_eq!("257ab647", hex::encode(seed_fingerprint(&entropy)));
+        assert_eq!(seed_fingerprint(&entropy), seed_fingerprint(&entropy));
+
+        // It is a hash of 4 bytes, so it can not hold any of the 32 bytes of
+        // entropy, and it differs for a different seed
+        let fingerprint = seed_fingerprint(&entropy);
+        assert!(!hex::encode(entropy).contains(&hex::encode(fingerprint)));
+        entropy[31] ^= 1;
+        assert_ne!(fingerprint, seed_fingerprint(&entropy));
+    }
 }
@@ -0,0 +1,419 @@
+//! Recovery of a master secret from SLIP-0039 mnemonic shares as
+//! specified in https://github.com/satoshilabs/slips/blob/master/slip-0039.md
+use super::WordList;
+use crate::result::{bail, Result};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+include!(concat!(env!("OUT_DIR"), "/slip39.rs"));
+
+const RADIX_BITS: usize = 10;
+// Identifier, iteration exponent, group and member information words
+const METADATA_WORDS: usize = 4;
+const CHECKSUM_WORDS: usize = 3;
+// Shares of a 128 bit master secret are the shortest valid shares
+const MIN_MNEMONIC_WORDS: usize = METADATA_WORDS + 13 + CHECKSUM_WORDS;
+const MIN_STRENGTH_BYTES: usize = 16;
+const MAX_PADDING_BITS: usize = 8;
+
+const SECRET_INDEX: u8 = 255;
+const DIGEST_INDEX: u8 = 254;
+const DIGEST_LENGTH: usize = 4;
+
+const BASE_ITERATION_COUNT: u32 = 10_000;
+const ROUND_COUNT: u8 = 4;
+
+const CUSTOMIZATION_STRING: &[u8] = b"shamir";
+const CUSTOMIZATION_STRING_EXTENDABLE: &[u8] = b"shamir_extendable";
+
+/// A single decoded SLIP-0039 mnemonic share
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    pub identifier: u16,
+    pub extendable: bool,
+    pub iteration_exponent: u8,
+    pub group_index: u8,
+    pub group_threshold: u8,
+    pub group_count: u8,
+    pub member_index: u8,
+    pub member_threshold: u8,
+    pub value: Vec<u8>,
+}
+
+impl Share {
+    /// Decodes a mnemonic share and verifies its checksum
+    pub fn from_mnemonic(mnemonic: &str) -> Result<Self> {
+        let indices = mnemonic
+            .split_whitespace()
+            .map(|word| {
+                let word = word.to_lowercase();
+                match WORDS_SLIP39.iter().position(|s| *s == word) {
+                    Some(idx) => Ok(idx as u32),
+                    None => bail!("Share word {} not found in wordlist", word),
+                }
+            })
+            .collect::<Result<Vec<u32>>>()?;
+        if indices.len() < MIN_MNEMONIC_WORDS {
+            bail!("Invalid number of share words");
+        }
+        let extendable = (indices[1] >> 4) & 1 == 1;
+        let customization = if extendable {
+            CUSTOMIZATION_STRING_EXTENDABLE
+        } else {
+            CUSTOMIZATION_STRING
+        };
+        if !verify_checksum(customization, &indices) {
+            bail!("Invalid share checksum");
+        }
+
+        let metadata = indices[..METADATA_WORDS]
+            .iter()
+            .fold(0u64, |acc, idx| (acc << RADIX_BITS) | *idx as u64);
+        let field = |shift: usize, bits: usize| ((metadata >> shift) & ((1 << bits) - 1)) as u8;
+        let share = Self {
+            identifier: (metadata >> 25) as u16,
+            extendable,
+            iteration_exponent: field(20, 4),
+            group_index: field(16, 4),
+            group_threshold: field(12, 4) + 1,
+            group_count: field(8, 4) + 1,
+            member_index: field(4, 4),
+            member_threshold: field(0, 4) + 1,
+            value: share_value(&indices[METADATA_WORDS..indices.len() - CHECKSUM_WORDS])?,
+        };
+        if share.group_threshold > share.group_count {
+            bail!("Invalid share: group threshold exceeds group count");
+        }
+        Ok(share)
+    }
+
+    fn is_compatible(&self, other: &Share) -> bool {
+        self.identifier == other.identifier
+            && self.extendable == other.extendable
+            && self.iteration_exponent == other.iteration_exponent
+            && self.group_threshold == other.group_threshold
+            && self.group_count == other.group_count
+            && self.value.len() == other.value.len()
+    }
+}
+
+/// Unpacks the share value from the value words, checking the padding bits
+fn share_value(words: &[u32]) -> Result<Vec<u8>> {
+    let value_bits = words.len() * RADIX_BITS;
+    let padding_bits = value_bits % 16;
+    if padding_bits > MAX_PADDING_BITS {
+        bail!("Invalid number of share words");
+    }
+    let mut bits = Vec::with_capacity(value_bits);
+    for word in words {
+        for shift in (0..RADIX_BITS).rev() {
+            bits.push((word >> shift) & 1 == 1);
+        }
+    }
+    let (padding, value) = bits.split_at(padding_bits);
+    if padding.iter().any(|bit| *bit) {
+        bail!("Invalid share padding");
+    }
+    let value: Vec<u8> = value
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0u8, |acc, bit| (acc << 1) | *bit as u8))
+        .collect();
+    if value.len() < MIN_STRENGTH_BYTES {
+        bail!("Invalid share length");
+    }
+    Ok(value)
+}
+
+fn polymod(values: impl Iterator<Item = u32>) -> u32 {
+    const GEN: [u32; 10] = [
+        0x00E0_E040,
+        0x01C1_C080,
+        0x0383_8100,
+        0x0707_0200,
+        0x0E0E_0009,
+        0x1C0C_2412,
+        0x3808_6C24,
+        0x3090_FC48,
+        0x21B1_F890,
+        0x03F3_F120,
+    ];
+    let mut chk = 1u32;
+    for value in values {
+        let b = chk >> 20;
+        chk = ((chk & 0xF_FFFF) << RADIX_BITS) ^ value;
+        for (i, gen) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= gen;
+            }
+        }
+    }
+    chk
+}
+
+fn verify_checksum(customization: &[u8], indices: &[u32]) -> bool {
+    let values = customization
+        .iter()
+        .map(|b| *b as u32)
+        .chain(indices.iter().copied());
+    polymod(values) == 1
+}
+
+/// Exponent and logarithm tables for GF(256) with the Rijndael polynomial
+/// x^8 + x^4 + x^3 + x + 1
+struct Gf256 {
+    exp: [u8; 255],
+    log: [u8; 256],
+}
+
+impl Gf256 {
+    fn new() -> Self {
+        let mut exp = [0u8; 255];
+        let mut log = [0u8; 256];
+        let mut poly: u16 = 1;
+        for (i, entry) in exp.iter_mut().enumerate() {
+            *entry = poly as u8;
+            log[poly as usize] = i as u8;
+            // Multiply by the generator 3
+            poly = (poly << 1) ^ poly;
+            if poly & 0x100 != 0 {
+                poly ^= 0x11b;
+            }
+        }
+        Self { exp, log }
+    }
+
+    /// Evaluates the polynomial through the given (x, value) points at x
+    /// using Lagrange interpolation
+    fn interpolate(&self, shares: &[(u8, &[u8])], x: u8) -> Vec<u8> {
+        if let Some((_, value)) = shares.iter().find(|(share_x, _)| *share_x == x) {
+            return value.to_vec();
+        }
+        let log = |v: u8| self.log[v as usize] as usize;
+        let log_prod: usize = shares.iter().map(|(share_x, _)| log(share_x ^ x)).sum();
+        let mut result = vec![0u8; shares[0].1.len()];
+        for (share_x, value) in shares {
+            let others: usize = shares.iter().map(|(other, _)| log(share_x ^ other)).sum();
+            // All terms are taken mod 255 so the subtractions can not
+            // underflow
+            let log_basis = (log_prod + 255 * shares.len() - log(share_x ^ x) - others) % 255;
+            for (r, v) in result.iter_mut().zip(value.iter()) {
+                if *v != 0 {
+                    *r ^= self.exp[(log(*v) + log_basis) % 255];
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Recovers the shared secret of a threshold of shares and verifies its
+/// digest
+fn recover_secret(gf: &Gf256, threshold: u8, shares: &[(u8, &[u8])]) -> Result<Vec<u8>> {
+    if threshold == 1 {
+        return Ok(shares[0].1.to_vec());
+    }
+    let secret = gf.interpolate(shares, SECRET_INDEX);
+    let digest_share = gf.interpolate(shares, DIGEST_INDEX);
+    let (digest, random) = digest_share.split_at(DIGEST_LENGTH);
+    let mut hmac = match Hmac::<Sha256>::new_varkey(random) {
+        Err(_) => bail!("Failed to initialize hmac"),
+        Ok(m) => m,
+    };
+    hmac.update(&secret);
+    if hmac.finalize().into_bytes()[..DIGEST_LENGTH] != *digest {
+        bail!("Invalid digest of the shared secret");
+    }
+    Ok(secret)
+}
+
+/// Decrypts the encrypted master secret with the given passphrase using
+/// the Feistel network from the specification
+fn decrypt(share: &Share, encrypted: &[u8], passphrase: &[u8]) -> Vec<u8> {
+    let half = encrypted.len() / 2;
+    let mut left = encrypted[..half].to_vec();
+    let mut right = encrypted[half..].to_vec();
+    let mut salt_prefix = vec![];
+    if !share.extendable {
+        salt_prefix.extend_from_slice(CUSTOMIZATION_STRING);
+        salt_prefix.extend_from_slice(&share.identifier.to_be_bytes());
+    }
+    let iterations = (BASE_ITERATION_COUNT << share.iteration_exponent) / ROUND_COUNT as u32;
+    for round in (0..ROUND_COUNT).rev() {
+        let mut password = vec![round];
+        password.extend_from_slice(passphrase);
+        let mut salt = salt_prefix.clone();
+        salt.extend_from_slice(&right);
+        let mut f = vec![0u8; half];
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(&password, &salt, iterations, &mut f);
+        let next: Vec<u8> = left.iter().zip(f.iter()).map(|(l, f)| l ^ f).collect();
+        left = right;
+        right = next;
+    }
+    right.extend_from_slice(&left);
+    right
+}
+
+/// Combines mnemonic shares into the master secret they encode. The
+/// shares must meet the group threshold and the member threshold of each
+/// used group. An empty passphrase is used if none was set for the
+/// shares.
+pub fn combine_shares(mnemonics: &[String], passphrase: &[u8]) -> Result<Vec<u8>> {
+    let shares = mnemonics
+        .iter()
+        .map(|m| Share::from_mnemonic(m))
+        .collect::<Result<Vec<Share>>>()?;
+    let first = match shares.first() {
+        Some(first) => first,
+        None => bail!("No shares given"),
+    };
+    if shares.iter().any(|share| !share.is_compatible(first)) {
+        bail!("Shares do not belong to the same secret");
+    }
+
+    let mut groups: BTreeMap<u8, Vec<&Share>> = BTreeMap::new();
+    for share in &shares {
+        let group = groups.entry(share.group_index).or_default();
+        if group.iter().any(|s| s.member_index == share.member_index) {
+            bail!(
+                "Duplicate share {} in group {}",
+                share.member_index,
+                share.group_index
+            );
+        }
+        if group
+            .iter()
+            .any(|s| s.member_threshold != share.member_threshold)
+        {
+            bail!(
+                "Inconsistent member threshold in group {}",
+                share.group_index
+            );
+        }
+        group.push(share);
+    }
+    if groups.len() < first.group_threshold as usize {
+        bail!(
+            "Insufficient groups: {} of {} required groups given",
+            groups.len(),
+            first.group_threshold
+        );
+    }
+
+    let gf = Gf256::new();
+    let mut group_secrets = vec![];
+    for (index, members) in groups.iter().take(first.group_threshold as usize) {
+        let threshold = members[0].member_threshold;
+        if members.len() < threshold as usize {
+            bail!(
+                "Insufficient shares for group {}: {} of {} required shares given",
+                index,
+                members.len(),
+                threshold
+            );
+        }
+        let points: Vec<(u8, &[u8])> = members
+            .iter()
+            .take(threshold as usize)
+            .map(|s| (s.member_index, s.value.as_slice()))
+            .collect();
+        group_secrets.push((*index, recover_secret(&gf, threshold, &points)?));
+    }
+    let points: Vec<(u8, &[u8])> = group_secrets
+        .iter()
+        .map(|(index, secret)| (*index, secret.as_slice()))
+        .collect();
+    let encrypted = recover_secret(&gf, first.group_threshold, &points)?;
+    Ok(decrypt(first, &encrypted, passphrase))
+}
+
+/// Converts SLIP-0039 mnemonic shares to an entropy that can be used to
+/// generate a keypair. A 128 bit master secret is repeated to fill the
+/// entropy the same way as a 12 word BIP39 mnemonic.
+pub fn shares_to_entropy(mnemonics: &[String], passphrase: &[u8]) -> Result<[u8; 32]> {
+    let secret = combine_shares(mnemonics, passphrase)?;
+    let mut entropy = [0u8; 32];
+    match secret.len() {
+        16 => {
+            entropy[..16].copy_from_slice(&secret);
+            entropy[16..].copy_from_slice(&secret);
+        }
+        32 => entropy.copy_from_slice(&secret),
+        len => bail!("Unsupported master secret length of {} bytes", len),
+    }
+    Ok(entropy)
+}
+
+/// The number of shares needed to recover the secret of a given share,
+/// assuming every group has the same member threshold as the share's
+/// group.
+pub fn required_shares(share: &Share) -> usize {
+    share.group_threshold as usize * share.member_threshold as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from the SLIP-0039 specification, all using the
+    // passphrase "TREZOR"
+    const PASSPHRASE: &[u8] = b"TREZOR";
+
+    fn shares(mnemonics: &[&str]) -> Vec<String> {
+        mnemonics.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn wordlist() {
+        assert_eq!(1024, WORDS_SLIP39.len());
+        assert_eq!("academic", WORDS_SLIP39[0]);
+        assert_eq!("zero", WORDS_SLIP39[1023]);
+    }
+
+    #[test]
+    fn single_share_128() {
+        let mnemonics = shares(&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard"]);
+        let secret = combine_shares(&mnemonics, PASSPHRASE).expect("secret");
+        assert_eq!("bb54aac4b89dc868ba37d9cc21b2cece", hex::encode(&secret));
+
+        let entropy = shares_to_entropy(&mnemonics, PASSPHRASE).expect("entropy");
+        assert_eq!(secret[..], entropy[..16]);
+        assert_eq!(secret[..], entropy[16..]);
+    }
+
+    #[test]
+    fn single_share_256() {
+        let mnemonics = shares(&["theory painting academic academic armed sweater year military elder discuss acne wildlife boring employer fused large satoshi bundle carbon diagnose anatomy hamster leaves tracks paces beyond phantom capital marvel lips brave detect luck"]);
+        let entropy = shares_to_entropy(&mnemonics, PASSPHRASE).expect("entropy");
+        assert_eq!(
+            "989baf9dcaad5b10ca33dfd8cc75e42477025dce88ae83e75a230086a0e00e92",
+            hex::encode(entropy)
+        );
+    }
+
+    #[test]
+    fn basic_sharing_2_of_3() {
+        let mnemonics = shares(&[
+            "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+            "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+        ]);
+        let secret = combine_shares(&mnemonics, PASSPHRASE).expect("secret");
+        assert_eq!("b43ceb7e57a0ea8766221624d01b0864", hex::encode(secret));
+
+        let share = Share::from_mnemonic(&mnemonics[0]).expect("share");
+        assert_eq!(2, share.member_threshold);
+        assert_eq!(2, required_shares(&share));
+        // A single share of the two required ones is not enough
+        assert!(combine_shares(&mnemonics[..1], PASSPHRASE).is_err());
+        // Nor is the same share given twice
+        let duplicate = vec![mnemonics[0].clone(), mnemonics[0].clone()];
+        assert!(combine_shares(&duplicate, PASSPHRASE).is_err());
+    }
+
+    #[test]
+    fn invalid_checksum() {
+        let mnemonics = shares(&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney"]);
+        assert!(combine_shares(&mnemonics, PASSPHRASE).is_err());
+        assert!(combine_shares(&[], PASSPHRASE).is_err());
+    }
+}
@@ -33,6 +33,17 @@ impl PwHash {
         }
     }
 
+    /// Describes the cost parameters of the password hash
+    pub fn params(&self) -> String {
+        match self {
+            PwHash::Pbkdf2(hasher) => format!("iterations={}", hasher.iterations),
+            PwHash::Argon2id13(hasher) => format!(
+                "mem_limit={} ops_limit={}",
+                hasher.mem_limit.0, hasher.ops_limit.0
+            ),
+        }
+    }
+
     pub fn pbkdf2_default() -> Self {
         PwHash::Pbkdf2(Pbkdf2::with_iterations(PBKDF2_DEFAULT_ITERATIONS))
     }
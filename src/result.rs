@@ -1,3 +1,107 @@
+use serde::Serialize;
+use std::io;
+
 pub type Result<T = ()> = anyhow::Result<T>;
 pub type Error = anyhow::Error;
 pub use anyhow::{anyhow, bail};
+
+/// The category of an error as reported to machine readable output
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// A request to the API failed
+    Api,
+    /// The API does not know the requested resource, like an account that
+    /// is not on chain yet
+    NotFound,
+    /// Reading or writing a local file failed
+    Io,
+    /// Encoding or decoding data failed
+    Decode,
+    /// Any other failure
+    Other,
+}
+
+impl ErrorKind {
+    /// Determines the category of an error from the first recognized
+    /// error in its chain of causes.
+    pub fn of(err: &Error) -> Self {
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+                if err.status() == Some(reqwest::StatusCode::NOT_FOUND) {
+                    return Self::NotFound;
+                }
+                return Self::Api;
+            }
+            if cause.is::<io::Error>() {
+                return Self::Io;
+            }
+            if cause.is::<serde_json::Error>() || cause.is::<base64::DecodeError>() {
+                return Self::Decode;
+            }
+        }
+        Self::Other
+    }
+}
+
+/// Whether the error is the API reporting that the requested resource does
+/// not exist
+pub fn is_not_found(err: &Error) -> bool {
+    ErrorKind::of(err) == ErrorKind::NotFound
+}
+
+/// Returns the json representation of an error:
+/// `{"error": {"kind": ..., "message": ..., "source": ...}}`. The source
+/// is the chain of underlying causes, or null if there are none.
+pub fn error_json(err: &Error) -> serde_json::Value {
+    let causes: Vec<String> = err.chain().skip(1).map(|e| e.to_string()).collect();
+    json!({
+        "error": {
+            "kind": ErrorKind::of(err),
+            "message": err.to_string(),
+            "source": if causes.is_empty() {
+                serde_json::Value::Null
+            } else {
+                json!(causes.join(": "))
+            },
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_kinds() {
+        assert_eq!(ErrorKind::Other, ErrorKind::of(&anyhow!("plain")));
+        let io_err: Error = io::Error::new(io::ErrorKind::NotFound, "no file").into();
+        assert_eq!(ErrorKind::Io, ErrorKind::of(&io_err));
+        // The category is found through added context
+        let json_err: Error = serde_json::from_str::<u64>("x").unwrap_err().into();
+        assert_eq!(
+            ErrorKind::Decode,
+            ErrorKind::of(&json_err.context("reading config"))
+        );
+    }
+
+    #[test]
+    fn error_to_json() {
+        let err: Error = io::Error::new(io::ErrorKind::NotFound, "no file").into();
+        let err = err.context("opening wallet.key");
+        assert_eq!(
+            json!({
+                "error": {
+                    "kind": "io",
+                    "message": "opening wallet.key",
+                    "source": "no file",
+                }
+            }),
+            error_json(&err)
+        );
+        assert_eq!(
+            json!(null),
+            error_json(&anyhow!("plain"))["error"]["source"]
+        );
+    }
+}
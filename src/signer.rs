@@ -0,0 +1,228 @@
+//! Signing with keys that are not held by this process.
+//!
+//! A [`CommandSigner`] runs an external program for every signature, which
+//! allows signing with hardware or services this crate has no backend for.
+//! The contract with the program is:
+//!
+//! * The program is run through the shell (`sh -c`, or `cmd /C` on
+//!   Windows) with the `HELIUM_WALLET_SIGNER_ADDRESS` environment variable
+//!   set to the address of the key to sign with.
+//! * Its stdin receives a single line with the standard, padded base64
+//!   encoding of the message to sign, after which stdin is closed. For a
+//!   transaction the message is the protobuf encoding of the transaction
+//!   itself, with all signature fields empty. This deliberately differs
+//!   from the base64 envelopes that other commands read and write, since
+//!   the signature covers the bare transaction and a signer would
+//!   otherwise have to unwrap the envelope itself.
+//! * It writes the base64 encoded raw signature to stdout. Surrounding
+//!   whitespace is ignored.
+//! * It exits with status 0. Any other status fails the signing. Its stderr
+//!   is passed through, so it can prompt or report problems. It does not
+//!   have to read stdin, the result only depends on its exit status and
+//!   stdout.
+//!
+//! The returned signature is verified against the public key before it is
+//! used.
+
+use crate::{
+    keypair::{Keypair, PublicKey, Verify},
+    result::{anyhow, bail, Result},
+};
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+/// Signs messages for a public key
+pub trait Signer {
+    fn public_key(&self) -> &PublicKey;
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>>;
+}
+
+impl Signer for Keypair {
+    fn public_key(&self) -> &PublicKey {
+        Keypair::public_key(self)
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        Keypair::sign(self, msg)
+    }
+}
+
+impl<T: Signer + ?Sized> Signer for &T {
+    fn public_key(&self) -> &PublicKey {
+        (**self).public_key()
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        (**self).sign(msg)
+    }
+}
+
+impl<T: Signer + ?Sized> Signer for Box<T> {
+    fn public_key(&self) -> &PublicKey {
+        (**self).public_key()
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        (**self).sign(msg)
+    }
+}
+
+/// The environment variable that holds the address to sign for
+pub const SIGNER_ADDRESS_ENV: &str = "HELIUM_WALLET_SIGNER_ADDRESS";
+
+/// A signer that runs an external command for every signature
+#[derive(Debug, Clone)]
+pub struct CommandSigner {
+    command: String,
+    public_key: PublicKey,
+}
+
+impl CommandSigner {
+    /// A signer that runs the given shell command to sign for the given
+    /// public key
+    pub fn new(command: &str, public_key: PublicKey) -> Self {
+        Self {
+            command: command.to_string(),
+            public_key,
+        }
+    }
+
+    fn shell(&self) -> Command {
+        if cfg!(windows) {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C").arg(&self.command);
+            shell
+        } else {
+            let mut shell = Command::new("sh");
+            shell.arg("-c").arg(&self.command);
+            shell
+        }
+    }
+}
+
+impl Signer for CommandSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        let mut child = self
+            .shell()
+            .env(SIGNER_ADDRESS_ENV, self.public_key.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run signer command {}: {}", self.command, e))?;
+        {
+            // Dropping stdin closes it
+            let mut stdin = child.stdin.take().expect("signer stdin");
+            // A command that exits without reading the message closes the
+            // pipe early, which leaves the outcome to its status and output
+            if let Err(err) = writeln!(stdin, "{}", base64::encode(msg)) {
+                if err.kind() != io::ErrorKind::BrokenPipe {
+                    return Err(err.into());
+                }
+            }
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!("Signer command failed with {}", output.status);
+        }
+        let signature = String::from_utf8(output.stdout)
+            .ok()
+            .and_then(|stdout| base64::decode(stdout.trim()).ok())
+            .ok_or_else(|| anyhow!("Signer command did not return a base64 signature"))?;
+        if self.public_key.verify(msg, &signature).is_err() {
+            bail!(
+                "Signer command returned a signature that is not valid for {}",
+                self.public_key
+            );
+        }
+        Ok(signature)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::keypair::KeyTag;
+    use std::{env, fs, path::PathBuf};
+
+    /// Writes a signer script that expects the given message and address
+    /// and answers with the given signature
+    fn signer_script(name: &str, msg: &[u8], address: &str, signature: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "helium-wallet-signer-{}-{}.sh",
+            name,
+            std::process::id()
+        ));
+        let script = format!(
+            "read msg\n\
+             [ \"$msg\" = \"{}\" ] || exit 1\n\
+             [ \"${}\" = \"{}\" ] || exit 2\n\
+             echo \"{}\"\n",
+            base64::encode(msg),
+            SIGNER_ADDRESS_ENV,
+            address,
+            signature
+        );
+        fs::write(&path, script).expect("signer script");
+        path
+    }
+
+    #[test]
+    fn command_signer() {
+        // ed25519 signatures are deterministic, so the script can answer
+        // with a precomputed signature
+        let seed: Vec<u8> = (0..32).collect();
+        let keypair = Keypair::generate_from_entropy(KeyTag::default(), &seed).unwrap();
+        let address = keypair.public_key().to_string();
+        let msg = b"unsigned transaction";
+        let signature = base64::encode(keypair.sign(msg).unwrap());
+
+        let good = signer_script("good", msg, &address, &signature);
+        let signer = CommandSigner::new(
+            &format!("sh {}", good.display()),
+            keypair.public_key().clone(),
+        );
+        let signed = signer.sign(msg);
+        // The script refuses to sign any other message
+        let other = signer.sign(b"other transaction");
+
+        // A signature by another key than the one asked for is refused
+        let other_key = Keypair::default().public_key().clone();
+        let forged = signer_script("forged", msg, &other_key.to_string(), &signature);
+        let wrong_key =
+            CommandSigner::new(&format!("sh {}", forged.display()), other_key).sign(msg);
+
+        let garbage = signer_script("garbage", msg, &address, "not base64!");
+        let invalid = CommandSigner::new(
+            &format!("sh {}", garbage.display()),
+            keypair.public_key().clone(),
+        )
+        .sign(msg);
+        let _ = fs::remove_file(&good);
+        let _ = fs::remove_file(&forged);
+        let _ = fs::remove_file(&garbage);
+
+        assert_eq!(keypair.sign(msg).unwrap(), signed.expect("signature"));
+        assert!(other.is_err());
+        assert!(wrong_key.is_err());
+        assert!(invalid.is_err());
+        assert!(CommandSigner::new("exit 3", keypair.public_key().clone())
+            .sign(msg)
+            .is_err());
+        // A command that does not read the message is judged by its output
+        let unread = CommandSigner::new(
+            &format!("echo '{}'", signature),
+            keypair.public_key().clone(),
+        );
+        assert_eq!(
+            keypair.sign(msg).unwrap(),
+            unread.sign(msg).expect("signature")
+        );
+    }
+}
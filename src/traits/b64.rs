@@ -64,3 +64,199 @@ impl B64 for Vec<u8> {
         Ok(decoded)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        keypair::Keypair,
+        traits::{Hex, TxnEnvelope},
+    };
+    use helium_api::{
+        BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1, BlockchainTxnCreateHtlcV1,
+        BlockchainTxnOuiV1, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2,
+        BlockchainTxnRedeemHtlcV1, BlockchainTxnSecurityExchangeV1, BlockchainTxnStakeValidatorV1,
+        BlockchainTxnTokenBurnV1, Payment,
+    };
+
+    fn assert_roundtrip(envelope: BlockchainTxn) {
+        let encoded = envelope.to_b64().expect("encode");
+        assert_eq!(envelope, BlockchainTxn::from_b64(&encoded).expect("decode"));
+        let encoded = envelope.to_b64_url().expect("encode url");
+        assert_eq!(
+            envelope,
+            BlockchainTxn::from_b64_url(&encoded).expect("decode url")
+        );
+        let encoded = envelope.to_hex().expect("encode hex");
+        assert_eq!(
+            envelope,
+            BlockchainTxn::from_hex(&encoded).expect("decode hex")
+        );
+    }
+
+    fn key() -> Vec<u8> {
+        Keypair::default().public_key().to_vec()
+    }
+
+    fn signature() -> Vec<u8> {
+        vec![0xab; 64]
+    }
+
+    #[test]
+    fn roundtrip_payment() {
+        assert_roundtrip(
+            BlockchainTxnPaymentV1 {
+                payee: key(),
+                payer: key(),
+                amount: 10_000,
+                nonce: 1,
+                fee: 35_000,
+                signature: signature(),
+            }
+            .in_envelope(),
+        );
+        assert_roundtrip(
+            BlockchainTxnPaymentV2 {
+                payer: key(),
+                payments: vec![
+                    Payment {
+                        payee: key(),
+                        amount: 10_000,
+                    },
+                    Payment {
+                        payee: key(),
+                        amount: 20_000,
+                    },
+                ],
+                nonce: 2,
+                fee: 40_000,
+                signature: signature(),
+            }
+            .in_envelope(),
+        );
+    }
+
+    #[test]
+    fn roundtrip_htlc() {
+        assert_roundtrip(
+            BlockchainTxnCreateHtlcV1 {
+                amount: 10_000,
+                fee: 40_000,
+                payee: key(),
+                payer: key(),
+                address: key(),
+                hashlock: vec![0x12; 32],
+                timelock: 100,
+                nonce: 3,
+                signature: signature(),
+            }
+            .in_envelope(),
+        );
+        assert_roundtrip(
+            BlockchainTxnRedeemHtlcV1 {
+                fee: 30_000,
+                payee: key(),
+                address: key(),
+                preimage: b"preimage".to_vec(),
+                signature: signature(),
+            }
+            .in_envelope(),
+        );
+    }
+
+    #[test]
+    fn roundtrip_oui() {
+        assert_roundtrip(
+            BlockchainTxnOuiV1 {
+                owner: key(),
+                payer: key(),
+                filter: vec![0x01; 16],
+                addresses: vec![key(), key()],
+                staking_fee: 10_000_000,
+                requested_subnet_size: 8,
+                fee: 45_000,
+                oui: 1,
+                owner_signature: signature(),
+                payer_signature: signature(),
+            }
+            .in_envelope(),
+        );
+    }
+
+    #[test]
+    fn roundtrip_hotspot() {
+        assert_roundtrip(
+            BlockchainTxnAddGatewayV1 {
+                owner: key(),
+                gateway: key(),
+                payer: key(),
+                staking_fee: 4_000_000,
+                fee: 65_000,
+                owner_signature: signature(),
+                gateway_signature: signature(),
+                payer_signature: vec![],
+            }
+            .in_envelope(),
+        );
+        assert_roundtrip(
+            BlockchainTxnAssertLocationV1 {
+                gateway: key(),
+                owner: key(),
+                payer: key(),
+                location: "8c28308280c15ff".to_string(),
+                nonce: 1,
+                staking_fee: 1_000_000,
+                fee: 55_000,
+                owner_signature: signature(),
+                gateway_signature: signature(),
+                ..Default::default()
+            }
+            .in_envelope(),
+        );
+    }
+
+    #[test]
+    fn roundtrip_stake_validator() {
+        assert_roundtrip(
+            BlockchainTxnStakeValidatorV1 {
+                address: key(),
+                owner: key(),
+                stake: 1_000_000_000_000,
+                fee: 35_000,
+                owner_signature: signature(),
+            }
+            .in_envelope(),
+        );
+    }
+
+    #[test]
+    fn roundtrip_token_burn() {
+        assert_roundtrip(
+            BlockchainTxnTokenBurnV1 {
+                payer: key(),
+                payee: key(),
+                amount: 10_000,
+                nonce: 4,
+                memo: u64::MAX,
+                fee: 30_000,
+                signature: signature(),
+            }
+            .in_envelope(),
+        );
+    }
+
+    #[test]
+    fn roundtrip_security_exchange() {
+        assert_roundtrip(
+            BlockchainTxnSecurityExchangeV1 {
+                payee: key(),
+                payer: key(),
+                amount: 10_000,
+                nonce: 1,
+                fee: 30_000,
+                signature: signature(),
+            }
+            .in_envelope(),
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use crate::result::Result;
+use helium_api::{BlockchainTxn, Message};
+
+/// Hex encoding, the counterpart of `B64` for tools that exchange data as
+/// hex strings.
+pub trait Hex {
+    fn to_hex(&self) -> Result<String>;
+    fn from_hex(str: &str) -> Result<Self>
+    where
+        Self: std::marker::Sized;
+}
+
+impl Hex for BlockchainTxn {
+    fn to_hex(&self) -> Result<String> {
+        let mut buf = vec![];
+        self.encode(&mut buf)?;
+        Ok(hex::encode(&buf))
+    }
+
+    fn from_hex(str: &str) -> Result<Self> {
+        let decoded = hex::decode(str)?;
+        let envelope = BlockchainTxn::decode(&decoded[..])?;
+        Ok(envelope)
+    }
+}
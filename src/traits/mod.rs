@@ -1,14 +1,18 @@
 pub use self::b64::B64;
+pub use self::hex::Hex;
 pub use self::json::ToJson;
 pub use self::read_write::ReadWrite;
+pub use self::txn_balance::{BalanceType, TxnBalance};
 pub use self::txn_envelope::TxnEnvelope;
-pub use self::txn_fee::{TxnFee, TxnFeeConfig, TxnStakingFee};
+pub use self::txn_fee::{FeeDetails, TxnFee, TxnFeeConfig, TxnStakingFee};
 pub use self::txn_payer::TxnPayer;
 pub use self::txn_sign::TxnSign;
 
 pub mod b64;
+pub mod hex;
 pub mod json;
 pub mod read_write;
+pub mod txn_balance;
 pub mod txn_envelope;
 pub mod txn_fee;
 pub mod txn_payer;
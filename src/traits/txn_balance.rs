@@ -0,0 +1,73 @@
+use crate::result::{bail, Result};
+use helium_api::{BlockchainTxn, Txn};
+use std::fmt;
+
+/// The balance of an account that a transaction draws its amount from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BalanceType {
+    Hnt,
+    Hst,
+}
+
+impl fmt::Display for BalanceType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Hnt => f.write_str("HNT"),
+            Self::Hst => f.write_str("HST"),
+        }
+    }
+}
+
+pub trait TxnBalance {
+    /// The balance type and the amount in bones the transaction draws
+    /// from the payer. The fee is not included.
+    fn txn_balance(&self) -> Result<(BalanceType, u64)>;
+}
+
+impl TxnBalance for BlockchainTxn {
+    fn txn_balance(&self) -> Result<(BalanceType, u64)> {
+        match &self.txn {
+            Some(Txn::Payment(t)) => Ok((BalanceType::Hnt, t.amount)),
+            Some(Txn::PaymentV2(t)) => Ok((
+                BalanceType::Hnt,
+                t.payments.iter().map(|payment| payment.amount).sum(),
+            )),
+            Some(Txn::CreateHtlc(t)) => Ok((BalanceType::Hnt, t.amount)),
+            Some(Txn::TokenBurn(t)) => Ok((BalanceType::Hnt, t.amount)),
+            Some(Txn::SecurityExchange(t)) => Ok((BalanceType::Hst, t.amount)),
+            _ => bail!("Unsupported transaction"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::TxnEnvelope;
+    use helium_api::{BlockchainTxnPaymentV2, BlockchainTxnSecurityExchangeV1, Payment};
+
+    #[test]
+    fn balance_types() {
+        let payment = |amount| Payment {
+            payee: vec![],
+            amount,
+        };
+        let txn = BlockchainTxnPaymentV2 {
+            payments: vec![payment(10), payment(20)],
+            ..Default::default()
+        };
+        assert_eq!(
+            (BalanceType::Hnt, 30),
+            txn.in_envelope().txn_balance().unwrap()
+        );
+
+        let txn = BlockchainTxnSecurityExchangeV1 {
+            amount: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            (BalanceType::Hst, 5),
+            txn.in_envelope().txn_balance().unwrap()
+        );
+    }
+}
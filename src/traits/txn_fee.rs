@@ -24,6 +24,10 @@ pub struct TxnFeeConfig {
     staking_fee_txn_add_gateway_v1: u64,
     // the staking fee in DC for asserting a location
     staking_fee_txn_assert_location_v1: u64,
+    // an override for the number of bytes covered by a single DC, not a
+    // chain variable
+    #[serde(skip)]
+    dc_payload_size: Option<usize>,
 }
 
 pub const LEGACY_STAKING_FEE: u64 = 1;
@@ -38,10 +42,41 @@ impl TxnFeeConfig {
             staking_fee_txn_oui_v1_per_address: 0,
             staking_fee_txn_add_gateway_v1: LEGACY_STAKING_FEE,
             staking_fee_txn_assert_location_v1: LEGACY_STAKING_FEE,
+            dc_payload_size: None,
         }
     }
 
+    /// A fee configuration with transaction fees active, using the given
+    /// fee multiplier and number of bytes per DC. Staking fees are not set
+    /// since they are not derived from the transaction size.
+    pub fn with_txn_fees(txn_fee_multiplier: u64, dc_payload_size: usize) -> Self {
+        Self {
+            txn_fees: true,
+            txn_fee_multiplier,
+            dc_payload_size: Some(dc_payload_size),
+            ..Self::legacy()
+        }
+    }
+
+    pub fn txn_fee_multiplier(&self) -> u64 {
+        self.txn_fee_multiplier
+    }
+
+    /// Overrides the fee multiplier, for simulating fees under a different
+    /// multiplier than the chain variable
+    pub fn set_txn_fee_multiplier(&mut self, txn_fee_multiplier: u64) {
+        self.txn_fee_multiplier = txn_fee_multiplier;
+    }
+
+    /// The fee in DC for a transaction payload of the given size in bytes
+    pub fn payload_fee(&self, payload_size: usize) -> u64 {
+        calculate_txn_fee(payload_size, self) * self.txn_fee_multiplier
+    }
+
     pub fn dc_payload_size(&self) -> usize {
+        if let Some(size) = self.dc_payload_size {
+            return size;
+        }
         if self.txn_fees {
             24
         } else {
@@ -50,12 +85,47 @@ impl TxnFeeConfig {
     }
 }
 
+/// The breakdown of the fees of a transaction under a fee configuration
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FeeDetails {
+    /// The transaction fee in DC
+    pub txn_fee: u64,
+    /// The staking fee in DC, 0 for transactions without one
+    pub staking_fee: u64,
+    /// The size in bytes the transaction fee is calculated from
+    pub payload_bytes: usize,
+    /// The number of bytes covered by a single DC
+    pub dc_payload_size: usize,
+}
+
 pub trait TxnFee {
     fn txn_fee(&self, config: &TxnFeeConfig) -> Result<u64>;
+    /// The encoded size in bytes of the transaction envelope that the fee
+    /// is calculated from. Signatures are counted at their full size and
+    /// the fee itself is left out.
+    fn txn_size(&self) -> Result<usize>;
+
+    /// The transaction and staking fees along with the sizes they are
+    /// based on
+    fn fee_details(&self, config: &TxnFeeConfig) -> Result<FeeDetails>
+    where
+        Self: TxnStakingFee,
+    {
+        Ok(FeeDetails {
+            txn_fee: self.txn_fee(config)?,
+            staking_fee: self.txn_staking_fee(config)?,
+            payload_bytes: self.txn_size()?,
+            dc_payload_size: config.dc_payload_size(),
+        })
+    }
 }
 
 pub trait TxnStakingFee {
-    fn txn_staking_fee(&self, config: &TxnFeeConfig) -> Result<u64>;
+    /// The staking fee in DC. Transactions without a staking fee use the
+    /// default of 0.
+    fn txn_staking_fee(&self, _config: &TxnFeeConfig) -> Result<u64> {
+        Ok(0)
+    }
 }
 
 fn calculate_txn_fee(payload_size: usize, config: &TxnFeeConfig) -> u64 {
@@ -85,13 +155,17 @@ macro_rules! impl_txn_fee {
     (($kind:ident, $txn_type:ty), $( $sig:ident ),+ ) => {
         impl TxnFee for $txn_type {
             fn txn_fee(&self, config: &TxnFeeConfig) -> Result<u64> {
+                Ok(config.payload_fee(self.txn_size()?))
+            }
+
+            fn txn_size(&self) -> Result<usize> {
                 let mut txn: $txn_type = self.clone();
                 txn.fee = 0;
                 $(txn.$sig = vec![0; TXN_FEE_SIGNATURE_SIZE];)+
                 payer_sig_clear!($kind, txn);
                 let mut buf = vec![];
                 txn.in_envelope().encode(&mut buf)?;
-                Ok(calculate_txn_fee(buf.len(), config) * config.txn_fee_multiplier)
+                Ok(buf.len())
             }
         }
     };
@@ -101,6 +175,9 @@ macro_rules! impl_txn_fee {
 }
 
 macro_rules! impl_txn_staking_fee {
+    ($txn_type: ty) => {
+        impl TxnStakingFee for $txn_type {}
+    };
     ($txn_type: ty, $field: ident) => {
         impl TxnStakingFee for $txn_type {
             fn txn_staking_fee(&self, config: &TxnFeeConfig) -> Result<u64> {
@@ -111,11 +188,17 @@ macro_rules! impl_txn_staking_fee {
 }
 
 impl_txn_fee!(BlockchainTxnPaymentV1, signature);
+impl_txn_staking_fee!(BlockchainTxnPaymentV1);
 impl_txn_fee!(BlockchainTxnPaymentV2, signature);
+impl_txn_staking_fee!(BlockchainTxnPaymentV2);
 impl_txn_fee!(BlockchainTxnCreateHtlcV1, signature);
+impl_txn_staking_fee!(BlockchainTxnCreateHtlcV1);
 impl_txn_fee!(BlockchainTxnRedeemHtlcV1, signature);
+impl_txn_staking_fee!(BlockchainTxnRedeemHtlcV1);
 impl_txn_fee!(BlockchainTxnSecurityExchangeV1, signature);
+impl_txn_staking_fee!(BlockchainTxnSecurityExchangeV1);
 impl_txn_fee!(BlockchainTxnTokenBurnV1, signature);
+impl_txn_staking_fee!(BlockchainTxnTokenBurnV1);
 impl_txn_fee!(
     (payer, BlockchainTxnAddGatewayV1),
     owner_signature,
@@ -138,13 +221,17 @@ impl_txn_fee!(
     buyer_signature,
     seller_signature
 );
+impl_txn_staking_fee!(BlockchainTxnTransferHotspotV1);
 impl_txn_fee!(BlockchainTxnStakeValidatorV1, owner_signature);
+impl_txn_staking_fee!(BlockchainTxnStakeValidatorV1);
 impl_txn_fee!(BlockchainTxnUnstakeValidatorV1, owner_signature);
+impl_txn_staking_fee!(BlockchainTxnUnstakeValidatorV1);
 impl_txn_fee!(
     BlockchainTxnTransferValidatorStakeV1,
     old_owner_signature,
     new_owner_signature
 );
+impl_txn_staking_fee!(BlockchainTxnTransferValidatorStakeV1);
 
 impl TxnStakingFee for BlockchainTxnOuiV1 {
     fn txn_staking_fee(&self, config: &TxnFeeConfig) -> Result<u64> {
@@ -188,10 +275,128 @@ mod tests {
                 staking_fee_txn_assert_location_v1: STAKING_FEE_ASSERT_LOCATION,
                 staking_fee_txn_oui_v1: STAKING_FEE_OUI,
                 staking_fee_txn_oui_v1_per_address: STAKING_FEE_OUI_PER_ADDRESS,
+                dc_payload_size: None,
             }
         }
     }
 
+    impl TxnFeeConfig {
+        pub fn with_multiplier(multiplier: u64) -> Self {
+            Self {
+                txn_fee_multiplier: multiplier,
+                ..Self::active()
+            }
+        }
+    }
+
+    #[test]
+    fn payload_size_boundaries() {
+        let active = TxnFeeConfig::active();
+        assert_eq!(24, active.dc_payload_size());
+        assert_eq!(1, calculate_txn_fee(0, &active));
+        assert_eq!(1, calculate_txn_fee(1, &active));
+        assert_eq!(1, calculate_txn_fee(24, &active));
+        assert_eq!(2, calculate_txn_fee(25, &active));
+        assert_eq!(2, calculate_txn_fee(48, &active));
+        assert_eq!(3, calculate_txn_fee(49, &active));
+        assert_eq!(100, calculate_txn_fee(2400, &active));
+        assert_eq!(101, calculate_txn_fee(2401, &active));
+
+        let legacy = TxnFeeConfig::legacy();
+        assert_eq!(1, legacy.dc_payload_size());
+        assert_eq!(1, calculate_txn_fee(0, &legacy));
+        assert_eq!(1, calculate_txn_fee(1, &legacy));
+        assert_eq!(2, calculate_txn_fee(2, &legacy));
+        assert_eq!(2401, calculate_txn_fee(2401, &legacy));
+    }
+
+    #[test]
+    fn payload_fee_override() {
+        let config = TxnFeeConfig::with_txn_fees(5000, 24);
+        assert_eq!(5000, config.payload_fee(24));
+        assert_eq!(10_000, config.payload_fee(25));
+
+        let config = TxnFeeConfig::with_txn_fees(1, 10);
+        assert_eq!(10, config.dc_payload_size());
+        assert_eq!(3, config.payload_fee(25));
+        assert_eq!(0, TxnFeeConfig::legacy().payload_fee(25));
+    }
+
+    #[test]
+    fn fee_multiplier() {
+        let payer = Keypair::default();
+        let payee = Keypair::default();
+        let mut txn = BlockchainTxnPaymentV1 {
+            payee: payee.public_key().to_vec(),
+            payer: payer.public_key().to_vec(),
+            amount: 10_000,
+            nonce: 1,
+            fee: 0,
+            signature: vec![],
+        };
+        // A multiplier of 1 exposes the number of payload units
+        assert_txn_fee!(txn, &TxnFeeConfig::with_multiplier(1), 6);
+        assert_txn_fee!(txn, &TxnFeeConfig::with_multiplier(5000), 30_000);
+        // Legacy fees have a zero multiplier
+        assert_txn_fee!(txn, &TxnFeeConfig::legacy(), 0);
+
+        // One more byte in the amount varint crosses into the next unit
+        txn.amount = 16_384;
+        assert_txn_fee!(txn, &TxnFeeConfig::with_multiplier(1), 7);
+        assert_txn_fee!(txn, &TxnFeeConfig::with_multiplier(5000), 35_000);
+    }
+
+    #[test]
+    fn fee_multiplier_override() {
+        let payer = Keypair::default();
+        let txn = BlockchainTxnPaymentV1 {
+            payee: Keypair::default().public_key().to_vec(),
+            payer: payer.public_key().to_vec(),
+            amount: 10_000,
+            nonce: 1,
+            fee: 0,
+            signature: vec![],
+        };
+        let mut config = TxnFeeConfig::active();
+        let fee = txn.txn_fee(&config).expect("fee");
+        config.set_txn_fee_multiplier(config.txn_fee_multiplier() * 3);
+        assert_eq!(fee * 3, txn.txn_fee(&config).expect("fee"));
+        // Staking fees are not scaled by the multiplier
+        assert_eq!(
+            STAKING_FEE_ADD_GATEWAY,
+            config.staking_fee_txn_add_gateway_v1
+        );
+    }
+
+    #[test]
+    fn payment_v2_size() {
+        let payment = || Payment {
+            payee: Keypair::default().public_key().to_vec(),
+            amount: 10_000,
+        };
+        let mut txn = BlockchainTxnPaymentV2 {
+            payer: Keypair::default().public_key().to_vec(),
+            payments: vec![payment()],
+            nonce: 1,
+            fee: 0,
+            signature: vec![],
+        };
+        let size = txn.txn_size().unwrap();
+        // The fee is one DC payload unit per 24 bytes of the size
+        assert_txn_fee!(
+            txn,
+            &TxnFeeConfig::with_multiplier(1),
+            ((size + 23) / 24) as u64
+        );
+        // Neither the fee nor the signature change the size
+        txn.fee = 35_000;
+        txn.signature = vec![1; 64];
+        assert_eq!(size, txn.txn_size().unwrap());
+
+        txn.payments.push(payment());
+        assert!(txn.txn_size().unwrap() > size);
+    }
+
     #[test]
     fn payment_v1_fee() {
         let payer = Keypair::default();
@@ -349,4 +554,93 @@ mod tests {
         assert_txn_fee!(txn, &fee_config, 45_000);
         assert_txn_staking_fee!(txn, &fee_config, expected_staking_fee);
     }
+
+    #[test]
+    fn fee_details() {
+        let key = || Keypair::default().public_key().to_vec();
+        let fee_config = TxnFeeConfig::active();
+        macro_rules! assert_fee_details {
+            ($txn: expr, $staking_fee: expr) => {
+                let details = $txn.fee_details(&fee_config).unwrap();
+                assert_eq!(
+                    FeeDetails {
+                        txn_fee: $txn.txn_fee(&fee_config).unwrap(),
+                        staking_fee: $staking_fee,
+                        payload_bytes: $txn.txn_size().unwrap(),
+                        dc_payload_size: 24,
+                    },
+                    details
+                );
+            };
+        }
+
+        let payment_v1 = BlockchainTxnPaymentV1 {
+            payer: key(),
+            payee: key(),
+            amount: 10_000,
+            nonce: 1,
+            ..Default::default()
+        };
+        assert_fee_details!(payment_v1, 0);
+        let payment_v2 = BlockchainTxnPaymentV2 {
+            payer: key(),
+            payments: vec![Payment {
+                payee: key(),
+                amount: 10_000,
+            }],
+            nonce: 1,
+            ..Default::default()
+        };
+        assert_fee_details!(payment_v2, 0);
+        let token_burn = BlockchainTxnTokenBurnV1 {
+            payer: key(),
+            payee: key(),
+            amount: 10_000,
+            nonce: 1,
+            ..Default::default()
+        };
+        assert_fee_details!(token_burn, 0);
+        let stake = BlockchainTxnStakeValidatorV1 {
+            address: key(),
+            owner: key(),
+            stake: 10_000,
+            ..Default::default()
+        };
+        assert_fee_details!(stake, 0);
+        let transfer_hotspot = BlockchainTxnTransferHotspotV1 {
+            gateway: key(),
+            seller: key(),
+            buyer: key(),
+            buyer_nonce: 1,
+            ..Default::default()
+        };
+        assert_fee_details!(transfer_hotspot, 0);
+
+        let add_gateway = BlockchainTxnAddGatewayV1 {
+            owner: key(),
+            gateway: key(),
+            ..Default::default()
+        };
+        assert_fee_details!(add_gateway, STAKING_FEE_ADD_GATEWAY);
+        let assert_location = BlockchainTxnAssertLocationV1 {
+            owner: key(),
+            gateway: key(),
+            location: "8c28308280c15ff".to_string(),
+            nonce: 1,
+            ..Default::default()
+        };
+        assert_fee_details!(assert_location, STAKING_FEE_ASSERT_LOCATION);
+        let oui = BlockchainTxnOuiV1 {
+            owner: key(),
+            requested_subnet_size: 8,
+            oui: 1,
+            ..Default::default()
+        };
+        assert_fee_details!(oui, STAKING_FEE_OUI + 8 * STAKING_FEE_OUI_PER_ADDRESS);
+
+        // Legacy fees cover a single byte per DC
+        let details = payment_v2.fee_details(&TxnFeeConfig::legacy()).unwrap();
+        assert_eq!(0, details.txn_fee);
+        assert_eq!(1, details.dc_payload_size);
+    }
 }
@@ -1,5 +1,6 @@
-use crate::keypair::{Keypair, PublicKey, Verify};
+use crate::keypair::{PublicKey, Verify};
 use crate::result::Result;
+use crate::signer::Signer;
 use helium_api::{
     BlockchainTxnAddGatewayV1, BlockchainTxnAssertLocationV1, BlockchainTxnCreateHtlcV1,
     BlockchainTxnOuiV1, BlockchainTxnPaymentV1, BlockchainTxnPaymentV2, BlockchainTxnPriceOracleV1,
@@ -10,7 +11,8 @@ use helium_api::{
 };
 
 pub trait TxnSign: Message + std::clone::Clone {
-    fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>>
+    /// Signs the transaction with all signature fields cleared
+    fn sign<S: Signer + ?Sized>(&self, signer: &S) -> Result<Vec<u8>>
     where
         Self: std::marker::Sized;
     fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result;
@@ -19,12 +21,12 @@ pub trait TxnSign: Message + std::clone::Clone {
 macro_rules! impl_sign {
     ($txn_type:ty, $( $sig: ident ),+ ) => {
         impl TxnSign for $txn_type {
-            fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>> {
+            fn sign<S: Signer + ?Sized>(&self, signer: &S) -> Result<Vec<u8>> {
                 let mut buf = vec![];
                 let mut txn = self.clone();
                 $(txn.$sig = vec![];)+
                 txn.encode(& mut buf)?;
-                keypair.sign(&buf)
+                signer.sign(&buf)
             }
 
             fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result {
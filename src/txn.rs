@@ -0,0 +1,263 @@
+//! Building and signing transactions without API access. The nonce and fee
+//! configuration that commands fetch from the API are passed in by the
+//! caller instead, so nothing in this module touches the network.
+//!
+//! A [`TxnBuilder`] assembles a transaction, sets its fee and optionally
+//! signs it:
+//!
+//! ```ignore
+//! let (envelope, fee) = TxnBuilder::payment(&payees)
+//!     .nonce(nonce)
+//!     .fee_config(&fee_config)
+//!     .build_signed(&keypair)?;
+//! ```
+//!
+//! `build` returns the unsigned transaction with its fee set, which is
+//! useful to check a balance or to have it signed elsewhere.
+//! `build_unsigned` and `build_signed` return the transaction, without or
+//! with a signature, in an envelope that is ready to be submitted.
+
+use crate::{
+    keypair::PublicKey,
+    result::{bail, Result},
+    signer::Signer,
+    traits::{TxnEnvelope, TxnFee, TxnFeeConfig, TxnSign},
+};
+use helium_api::{
+    BlockchainTxn, BlockchainTxnPaymentV2, BlockchainTxnStakeValidatorV1, BlockchainTxnTokenBurnV1,
+    Payment,
+};
+
+/// A transaction that can be built by a [`TxnBuilder`]
+pub trait BuildTxn: TxnFee + TxnSign + TxnEnvelope {
+    /// Sets the account that pays for (or owns) the transaction
+    fn set_payer(&mut self, payer: &PublicKey);
+    fn fee(&self) -> u64;
+    fn set_fee(&mut self, fee: u64);
+    /// Sets the signature of the payer
+    fn set_signature(&mut self, signature: Vec<u8>);
+}
+
+macro_rules! impl_build_txn {
+    ($txn_type:ty, $payer:ident, $signature:ident) => {
+        impl BuildTxn for $txn_type {
+            fn set_payer(&mut self, payer: &PublicKey) {
+                self.$payer = payer.to_vec();
+            }
+
+            fn fee(&self) -> u64 {
+                self.fee
+            }
+
+            fn set_fee(&mut self, fee: u64) {
+                self.fee = fee;
+            }
+
+            fn set_signature(&mut self, signature: Vec<u8>) {
+                self.$signature = signature;
+            }
+        }
+    };
+}
+
+impl_build_txn!(BlockchainTxnPaymentV2, payer, signature);
+impl_build_txn!(BlockchainTxnTokenBurnV1, payer, signature);
+impl_build_txn!(BlockchainTxnStakeValidatorV1, owner, owner_signature);
+
+/// Builds a transaction and sets its fee, either from a fee configuration
+/// or explicitly given
+#[derive(Debug, Clone)]
+pub struct TxnBuilder<T> {
+    txn: T,
+    fee_config: Option<TxnFeeConfig>,
+    fee: Option<u64>,
+}
+
+impl TxnBuilder<BlockchainTxnPaymentV2> {
+    /// Starts a payment to the given payees and amounts in bones
+    pub fn payment(payees: &[(PublicKey, u64)]) -> Self {
+        Self::new(BlockchainTxnPaymentV2 {
+            payments: payees
+                .iter()
+                .map(|(payee, amount)| Payment {
+                    payee: payee.to_vec(),
+                    amount: *amount,
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.txn.nonce = nonce;
+        self
+    }
+}
+
+impl TxnBuilder<BlockchainTxnTokenBurnV1> {
+    /// Starts a burn of the given amount of bones to DC for the payee
+    pub fn token_burn(payee: &PublicKey, amount: u64, memo: u64) -> Self {
+        Self::new(BlockchainTxnTokenBurnV1 {
+            payee: payee.to_vec(),
+            amount,
+            memo,
+            ..Default::default()
+        })
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.txn.nonce = nonce;
+        self
+    }
+}
+
+impl TxnBuilder<BlockchainTxnStakeValidatorV1> {
+    /// Starts a stake of the given amount of bones for the validator with
+    /// the given address
+    pub fn stake_validator(address: &PublicKey, stake: u64) -> Self {
+        Self::new(BlockchainTxnStakeValidatorV1 {
+            address: address.to_vec(),
+            stake,
+            ..Default::default()
+        })
+    }
+}
+
+impl<T: BuildTxn> TxnBuilder<T> {
+    fn new(txn: T) -> Self {
+        Self {
+            txn,
+            fee_config: None,
+            fee: None,
+        }
+    }
+
+    /// The fee configuration to calculate the fee with
+    pub fn fee_config(mut self, fee_config: &TxnFeeConfig) -> Self {
+        self.fee_config = Some(fee_config.clone());
+        self
+    }
+
+    /// Sets the fee in DC explicitly instead of calculating it
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    /// Builds the unsigned transaction for the given payer with its fee set
+    pub fn build(self, payer: &PublicKey) -> Result<T> {
+        let mut txn = self.txn;
+        txn.set_payer(payer);
+        let fee = match (self.fee, &self.fee_config) {
+            (Some(fee), _) => fee,
+            (None, Some(fee_config)) => txn.txn_fee(fee_config)?,
+            (None, None) => bail!("A fee or fee configuration is required to build a transaction"),
+        };
+        txn.set_fee(fee);
+        Ok(txn)
+    }
+
+    /// Builds the unsigned transaction for the given payer and returns its
+    /// envelope and fee
+    pub fn build_unsigned(self, payer: &PublicKey) -> Result<(BlockchainTxn, u64)> {
+        let txn = self.build(payer)?;
+        let fee = txn.fee();
+        Ok((txn.in_envelope(), fee))
+    }
+
+    /// Builds the transaction for the public key of the given signer, signs
+    /// it and returns its envelope and fee
+    pub fn build_signed<S: Signer + ?Sized>(self, signer: &S) -> Result<(BlockchainTxn, u64)> {
+        let mut txn = self.build(signer.public_key())?;
+        let signature = txn.sign(signer)?;
+        txn.set_signature(signature);
+        let fee = txn.fee();
+        Ok((txn.in_envelope(), fee))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn offline_payment() {
+        let keypair = Keypair::default();
+        let payee = Keypair::default().public_key().clone();
+        let fee_config = TxnFeeConfig::with_txn_fees(5000, 24);
+        let (envelope, _) = TxnBuilder::payment(&[(payee.clone(), 10_000)])
+            .nonce(7)
+            .fee_config(&fee_config)
+            .build_signed(&keypair)
+            .expect("payment");
+        let txn = BlockchainTxnPaymentV2::from_envelope(&envelope).expect("payment v2");
+        assert_eq!(7, txn.nonce);
+        assert_eq!(keypair.public_key().to_vec(), txn.payer);
+        assert_eq!(payee.to_vec(), txn.payments[0].payee);
+        assert_eq!(10_000, txn.payments[0].amount);
+        assert!(txn.fee > 0);
+        assert_eq!(txn.fee, txn.txn_fee(&fee_config).expect("fee"));
+        txn.verify(keypair.public_key(), &txn.signature)
+            .expect("signature");
+    }
+
+    #[test]
+    fn builder_fees() {
+        let keypair = Keypair::default();
+        let payees = [(Keypair::default().public_key().clone(), 10_000)];
+        let fee_config = TxnFeeConfig::with_txn_fees(5000, 24);
+        let (envelope, fee) = TxnBuilder::payment(&payees)
+            .nonce(2)
+            .fee_config(&fee_config)
+            .build_signed(&keypair)
+            .expect("payment");
+        let txn = BlockchainTxnPaymentV2::from_envelope(&envelope).expect("payment v2");
+        assert_eq!(fee, txn.fee);
+        assert_eq!(txn.txn_fee(&fee_config).expect("fee"), fee);
+
+        // An explicit fee wins over the fee configuration
+        let (_, fee) = TxnBuilder::payment(&payees)
+            .fee_config(&fee_config)
+            .fee(1)
+            .build_unsigned(keypair.public_key())
+            .expect("payment");
+        assert_eq!(1, fee);
+        assert!(TxnBuilder::payment(&payees)
+            .build(keypair.public_key())
+            .is_err());
+    }
+
+    #[test]
+    fn offline_token_burn() {
+        let keypair = Keypair::default();
+        let payee = Keypair::default().public_key().clone();
+        let fee_config = TxnFeeConfig::legacy();
+        let (envelope, _) = TxnBuilder::token_burn(&payee, 10_000, 42)
+            .nonce(3)
+            .fee_config(&fee_config)
+            .build_signed(&keypair)
+            .expect("burn");
+        let txn = BlockchainTxnTokenBurnV1::from_envelope(&envelope).expect("token burn");
+        assert_eq!((10_000, 42, 3), (txn.amount, txn.memo, txn.nonce));
+        assert_eq!(txn.fee, txn.txn_fee(&fee_config).expect("fee"));
+        txn.verify(keypair.public_key(), &txn.signature)
+            .expect("signature");
+    }
+
+    #[test]
+    fn offline_stake_validator() {
+        let keypair = Keypair::default();
+        let address = Keypair::default().public_key().clone();
+        let fee_config = TxnFeeConfig::with_txn_fees(5000, 24);
+        let (envelope, _) = TxnBuilder::stake_validator(&address, 10_000 * 100_000_000)
+            .fee_config(&fee_config)
+            .build_signed(&keypair)
+            .expect("stake");
+        let txn = BlockchainTxnStakeValidatorV1::from_envelope(&envelope).expect("stake");
+        assert_eq!(address.to_vec(), txn.address);
+        assert_eq!(txn.fee, txn.txn_fee(&fee_config).expect("fee"));
+        txn.verify(keypair.public_key(), &txn.owner_signature)
+            .expect("signature");
+    }
+}
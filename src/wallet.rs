@@ -1,6 +1,6 @@
 use crate::{
     format::{self, Format},
-    keypair::{Keypair, PublicKey},
+    keypair::{keypair_len, Keypair, PublicKey},
     pwhash::PwHash,
     result::{anyhow, bail, Result},
     traits::ReadWrite,
@@ -12,6 +12,7 @@ use aes_gcm::{
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use sodiumoxide::randombytes;
 use std::io::{self, Cursor};
+use zeroize::Zeroizing;
 
 pub type Tag = [u8; 16];
 pub type Iv = [u8; 12];
@@ -19,26 +20,86 @@ pub type AesKey = [u8; 32];
 
 const WALLET_KIND_BASIC_V1: u16 = 0x0001;
 const WALLET_KIND_BASIC_V2: u16 = 0x0002;
+const WALLET_KIND_BASIC_V3: u16 = 0x0003;
 
 const WALLET_KIND_SHARDED_V1: u16 = 0x0101;
 const WALLET_KIND_SHARDED_V2: u16 = 0x0102;
+const WALLET_KIND_SHARDED_V3: u16 = 0x0103;
+
+const WALLET_KIND_WATCH_V1: u16 = 0x0201;
+
+// The high byte of a wallet kind is the wallet family and the low byte the
+// version of the file format for that family
+const WALLET_FAMILY_BASIC: u8 = 0x00;
+const WALLET_FAMILY_SHARDED: u8 = 0x01;
+const WALLET_FAMILY_WATCH: u8 = 0x02;
+
+/// The length of the seed fingerprint stored after the keypair in the
+/// encrypted key of a wallet derived from seed words for an account
+const SEED_FINGERPRINT_LEN: usize = 4;
 
 const PWHASH_KIND_PBKDF2: u8 = 0;
 const PWHASH_KIND_ARGON2ID13: u8 = 1;
 
-pub struct Wallet {
-    pub public_key: PublicKey,
+/// The encrypted private key of a wallet
+pub struct Secret {
     pub iv: Iv,
     pub tag: Tag,
     pub encrypted: Vec<u8>,
     pub format: Format,
 }
 
+/// The password of wallets created with `Wallet::in_memory`
+#[cfg(test)]
+pub const IN_MEMORY_PASSWORD: &str = "password";
+
+pub struct Wallet {
+    pub public_key: PublicKey,
+    /// The encrypted private key. Watch-only wallets only have a public key
+    /// and no secret.
+    pub secret: Option<Secret>,
+    /// The account index the key was derived for when the wallet was
+    /// created from seed words for a specific account.
+    pub account: Option<u32>,
+    /// The file format version the wallet was read with. Wallets that were
+    /// not read from a file have the version they are written with.
+    pub version: u8,
+}
+
 impl Wallet {
     pub fn encrypt(keypair: &Keypair, password: &[u8], fmt: Format) -> Result<Wallet> {
+        let mut plaintext = Zeroizing::new(vec![]);
+        keypair.write(&mut *plaintext)?;
+        Self::encrypt_bytes(keypair.public_key(), &plaintext, password, fmt)
+    }
+
+    /// Encrypts the keypair of a wallet derived from seed words for the
+    /// given account. The fingerprint of the seed is encrypted along with
+    /// the keypair, so only someone with the password can tell which seed
+    /// the wallet belongs to.
+    pub fn encrypt_account(
+        keypair: &Keypair,
+        account: u32,
+        seed_fingerprint: &[u8; SEED_FINGERPRINT_LEN],
+        password: &[u8],
+        fmt: Format,
+    ) -> Result<Wallet> {
+        let mut plaintext = Zeroizing::new(vec![]);
+        keypair.write(&mut *plaintext)?;
+        plaintext.extend_from_slice(seed_fingerprint);
+        let mut wallet = Self::encrypt_bytes(keypair.public_key(), &plaintext, password, fmt)?;
+        wallet.account = Some(account);
+        Ok(wallet)
+    }
+
+    fn encrypt_bytes(
+        public_key: &PublicKey,
+        plaintext: &[u8],
+        password: &[u8],
+        fmt: Format,
+    ) -> Result<Wallet> {
         let mut encryption_key = AesKey::default();
         let mut format = fmt;
-        let public_key = keypair.public_key();
         format.derive_key(password, &mut encryption_key)?;
 
         let mut iv = Iv::default();
@@ -46,8 +107,7 @@ impl Wallet {
 
         let aead = Aes256Gcm::new(GenericArray::from_slice(&encryption_key));
 
-        let mut encrypted = vec![];
-        keypair.write(&mut encrypted)?;
+        let mut encrypted = plaintext.to_vec();
 
         match aead.encrypt_in_place_detached(
             iv.as_ref().into(),
@@ -57,52 +117,221 @@ impl Wallet {
             Err(_) => Err(anyhow!("Failed to encrypt wallet")),
             Ok(gtag) => Ok(Wallet {
                 public_key: public_key.clone(),
-                iv,
-                tag: gtag.into(),
-                encrypted,
-                format,
+                secret: Some(Secret {
+                    iv,
+                    tag: gtag.into(),
+                    encrypted,
+                    format,
+                }),
+                account: None,
+                version: kind_version(WALLET_KIND_BASIC_V2),
             }),
         }
     }
 
-    pub fn decrypt(&self, password: &[u8]) -> Result<Keypair> {
-        let mut encryption_key = AesKey::default();
-        let mut format = self.format.clone();
-        format.derive_key(password, &mut encryption_key)?;
+    /// Creates a watch-only wallet for a given public key. A watch-only
+    /// wallet can be used to look up information for the key but can not
+    /// sign transactions.
+    pub fn watch(public_key: PublicKey) -> Wallet {
+        Wallet {
+            public_key,
+            secret: None,
+            account: None,
+            version: kind_version(WALLET_KIND_WATCH_V1),
+        }
+    }
 
-        let aead = Aes256Gcm::new(GenericArray::from_slice(&encryption_key));
-        let mut buffer = self.encrypted.to_owned();
+    /// Creates a wallet for the given keypair to drive commands in tests
+    /// without a wallet file. The wallet is encrypted with
+    /// `IN_MEMORY_PASSWORD` and a single pbkdf2 iteration to keep tests
+    /// fast.
+    #[cfg(test)]
+    pub fn in_memory(keypair: &Keypair) -> Wallet {
+        Self::encrypt(
+            keypair,
+            IN_MEMORY_PASSWORD.as_bytes(),
+            Format::basic(PwHash::pbkdf2(1)),
+        )
+        .expect("in-memory wallet")
+    }
+
+    pub fn is_watch_only(&self) -> bool {
+        self.secret.is_none()
+    }
+
+    fn secret(&self) -> Result<&Secret> {
+        self.secret
+            .as_ref()
+            .ok_or_else(|| anyhow!("watch-only wallet cannot sign"))
+    }
+
+    /// Decrypts the stored keypair bytes, which are zeroed when dropped
+    fn decrypt_keypair_bytes(&self, password: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let secret = self.secret()?;
+        let mut encryption_key = Zeroizing::new(AesKey::default());
+        let mut format = secret.format.clone();
+        format.derive_key(password, &mut encryption_key[..])?;
+
+        let aead = Aes256Gcm::new(GenericArray::from_slice(&encryption_key[..]));
+        let mut buffer = Zeroizing::new(secret.encrypted.to_owned());
         match aead.decrypt_in_place_detached(
-            self.iv.as_ref().into(),
+            secret.iv.as_ref().into(),
             &self.public_key.to_bytes(),
-            &mut buffer,
-            self.tag.as_ref().into(),
+            &mut *buffer,
+            secret.tag.as_ref().into(),
         ) {
             Err(_) => Err(anyhow!("Failed to decrypt wallet")),
             _ => Ok(()),
         }?;
-        let keypair = Keypair::read(&mut Cursor::new(buffer))?;
+        Ok(buffer)
+    }
+
+    pub fn decrypt(&self, password: &[u8]) -> Result<Keypair> {
+        let buffer = self.decrypt_keypair_bytes(password)?;
+        let keypair = Keypair::read(&mut Cursor::new(&buffer[..]))?;
         Ok(keypair)
     }
 
+    /// Decrypts the 32 bytes of entropy the private key of the wallet was
+    /// generated from. For a wallet created from seed words these are the
+    /// bytes `mnemonic_to_entropy` returned for them, or the derived account
+    /// entropy for a wallet created for a specific account. Library users
+    /// can use it to deterministically derive application specific keys
+    /// from the same seed.
+    ///
+    /// The entropy is the private key itself. Anyone who learns it, or a
+    /// key derived from it in a reversible way, controls the wallet, so it
+    /// should never be stored or sent anywhere. Only derive keys from it
+    /// with a one-way function, like HMAC-SHA256 with an application
+    /// specific label, and drop it as soon as possible. The returned bytes
+    /// are zeroed when dropped.
+    pub fn decrypt_entropy(&self, password: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+        let buffer = self.decrypt_keypair_bytes(password)?;
+        // The key type tag is followed by the secret key the keypair was
+        // generated from
+        if buffer.len() < 33 {
+            bail!("Invalid decrypted wallet key");
+        }
+        let mut entropy = Zeroizing::new([0u8; 32]);
+        entropy.copy_from_slice(&buffer[1..33]);
+        Ok(entropy)
+    }
+
+    /// Decrypts the fingerprint of the seed a wallet derived for an account
+    /// was created from. Returns None if the wallet does not store one,
+    /// like wallets created without an account index or before seed
+    /// fingerprints were stored.
+    pub fn decrypt_seed_fingerprint(
+        &self,
+        password: &[u8],
+    ) -> Result<Option<[u8; SEED_FINGERPRINT_LEN]>> {
+        let buffer = self.decrypt_keypair_bytes(password)?;
+        let keypair_len = keypair_len(&self.public_key)?;
+        match buffer.len().checked_sub(keypair_len) {
+            Some(0) => Ok(None),
+            Some(SEED_FINGERPRINT_LEN) => {
+                let mut fingerprint = [0u8; SEED_FINGERPRINT_LEN];
+                fingerprint.copy_from_slice(&buffer[keypair_len..]);
+                Ok(Some(fingerprint))
+            }
+            _ => bail!("Invalid decrypted wallet key"),
+        }
+    }
+
+    /// Encrypts the key of the wallet again under the same password with
+    /// the given format. The address, account index and stored seed
+    /// fingerprint are kept.
+    pub fn encrypt_again(&self, password: &[u8], fmt: Format) -> Result<Wallet> {
+        let plaintext = self.decrypt_keypair_bytes(password)?;
+        let mut wallet = Self::encrypt_bytes(&self.public_key, &plaintext, password, fmt)?;
+        wallet.account = self.account;
+        Ok(wallet)
+    }
+
+    /// Encrypts the key of a basic wallet again under the same password
+    /// with the given password hash, like one with a higher cost. The
+    /// address, account index and stored seed fingerprint are kept.
+    pub fn reencrypt(&self, password: &[u8], pwhash: PwHash) -> Result<Wallet> {
+        if self.is_sharded() {
+            bail!("Sharded wallets can not be re-encrypted");
+        }
+        self.encrypt_again(password, Format::basic(pwhash))
+    }
+
     pub fn address(&self) -> Result<String> {
         Ok(self.public_key.to_string())
     }
 
-    pub fn pwhash(&self) -> &PwHash {
-        self.format.pwhash()
+    /// Returns the password hash of the wallet, or None for a watch-only
+    /// wallet.
+    pub fn pwhash(&self) -> Option<&PwHash> {
+        self.secret.as_ref().map(|secret| secret.format.pwhash())
+    }
+
+    // Accessors for the fields that moved into `Secret` with watch-only
+    // wallets. They return None for a watch-only wallet.
+
+    pub fn iv(&self) -> Option<&Iv> {
+        self.secret.as_ref().map(|secret| &secret.iv)
+    }
+
+    pub fn tag(&self) -> Option<&Tag> {
+        self.secret.as_ref().map(|secret| &secret.tag)
+    }
+
+    pub fn encrypted(&self) -> Option<&[u8]> {
+        self.secret
+            .as_ref()
+            .map(|secret| secret.encrypted.as_slice())
+    }
+
+    pub fn format(&self) -> Option<&Format> {
+        self.secret.as_ref().map(|secret| &secret.format)
+    }
+
+    /// Checks the structure of the wallet without decrypting it. The
+    /// encrypted key has to be as long as a keypair for the public key of
+    /// the wallet, followed by a seed fingerprint for wallets with an
+    /// account index. A well formed but altered encrypted key or public key
+    /// is only detected by decrypting the wallet.
+    pub fn check(&self) -> Result {
+        let secret = match &self.secret {
+            Some(secret) => secret,
+            None => return Ok(()),
+        };
+        if let Format::Sharded(format) = &secret.format {
+            if format.recovery_threshold == 0 || format.recovery_threshold > format.key_share_count
+            {
+                bail!(
+                    "Invalid recovery threshold {} for {} key shares",
+                    format.recovery_threshold,
+                    format.key_share_count
+                );
+            }
+        }
+        let expected = keypair_len(&self.public_key)?;
+        let fingerprinted =
+            self.account.is_some() && secret.encrypted.len() == expected + SEED_FINGERPRINT_LEN;
+        if secret.encrypted.len() != expected && !fingerprinted {
+            bail!(
+                "Encrypted key is {} bytes, expected {} bytes",
+                secret.encrypted.len(),
+                expected
+            );
+        }
+        Ok(())
     }
 
     fn mut_sharded_format(&mut self) -> Result<&mut format::Sharded> {
-        match &mut self.format {
-            Format::Sharded(format) => Ok(format),
+        match self.secret.as_mut().map(|secret| &mut secret.format) {
+            Some(Format::Sharded(format)) => Ok(format),
             _ => Err(anyhow!("Wallet not sharded")),
         }
     }
 
     fn sharded_format(&self) -> Result<&format::Sharded> {
-        match &self.format {
-            Format::Sharded(format) => Ok(format),
+        match self.secret.as_ref().map(|secret| &secret.format) {
+            Some(Format::Sharded(format)) => Ok(format),
             _ => Err(anyhow!("Wallet not sharded")),
         }
     }
@@ -113,13 +342,18 @@ impl Wallet {
 
     pub fn shards(&self) -> Result<Vec<Wallet>> {
         let format = self.sharded_format()?;
+        let secret = self.secret()?;
         let mut wallets = vec![];
         for shard in format.shards() {
             wallets.push(Self {
-                format: Format::Sharded(shard),
-                encrypted: self.encrypted.clone(),
                 public_key: self.public_key.clone(),
-                ..*self
+                secret: Some(Secret {
+                    format: Format::Sharded(shard),
+                    encrypted: secret.encrypted.clone(),
+                    ..*secret
+                }),
+                account: self.account,
+                version: self.version,
             })
         }
         Ok(wallets)
@@ -143,13 +377,25 @@ impl Wallet {
 
     pub fn read(reader: &mut dyn io::Read) -> Result<Wallet> {
         let kind = reader.read_u16::<LittleEndian>()?;
+        check_kind(kind)?;
+        if kind == WALLET_KIND_WATCH_V1 {
+            return Ok(Self::watch(PublicKey::read(reader)?));
+        }
         let mut format = match kind {
             WALLET_KIND_BASIC_V1 => Format::basic(PwHash::pbkdf2_default()),
             WALLET_KIND_BASIC_V2 => Format::basic(Self::read_pwhash(reader)?),
             WALLET_KIND_SHARDED_V1 => Format::sharded_default(PwHash::pbkdf2_default()),
             WALLET_KIND_SHARDED_V2 => Format::sharded_default(Self::read_pwhash(reader)?),
+            WALLET_KIND_BASIC_V3 => Format::basic(Self::read_pwhash(reader)?),
+            WALLET_KIND_SHARDED_V3 => Format::sharded_default(Self::read_pwhash(reader)?),
             _ => bail!("Invalid wallet kind {}", kind),
         };
+        let account = match kind {
+            WALLET_KIND_BASIC_V3 | WALLET_KIND_SHARDED_V3 => {
+                Some(reader.read_u32::<LittleEndian>()?)
+            }
+            _ => None,
+        };
         format.read(reader)?;
         let public_key = PublicKey::read(reader)?;
         let mut iv = Iv::default();
@@ -162,10 +408,14 @@ impl Wallet {
 
         Ok(Wallet {
             public_key,
-            iv,
-            tag,
-            format,
-            encrypted,
+            secret: Some(Secret {
+                iv,
+                tag,
+                format,
+                encrypted,
+            }),
+            account,
+            version: kind_version(kind),
         })
     }
 
@@ -177,23 +427,84 @@ impl Wallet {
         Ok(())
     }
 
+    /// The wallet kind this wallet is written with
+    fn write_kind(&self) -> u16 {
+        // Wallets without an account index are written in the V2 format to
+        // keep them readable by older versions
+        match (
+            self.secret.as_ref().map(|secret| &secret.format),
+            self.account,
+        ) {
+            (None, _) => WALLET_KIND_WATCH_V1,
+            (Some(Format::Basic(_)), None) => WALLET_KIND_BASIC_V2,
+            (Some(Format::Sharded(_)), None) => WALLET_KIND_SHARDED_V2,
+            (Some(Format::Basic(_)), Some(_)) => WALLET_KIND_BASIC_V3,
+            (Some(Format::Sharded(_)), Some(_)) => WALLET_KIND_SHARDED_V3,
+        }
+    }
+
+    /// The file format version this wallet is written with
+    pub fn write_version(&self) -> u8 {
+        kind_version(self.write_kind())
+    }
+
+    /// Whether the wallet was read from an older file format version than
+    /// the one it is written with
+    pub fn needs_upgrade(&self) -> bool {
+        self.version < self.write_version()
+    }
+
     pub fn write(&self, writer: &mut dyn io::Write) -> Result {
-        let kind = match self.format {
-            Format::Basic(_) => WALLET_KIND_BASIC_V2,
-            Format::Sharded(_) => WALLET_KIND_SHARDED_V2,
-        };
+        let kind = self.write_kind();
         writer.write_u16::<LittleEndian>(kind)?;
-        Self::write_pwhash(self.format.pwhash(), writer)?;
-        self.format.write(writer)?;
+        let secret = match &self.secret {
+            Some(secret) => secret,
+            None => {
+                self.public_key.write(writer)?;
+                return Ok(());
+            }
+        };
+        Self::write_pwhash(secret.format.pwhash(), writer)?;
+        if let Some(account) = self.account {
+            writer.write_u32::<LittleEndian>(account)?;
+        }
+        secret.format.write(writer)?;
         self.public_key.write(writer)?;
-        writer.write_all(&self.iv)?;
-        self.format.pwhash().write(writer)?;
-        writer.write_all(&self.tag)?;
-        writer.write_all(&self.encrypted)?;
+        writer.write_all(&secret.iv)?;
+        secret.format.pwhash().write(writer)?;
+        writer.write_all(&secret.tag)?;
+        writer.write_all(&secret.encrypted)?;
         Ok(())
     }
 }
 
+fn kind_version(kind: u16) -> u8 {
+    (kind & 0xff) as u8
+}
+
+/// Checks that the given wallet kind is a supported one. A wallet written
+/// in a newer format version of a known family is reported as such rather
+/// than as an invalid wallet.
+fn check_kind(kind: u16) -> Result {
+    let version = kind_version(kind);
+    let latest = match (kind >> 8) as u8 {
+        WALLET_FAMILY_BASIC => WALLET_KIND_BASIC_V3,
+        WALLET_FAMILY_SHARDED => WALLET_KIND_SHARDED_V3,
+        WALLET_FAMILY_WATCH => WALLET_KIND_WATCH_V1,
+        _ => bail!("Invalid wallet kind {}", kind),
+    };
+    match version {
+        0 => bail!("Invalid wallet kind {}", kind),
+        v if v > kind_version(latest) => bail!(
+            "Unsupported wallet version {}, the latest supported version is {}. \
+             Upgrade helium-wallet to use this wallet",
+            v,
+            kind_version(latest)
+        ),
+        _ => Ok(()),
+    }
+}
+
 //
 // Test
 //
@@ -201,6 +512,7 @@ impl Wallet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::keypair::KeyTag;
 
     #[test]
     fn rountrip_basic() {
@@ -230,4 +542,226 @@ mod tests {
         let to_keypair = wallet.decrypt(password).expect("wallet to keypair");
         assert_eq!(from_keypair, to_keypair);
     }
+
+    #[test]
+    fn decrypt_entropy() {
+        let mut entropy = [0u8; 32];
+        randombytes::randombytes_into(&mut entropy);
+        let keypair = Keypair::generate_from_entropy(KeyTag::default(), &entropy).expect("keypair");
+        let format = Format::basic(PwHash::argon2id13_default());
+        let password = b"password";
+        let wallet = Wallet::encrypt(&keypair, password, format).expect("wallet creation");
+        assert_eq!(
+            entropy,
+            *wallet.decrypt_entropy(password).expect("wallet entropy")
+        );
+        assert!(wallet.decrypt_entropy(b"wrong").is_err());
+        assert!(Wallet::watch(keypair.public_key().clone())
+            .decrypt_entropy(password)
+            .is_err());
+    }
+
+    #[test]
+    fn roundtrip_account() {
+        let from_keypair = Keypair::default();
+        let format = format::Basic {
+            pwhash: PwHash::argon2id13_default(),
+        };
+        let password = b"passsword";
+        let mut wallet = Wallet::encrypt(&from_keypair, password, Format::Basic(format))
+            .expect("wallet creation");
+        wallet.account = Some(3);
+        let mut buffer = vec![];
+        wallet.write(&mut buffer).expect("wallet write");
+        let wallet = Wallet::read(&mut Cursor::new(buffer)).expect("wallet read");
+        assert_eq!(Some(3), wallet.account);
+        let to_keypair = wallet.decrypt(password).expect("wallet to keypair");
+        assert_eq!(from_keypair, to_keypair);
+    }
+
+    #[test]
+    fn roundtrip_seed_fingerprint() {
+        let keypair = Keypair::default();
+        let password = IN_MEMORY_PASSWORD.as_bytes();
+        let format = Format::sharded_default(PwHash::pbkdf2(1));
+        let wallet = Wallet::encrypt_account(&keypair, 2, &[1, 2, 3, 4], password, format)
+            .expect("wallet creation");
+        let mut buffer = vec![];
+        let mut shards = wallet.shards().expect("wallet shards");
+        shards[0].write(&mut buffer).expect("wallet write");
+        let mut read = Wallet::read(&mut Cursor::new(buffer)).expect("wallet read");
+        read.check().expect("wallet check");
+        for shard in &shards[1..3] {
+            read.absorb_shard(shard).expect("absorb shard");
+        }
+        assert_eq!(Some(2), read.account);
+        assert_eq!(keypair, read.decrypt(password).expect("decrypt"));
+        assert_eq!(
+            Some([1, 2, 3, 4]),
+            read.decrypt_seed_fingerprint(password)
+                .expect("fingerprint")
+        );
+        assert!(read.decrypt_seed_fingerprint(b"wrong").is_err());
+
+        // Encrypting again keeps the fingerprint
+        let basic = read
+            .encrypt_again(password, Format::basic(PwHash::pbkdf2(1)))
+            .expect("encrypt again");
+        assert_eq!(
+            Some([1, 2, 3, 4]),
+            basic
+                .decrypt_seed_fingerprint(password)
+                .expect("fingerprint")
+        );
+        // Wallets without an account index store none
+        assert_eq!(
+            None,
+            Wallet::in_memory(&keypair)
+                .decrypt_seed_fingerprint(password)
+                .expect("fingerprint")
+        );
+    }
+
+    #[test]
+    fn reencrypt() {
+        let keypair = Keypair::default();
+        let mut wallet = Wallet::in_memory(&keypair);
+        wallet.account = Some(2);
+        let password = IN_MEMORY_PASSWORD.as_bytes();
+        let reencrypted = wallet
+            .reencrypt(password, PwHash::pbkdf2(10))
+            .expect("reencrypt");
+        assert_eq!(
+            "iterations=10",
+            reencrypted.pwhash().expect("pwhash").params()
+        );
+        assert_eq!(Some(2), reencrypted.account);
+
+        let mut buffer = vec![];
+        reencrypted.write(&mut buffer).expect("wallet write");
+        let read = Wallet::read(&mut Cursor::new(buffer)).expect("wallet read");
+        assert_eq!("iterations=10", read.pwhash().expect("pwhash").params());
+        assert_eq!(keypair, read.decrypt(password).expect("decrypt"));
+        assert!(read.decrypt(b"wrong").is_err());
+
+        assert!(wallet.reencrypt(b"wrong", PwHash::pbkdf2(10)).is_err());
+        assert!(Wallet::watch(keypair.public_key().clone())
+            .reencrypt(password, PwHash::pbkdf2(10))
+            .is_err());
+    }
+
+    #[test]
+    fn check_structure() {
+        let format = format::Basic {
+            pwhash: PwHash::argon2id13_default(),
+        };
+        let wallet = Wallet::encrypt(&Keypair::default(), b"password", Format::Basic(format))
+            .expect("wallet creation");
+        let mut buffer = vec![];
+        wallet.write(&mut buffer).expect("wallet write");
+        let read = |buffer: &[u8]| Wallet::read(&mut Cursor::new(buffer.to_vec()));
+        read(&buffer)
+            .expect("wallet read")
+            .check()
+            .expect("wallet check");
+        // Truncated in the encrypted key
+        let wallet = read(&buffer[..buffer.len() - 1]).expect("wallet read");
+        assert!(wallet.check().is_err());
+        // Truncated before the encrypted key
+        assert!(read(&buffer[..20]).is_err());
+        // Trailing bytes
+        buffer.push(0);
+        assert!(read(&buffer).expect("wallet read").check().is_err());
+        assert!(Wallet::watch(Keypair::default().public_key().clone())
+            .check()
+            .is_ok());
+    }
+
+    #[test]
+    fn read_versions() {
+        let keypair = Keypair::default();
+        let password = b"password";
+        let format = Format::basic(PwHash::pbkdf2_default());
+        let mut wallet = Wallet::encrypt(&keypair, password, format).expect("wallet creation");
+        let read = |buffer: &[u8]| Wallet::read(&mut Cursor::new(buffer.to_vec()));
+
+        let mut v2 = vec![];
+        wallet.write(&mut v2).expect("wallet write");
+        // A V1 wallet is a V2 wallet without the pwhash kind, always pbkdf2
+        let mut v1 = WALLET_KIND_BASIC_V1.to_le_bytes().to_vec();
+        v1.extend_from_slice(&v2[3..]);
+        wallet.account = Some(1);
+        let mut v3 = vec![];
+        wallet.write(&mut v3).expect("wallet write");
+
+        for (version, buffer) in [(1, &v1), (2, &v2), (3, &v3)].iter() {
+            let wallet = read(buffer).expect("wallet read");
+            assert_eq!(*version, wallet.version);
+            assert_eq!(*version == 1, wallet.needs_upgrade());
+            assert_eq!(keypair, wallet.decrypt(password).expect("wallet decrypt"));
+        }
+
+        // Upgrading a V1 wallet preserves the key
+        let mut upgraded = vec![];
+        read(&v1)
+            .expect("wallet read")
+            .write(&mut upgraded)
+            .expect("wallet write");
+        assert_eq!(v2, upgraded);
+
+        let mut watch = vec![];
+        Wallet::watch(keypair.public_key().clone())
+            .write(&mut watch)
+            .expect("wallet write");
+        let wallet = read(&watch).expect("wallet read");
+        assert_eq!(1, wallet.version);
+        assert!(!wallet.needs_upgrade());
+
+        // A future version of a known family is reported as unsupported
+        let mut future = v2.clone();
+        future[0] = 0x04;
+        let err = read(&future).err().expect("unsupported version");
+        assert!(err.to_string().starts_with("Unsupported wallet version 4"));
+        future[1] = 0x05;
+        let err = read(&future).err().expect("invalid kind");
+        assert!(err.to_string().starts_with("Invalid wallet kind"));
+    }
+
+    #[test]
+    fn read_sharded_versions() {
+        let format = Format::sharded_default(PwHash::pbkdf2_default());
+        let wallet =
+            Wallet::encrypt(&Keypair::default(), b"password", format).expect("wallet creation");
+        let mut shard = wallet.shards().expect("wallet shards").remove(0);
+        let mut v2 = vec![];
+        shard.write(&mut v2).expect("shard write");
+        let mut v1 = WALLET_KIND_SHARDED_V1.to_le_bytes().to_vec();
+        v1.extend_from_slice(&v2[3..]);
+        shard.account = Some(1);
+        let mut v3 = vec![];
+        shard.write(&mut v3).expect("shard write");
+
+        for (version, buffer) in [(1, &v1), (2, &v2), (3, &v3)].iter() {
+            let shard = Wallet::read(&mut Cursor::new(buffer.to_vec())).expect("shard read");
+            assert_eq!(*version, shard.version);
+            assert!(shard.is_sharded());
+            shard.check().expect("shard check");
+        }
+    }
+
+    #[test]
+    fn roundtrip_watch() {
+        let keypair = Keypair::default();
+        let wallet = Wallet::watch(keypair.public_key().clone());
+        let mut buffer = vec![];
+        wallet.write(&mut buffer).expect("wallet write");
+        let wallet = Wallet::read(&mut Cursor::new(buffer)).expect("wallet read");
+        assert!(wallet.is_watch_only());
+        assert_eq!(keypair.public_key(), &wallet.public_key);
+        assert!(wallet.pwhash().is_none());
+        assert!(wallet.encrypted().is_none());
+        assert!(wallet.format().is_none());
+        assert!(!wallet.is_sharded());
+        assert!(wallet.decrypt(b"password").is_err());
+    }
 }